@@ -3,8 +3,10 @@ use std::{borrow::Cow, path::PathBuf};
 use egui::Color32;
 use serde::*;
 
-use crate::{images::RawScreenshotPair, keycodes_to_string::key_code_to_string};
-#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+use crate::{
+    images::RawScreenshotPair, keycodes_to_string::key_code_to_string, recorder::keymap::VK_LBUTTON,
+};
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default, Debug)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
@@ -15,6 +17,8 @@ pub enum MouseActionKind {
     Moved(MousePointKind),
     Button(MouseActionButton),
     Wheel(i32, Option<MousePointKind>),
+    /// Positive amounts scroll right.
+    HorizontalWheel(i32, Option<MousePointKind>),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -24,24 +28,43 @@ pub struct MouseActionButton {
     pub state: MouseActionButtonState,
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum MousePointKind {
     To(Point),
     By(Point),
+    /// Relative to the top left of the client area of the first matching window.
+    Window(WindowInfo, Point),
+    /// Relative to the top left of the monitor it was recorded on, in that monitor's pixels.
+    Monitor(MonitorInfo, Point),
 }
 
 impl MousePointKind {
-    pub fn x(self) -> i32 {
+    pub fn point(&self) -> Point {
         match self {
-            MousePointKind::To(point) => point.x,
-            MousePointKind::By(point) => point.x,
+            MousePointKind::To(point) => *point,
+            MousePointKind::By(point) => *point,
+            MousePointKind::Window(_, point) => *point,
+            MousePointKind::Monitor(_, point) => *point,
         }
     }
 
-    pub fn y(self) -> i32 {
+    pub fn x(&self) -> i32 {
+        self.point().x
+    }
+
+    pub fn y(&self) -> i32 {
+        self.point().y
+    }
+
+    /// The same kind of position with a different point.
+    pub fn with_point(&self, point: Point) -> MousePointKind {
         match self {
-            MousePointKind::To(point) => point.y,
-            MousePointKind::By(point) => point.y,
+            MousePointKind::To(_) => MousePointKind::To(point),
+            MousePointKind::By(_) => MousePointKind::By(point),
+            MousePointKind::Window(window_info, _) => {
+                MousePointKind::Window(window_info.clone(), point)
+            }
+            MousePointKind::Monitor(monitor, _) => MousePointKind::Monitor(monitor.clone(), point),
         }
     }
 }
@@ -55,9 +78,10 @@ pub enum MouseActionButtonState {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum Action {
-    Delay(u32),
+    Delay(u32, DelayJitter),
     Mouse(MouseActionKind),
-    Keyboard(i32, KeyState),
+    /// The virtual-key code, and the scan code if it was recorded.
+    Keyboard(i32, KeyState, Option<ScanCode>),
     WaitForImage(ImageInfo),
     IfImage(ImageInfo),
     WaitForPixel(PixelInfo),
@@ -68,6 +92,37 @@ pub enum Action {
     EndRepeat,
     Break,
     Play(PathBuf),
+    Launch(LaunchInfo),
+    WaitForWindow(WindowInfo),
+    ActivateWindow(WindowInfo),
+    MoveWindow(WindowInfo, WindowRect),
+    /// `{name}` is replaced with the value copied by `CopyClipboard(name)` earlier in the run, and
+    /// `{exit_code}` and `{output}` with the result of the last `Launch` that waited for exit.
+    SetClipboard(String),
+    CopyClipboard(String),
+    WaitForClipboardChange,
+    /// Regular expression the clipboard text is matched against.
+    IfClipboard(String),
+    Drag(DragInfo),
+}
+
+/// Random amount of milliseconds added to a delay during playback.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub enum DelayJitter {
+    #[default]
+    None,
+    /// Anywhere from minus to plus this many milliseconds.
+    Uniform(u32),
+    /// Normally distributed with this standard deviation.
+    Normal(u32),
+}
+
+/// Where the key is on the keyboard, which stays the same under every keyboard layout.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct ScanCode {
+    pub code: u16,
+    /// Keys sent with an 0xE0 prefix, like the arrows or right control.
+    pub extended: bool,
 }
 
 #[derive(PartialEq, Eq, Serialize, Deserialize, Clone, Copy, Debug)]
@@ -84,6 +139,8 @@ pub struct PixelInfo {
     pub search_location_width_height: (i32, i32),
     pub check_if_not_found: bool,
     pub move_mouse_if_found: bool,
+    #[serde(default)]
+    pub search_anchor: SearchAnchor,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -94,6 +151,110 @@ pub struct ImageInfo {
     pub search_location_left_top: Option<(i32, i32)>,
     pub search_location_width_height: Option<(i32, i32)>,
     pub image_similarity: f32,
+    #[serde(default)]
+    pub search_anchor: SearchAnchor,
+}
+
+/// What the search location of an image or pixel condition is measured from.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub enum SearchAnchor {
+    #[default]
+    Screen,
+    /// The top left of the client area of the first matching window. Without a search
+    /// location, the whole client area is searched.
+    Window(WindowInfo),
+    /// The center of the image most recently found by an image condition.
+    LastFoundImage,
+}
+
+/// Holds a button down while moving from one position to another. An offset `from` is
+/// relative to the mouse position before the drag, an offset `to` is relative to `from`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DragInfo {
+    pub button: i32,
+    pub from: MousePointKind,
+    pub to: MousePointKind,
+    /// In milliseconds.
+    pub duration: u32,
+    /// Points passed through on the way, as offsets from `from`. Empty for a straight line.
+    pub path: Vec<Point>,
+}
+
+impl Default for DragInfo {
+    fn default() -> Self {
+        Self {
+            button: VK_LBUTTON,
+            from: MousePointKind::To(Point { x: 0, y: 0 }),
+            to: MousePointKind::To(Point { x: 0, y: 0 }),
+            duration: 500,
+            path: vec![],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct LaunchInfo {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub working_dir: Option<PathBuf>,
+    pub wait_for_exit: bool,
+    pub timeout: Option<u32>,
+}
+
+/// Title and class are regular expressions, an empty one matches any window.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct WindowInfo {
+    pub title: String,
+    pub class: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default, Debug)]
+pub struct WindowRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl WindowRect {
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+}
+
+/// A monitor as it was set up when a position was recorded on it.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct MonitorInfo {
+    /// Device or output name, such as `\\.\DISPLAY2` or `HDMI-1`.
+    pub name: String,
+    /// In screen pixels, monitors left of or above the primary one have negative positions.
+    pub rect: WindowRect,
+    /// 1.0 at 100% scaling.
+    pub scale: f32,
+    pub primary: bool,
+}
+
+impl MonitorInfo {
+    pub fn describe(&self) -> String {
+        format!(
+            "{} ({}x{} at {}%)",
+            self.name,
+            self.rect.width,
+            self.rect.height,
+            (self.scale * 100.0).round()
+        )
+    }
+}
+
+impl WindowInfo {
+    fn describe(&self) -> String {
+        match (self.title.is_empty(), self.class.is_empty()) {
+            (false, false) => format!("Title \"{}\", Class \"{}\"", self.title, self.class),
+            (false, true) => format!("Title \"{}\"", self.title),
+            (true, false) => format!("Class \"{}\"", self.class),
+            (true, true) => "Any window".into(),
+        }
+    }
 }
 
 impl Default for ImageInfo {
@@ -105,6 +266,7 @@ impl Default for ImageInfo {
             search_location_left_top: None,
             search_location_width_height: None,
             image_similarity: 1.0,
+            search_anchor: SearchAnchor::Screen,
         }
     }
 }
@@ -112,18 +274,36 @@ impl Default for ImageInfo {
 impl Action {
     pub fn get_grid_formatted(&self) -> [String; 3] {
         match self {
-            Self::Delay(delay) => ["Delay".into(), delay.to_string(), "".into()],
+            Self::Delay(delay, jitter) => [
+                "Delay".into(),
+                delay.to_string(),
+                match jitter {
+                    DelayJitter::None => "".into(),
+                    DelayJitter::Uniform(range) => format!("± {range} ms"),
+                    DelayJitter::Normal(deviation) => format!("σ = {deviation} ms"),
+                },
+            ],
             Self::Mouse(kind) => match kind {
                 MouseActionKind::Moved(point) => {
-                    let (move_type, x, y) = match *point {
+                    let (move_type, x, y) = match point {
                         MousePointKind::To(point) => ("Moved To", point.x, point.y),
                         MousePointKind::By(point) => ("Changed By", point.x, point.y),
+                        MousePointKind::Window(_, point) => ("Moved In Window", point.x, point.y),
+                        MousePointKind::Monitor(_, point) => ("Moved On Monitor", point.x, point.y),
                     };
 
                     [
                         "Mouse".into(),
                         move_type.to_string(),
-                        format!("X = {}, Y = {}", x, y),
+                        match point {
+                            MousePointKind::Window(info, _) => {
+                                format!("X = {}, Y = {} in {}", x, y, info.describe())
+                            }
+                            MousePointKind::Monitor(monitor, _) => {
+                                format!("X = {}, Y = {} on {}", x, y, monitor.describe())
+                            }
+                            _ => format!("X = {}, Y = {}", x, y),
+                        },
                     ]
                 }
                 MouseActionKind::Button(action_button) => [
@@ -138,11 +318,20 @@ impl Action {
                         }
                     )
                     .into(),
-                    match action_button.point {
+                    match &action_button.point {
+                        Some(MousePointKind::Window(info, point)) => {
+                            format!("At X = {}, Y = {} in {}", point.x, point.y, info.describe())
+                        }
+                        Some(MousePointKind::Monitor(monitor, point)) => format!(
+                            "At X = {}, Y = {} on {}",
+                            point.x,
+                            point.y,
+                            monitor.describe()
+                        ),
                         Some(point) => {
                             let (move_type, x, y) = match point {
-                                MousePointKind::To(point) => ("At", point.x, point.y),
                                 MousePointKind::By(point) => ("Moved By", point.x, point.y),
+                                _ => ("At", point.x(), point.y()),
                             };
                             format!("{} X = {}, Y = {}", move_type, x, y)
                         }
@@ -152,8 +341,13 @@ impl Action {
                 MouseActionKind::Wheel(amount, _) => {
                     ["Mouse".into(), "Wheel".into(), (amount / 120).to_string()]
                 }
+                MouseActionKind::HorizontalWheel(amount, _) => [
+                    "Mouse".into(),
+                    "Horizontal Wheel".into(),
+                    (amount / 120).to_string(),
+                ],
             },
-            Self::Keyboard(key_code, state) => [
+            Self::Keyboard(key_code, state, _) => [
                 "Keyboard".into(),
                 format!("Key {}", key_code_to_string(*key_code)),
                 format!("{:?}", state),
@@ -224,6 +418,58 @@ impl Action {
             Self::EndRepeat => ["End Repeat".into(), "".into(), "".into()],
             Self::Break => ["Break".into(), "".into(), "".into()],
             Self::Play(path) => ["Play".into(), path.to_string_lossy().into(), "".into()],
+            Self::Launch(info) => [
+                "Launch".into(),
+                info.program
+                    .file_name()
+                    .map_or(info.program.to_string_lossy(), |name| {
+                        name.to_string_lossy()
+                    })
+                    .into(),
+                match (info.wait_for_exit, info.timeout) {
+                    (true, Some(timeout)) => format!("Wait for exit ({timeout} ms timeout)"),
+                    (true, None) => "Wait for exit".into(),
+                    (false, _) => "".into(),
+                },
+            ],
+            Self::WaitForWindow(info) => ["Wait For Window".into(), info.describe(), "".into()],
+            Self::ActivateWindow(info) => ["Activate Window".into(), info.describe(), "".into()],
+            Self::MoveWindow(info, rect) => [
+                "Move Window".into(),
+                info.describe(),
+                format!(
+                    "X = {}, Y = {}, W = {}, H = {}",
+                    rect.x, rect.y, rect.width, rect.height
+                ),
+            ],
+            Self::SetClipboard(text) => ["Set Clipboard".into(), text.clone(), "".into()],
+            Self::CopyClipboard(name) => [
+                "Copy Clipboard".into(),
+                format!("Into \"{name}\""),
+                "".into(),
+            ],
+            Self::WaitForClipboardChange => [
+                "Wait For Clipboard".into(),
+                "Until changed".into(),
+                "".into(),
+            ],
+            Self::IfClipboard(regex) => [
+                "If Clipboard".into(),
+                format!("Matches \"{regex}\""),
+                "".into(),
+            ],
+            Self::Drag(info) => [
+                "Mouse".into(),
+                format!("Drag {}", key_code_to_string(info.button)),
+                format!(
+                    "X = {}, Y = {} to X = {}, Y = {} in {} ms",
+                    info.from.x(),
+                    info.from.y(),
+                    info.to.x(),
+                    info.to.y(),
+                    info.duration
+                ),
+            ],
         }
     }
 }
@@ -0,0 +1,11 @@
+//! Text on the system clipboard.
+
+#[cfg(windows)]
+mod win32;
+#[cfg(target_os = "linux")]
+mod x11;
+
+#[cfg(windows)]
+pub use win32::*;
+#[cfg(target_os = "linux")]
+pub use x11::*;
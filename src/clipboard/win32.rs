@@ -0,0 +1,89 @@
+use std::{error::Error, ptr::null_mut};
+
+use winapi::um::{
+    winbase::{GlobalAlloc, GlobalFree, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE},
+    winuser::*,
+};
+
+/// Keeps the clipboard open until dropped, it has to be closed again for other programs to use it.
+struct OpenedClipboard;
+
+impl OpenedClipboard {
+    fn open() -> Result<Self, Box<dyn Error>> {
+        if unsafe { OpenClipboard(null_mut()) } == 0 {
+            return Err("Failed to open the clipboard".into());
+        }
+
+        Ok(Self)
+    }
+}
+
+impl Drop for OpenedClipboard {
+    fn drop(&mut self) {
+        unsafe { CloseClipboard() };
+    }
+}
+
+/// The text on the clipboard, empty if it holds something else.
+pub fn get_text() -> Result<String, Box<dyn Error>> {
+    let _clipboard = OpenedClipboard::open()?;
+
+    unsafe {
+        let handle = GetClipboardData(CF_UNICODETEXT);
+        if handle.is_null() {
+            return Ok(String::new());
+        }
+
+        let data = GlobalLock(handle) as *const u16;
+        if data.is_null() {
+            return Err("Failed to read the clipboard".into());
+        }
+
+        let max_length = GlobalSize(handle) / 2;
+        let length = (0..max_length)
+            .position(|index| *data.add(index) == 0)
+            .unwrap_or(max_length);
+        let text = String::from_utf16_lossy(std::slice::from_raw_parts(data, length));
+
+        GlobalUnlock(handle);
+
+        Ok(text)
+    }
+}
+
+pub fn set_text(text: &str) -> Result<(), Box<dyn Error>> {
+    let mut wide = text.encode_utf16().collect::<Vec<_>>();
+    wide.push(0);
+
+    let _clipboard = OpenedClipboard::open()?;
+
+    unsafe {
+        EmptyClipboard();
+
+        let handle = GlobalAlloc(GMEM_MOVEABLE, wide.len() * 2);
+        if handle.is_null() {
+            return Err("Failed to allocate clipboard memory".into());
+        }
+
+        let data = GlobalLock(handle) as *mut u16;
+        if data.is_null() {
+            GlobalFree(handle);
+            return Err("Failed to write to clipboard memory".into());
+        }
+        std::ptr::copy_nonoverlapping(wide.as_ptr(), data, wide.len());
+        GlobalUnlock(handle);
+
+        // The clipboard only owns the memory once it's been set
+        if SetClipboardData(CF_UNICODETEXT, handle).is_null() {
+            GlobalFree(handle);
+            return Err("Failed to set the clipboard".into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Changes every time the clipboard content changes.
+pub fn sequence_number() -> u32 {
+    unsafe { GetClipboardSequenceNumber() }
+}
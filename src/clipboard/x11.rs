@@ -0,0 +1,48 @@
+//! X11 clipboards belong to the program that set them, so the text is handed to `xclip` which
+//! keeps serving it after playback moves on.
+
+use std::{
+    error::Error,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// The text on the clipboard, empty if it holds something else.
+pub fn get_text() -> Result<String, Box<dyn Error>> {
+    let output = Command::new("xclip")
+        .args(["-selection", "clipboard", "-out"])
+        .output()?;
+
+    // xclip fails when the clipboard is empty or holds something other than text
+    if !output.status.success() {
+        return Ok(String::new());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub fn set_text(text: &str) -> Result<(), Box<dyn Error>> {
+    let mut child = Command::new("xclip")
+        .args(["-selection", "clipboard", "-in"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()?;
+
+    child
+        .stdin
+        .take()
+        .ok_or("Failed to write to xclip")?
+        .write_all(text.as_bytes())?;
+
+    if !child.wait()?.success() {
+        return Err("xclip failed to set the clipboard".into());
+    }
+
+    Ok(())
+}
+
+/// Changes every time the clipboard content changes. X11 doesn't count changes, so this is a
+/// checksum of the text, setting the same text again isn't noticed.
+pub fn sequence_number() -> u32 {
+    crc32fast::hash(get_text().unwrap_or_default().as_bytes())
+}
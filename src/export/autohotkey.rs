@@ -0,0 +1,919 @@
+//! AutoHotkey v2 scripts. Windows are matched by regular expression like during playback, but
+//! images are compared by how far each pixel's color is off instead of by similarity, so the
+//! similarity is turned into a roughly matching color variation.
+
+use std::path::Path;
+
+use super::{describe, parts, Export, Exporter, Part};
+use crate::{actions::*, keys::Key, recorder::keymap::*};
+
+const INDENT: &str = "    ";
+
+/// AutoHotkey's names for keys that aren't letters, digits or function keys. Keys without a name
+/// are sent by their virtual key code.
+const KEY_NAMES: [(i32, &str); 53] = [
+    (VK_LBUTTON, "LButton"),
+    (VK_RBUTTON, "RButton"),
+    (VK_MBUTTON, "MButton"),
+    (VK_XBUTTON1, "XButton1"),
+    (VK_XBUTTON2, "XButton2"),
+    (VK_BACK, "Backspace"),
+    (VK_TAB, "Tab"),
+    (VK_RETURN, "Enter"),
+    (VK_SHIFT, "Shift"),
+    (VK_CONTROL, "Ctrl"),
+    (VK_MENU, "Alt"),
+    (VK_PAUSE, "Pause"),
+    (VK_CAPITAL, "CapsLock"),
+    (VK_ESCAPE, "Escape"),
+    (VK_SPACE, "Space"),
+    (VK_PRIOR, "PgUp"),
+    (VK_NEXT, "PgDn"),
+    (VK_END, "End"),
+    (VK_HOME, "Home"),
+    (VK_LEFT, "Left"),
+    (VK_UP, "Up"),
+    (VK_RIGHT, "Right"),
+    (VK_DOWN, "Down"),
+    (VK_SNAPSHOT, "PrintScreen"),
+    (VK_INSERT, "Insert"),
+    (VK_DELETE, "Delete"),
+    (VK_LWIN, "LWin"),
+    (VK_RWIN, "RWin"),
+    (VK_APPS, "AppsKey"),
+    (VK_SLEEP, "Sleep"),
+    (VK_NUMPAD0, "Numpad0"),
+    (VK_NUMPAD1, "Numpad1"),
+    (VK_NUMPAD2, "Numpad2"),
+    (VK_NUMPAD3, "Numpad3"),
+    (VK_NUMPAD4, "Numpad4"),
+    (VK_NUMPAD5, "Numpad5"),
+    (VK_NUMPAD6, "Numpad6"),
+    (VK_NUMPAD7, "Numpad7"),
+    (VK_NUMPAD8, "Numpad8"),
+    (VK_NUMPAD9, "Numpad9"),
+    (VK_MULTIPLY, "NumpadMult"),
+    (VK_ADD, "NumpadAdd"),
+    (VK_SUBTRACT, "NumpadSub"),
+    (VK_DECIMAL, "NumpadDot"),
+    (VK_DIVIDE, "NumpadDiv"),
+    (VK_NUMLOCK, "NumLock"),
+    (VK_SCROLL, "ScrollLock"),
+    (VK_LSHIFT, "LShift"),
+    (VK_RSHIFT, "RShift"),
+    (VK_LCONTROL, "LCtrl"),
+    (VK_RCONTROL, "RCtrl"),
+    (VK_LMENU, "LAlt"),
+    (VK_RMENU, "RAlt"),
+];
+
+/// Polling interval of waits, in milliseconds.
+const WAIT_INTERVAL: u32 = 100;
+
+pub struct AutoHotkeyExporter;
+
+impl Exporter for AutoHotkeyExporter {
+    fn name(&self) -> &'static str {
+        "AutoHotkey script"
+    }
+
+    fn extension(&self) -> &'static str {
+        "ahk"
+    }
+
+    fn export<'a>(&self, actions: &'a [Action], image_prefix: &str) -> Export<'a> {
+        export_autohotkey(actions, image_prefix)
+    }
+}
+
+/// Writes the actions as an AutoHotkey v2 script. Screenshots are named after the prefix and
+/// their number, and looked for next to the script.
+pub fn export_autohotkey<'a>(actions: &'a [Action], image_prefix: &str) -> Export<'a> {
+    let mut script = AutoHotkey {
+        export: Export {
+            text: String::new(),
+            images: vec![],
+            warnings: vec![],
+        },
+        depth: 0,
+        image_prefix,
+        tracks_images: actions.iter().any(|action| {
+            matches!(
+                action,
+                Action::WaitForImage(ImageInfo {
+                    search_anchor: SearchAnchor::LastFoundImage,
+                    ..
+                }) | Action::IfImage(ImageInfo {
+                    search_anchor: SearchAnchor::LastFoundImage,
+                    ..
+                }) | Action::WaitForPixel(PixelInfo {
+                    search_anchor: SearchAnchor::LastFoundImage,
+                    ..
+                }) | Action::IfPixel(PixelInfo {
+                    search_anchor: SearchAnchor::LastFoundImage,
+                    ..
+                })
+            )
+        }),
+        copied: vec![],
+        warned_monitors: vec![],
+    };
+
+    script.line("; Exported from Fluffy Macro Recorder");
+    script.line("#Requires AutoHotkey v2.0");
+    script.line("SetTitleMatchMode \"RegEx\"");
+    script.line("CoordMode \"Mouse\", \"Screen\"");
+    script.line("CoordMode \"Pixel\", \"Screen\"");
+    script.line("");
+
+    for (index, (action, part)) in actions.iter().zip(parts(actions)).enumerate() {
+        match part {
+            Part::Open => script.block(index, action),
+            Part::Else => {
+                script.depth -= 1;
+                script.line("} else {");
+                script.depth += 1;
+            }
+            Part::Close => {
+                script.depth -= 1;
+                script.line("}");
+            }
+            Part::Action => script.action(index, action),
+            Part::LeftOut(reason) => script.leave_out(index, action, reason),
+        }
+    }
+
+    script.export
+}
+
+struct AutoHotkey<'a, 'b> {
+    export: Export<'a>,
+    depth: usize,
+    image_prefix: &'b str,
+    /// Whether the center of the last found image is kept for searches relative to it.
+    tracks_images: bool,
+    /// Names of clipboard values copied so far.
+    copied: Vec<&'a str>,
+    warned_monitors: Vec<&'a str>,
+}
+
+impl<'a, 'b> AutoHotkey<'a, 'b> {
+    fn line(&mut self, line: &str) {
+        if !line.is_empty() {
+            for _ in 0..self.depth {
+                self.export.text.push_str(INDENT);
+            }
+        }
+        self.export.text.push_str(line);
+        self.export.text.push('\n');
+    }
+
+    fn leave_out(&mut self, index: usize, action: &Action, reason: &str) {
+        self.export.warn(index, format!("{}, left out", reason));
+        self.line(&format!("; Left out: {}", describe(action)));
+    }
+
+    /// Opens the block of an if or repeat.
+    fn block(&mut self, index: usize, action: &'a Action) {
+        match action {
+            Action::IfImage(info) => {
+                let Some((search, center)) = self.image_search(index, info) else {
+                    self.line(if info.check_if_not_found {
+                        "if true {"
+                    } else {
+                        "if false {"
+                    });
+                    self.depth += 1;
+                    return;
+                };
+
+                self.line(&format!("found := {}", search));
+                self.track_image("if found", &center);
+                let (x, y) = center;
+                self.line(if info.check_if_not_found {
+                    "if !found {"
+                } else {
+                    "if found {"
+                });
+                self.depth += 1;
+                if info.move_mouse_if_found && !info.check_if_not_found {
+                    self.line(&format!("MouseMove {}, {}, 0", x, y));
+                }
+            }
+            Action::IfPixel(info) => {
+                let search = self.pixel_search(info);
+
+                self.line(&format!("found := {}", search));
+                self.line(if info.check_if_not_found {
+                    "if !found {"
+                } else {
+                    "if found {"
+                });
+                self.depth += 1;
+                if info.move_mouse_if_found && !info.check_if_not_found {
+                    self.line("MouseMove foundX, foundY, 0");
+                }
+            }
+            Action::IfClipboard(regex) => {
+                self.line(&format!(
+                    "if RegExMatch(A_Clipboard, {}) {{",
+                    ahk_string(regex)
+                ));
+                self.depth += 1;
+            }
+            Action::Repeat(0) => {
+                self.line("Loop {");
+                self.depth += 1;
+            }
+            Action::Repeat(amount) => {
+                self.line(&format!("Loop {} {{", amount));
+                self.depth += 1;
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn action(&mut self, index: usize, action: &'a Action) {
+        match action {
+            Action::Delay(delay, jitter) => self.line(&match jitter {
+                DelayJitter::None => format!("Sleep {}", delay),
+                DelayJitter::Uniform(range) => {
+                    format!("Sleep Max(0, {} + Random(-{}, {}))", delay, range, range)
+                }
+                // Box-Muller, AutoHotkey only has uniform random numbers
+                DelayJitter::Normal(deviation) => format!(
+                    "Sleep Max(0, Round({} + {} * Sqrt(-2 * Ln(Random(0.0001, 1.0))) * Cos(6.2832 * Random(0.0, 1.0))))",
+                    delay, deviation
+                ),
+            }),
+            Action::Mouse(kind) => match kind {
+                MouseActionKind::Moved(point) => {
+                    let (x, y, relative) = self.position(index, point);
+                    self.line(&if relative {
+                        format!("MouseMove {}, {}, 0, \"R\"", x, y)
+                    } else {
+                        format!("MouseMove {}, {}, 0", x, y)
+                    });
+                }
+                MouseActionKind::Button(action_button) => {
+                    let Some(button) = click_button(action_button.button) else {
+                        return self.leave_out(index, action, "AutoHotkey can't click this button");
+                    };
+                    let state = match action_button.state {
+                        MouseActionButtonState::Pressed => Some("\"Down\""),
+                        MouseActionButtonState::Released => Some("\"Up\""),
+                        MouseActionButtonState::Clicked => None,
+                    };
+
+                    self.click(index, &action_button.point, button, state);
+                }
+                MouseActionKind::Wheel(amount, point) => {
+                    let direction = if *amount > 0 { "WheelUp" } else { "WheelDown" };
+                    self.wheel(index, *amount, point, direction);
+                }
+                MouseActionKind::HorizontalWheel(amount, point) => {
+                    let direction = if *amount > 0 {
+                        "WheelRight"
+                    } else {
+                        "WheelLeft"
+                    };
+                    self.wheel(index, *amount, point, direction);
+                }
+            },
+            Action::Keyboard(key_code, state, _) => {
+                let key = key_name(*key_code);
+                self.line(&match state {
+                    KeyState::Down => format!("Send \"{{{} down}}\"", key),
+                    KeyState::Up => format!("Send \"{{{} up}}\"", key),
+                    KeyState::Pressed => format!("Send \"{{{}}}\"", key),
+                });
+            }
+            Action::WaitForImage(info) => {
+                let Some((search, center)) = self.image_search(index, info) else {
+                    return;
+                };
+
+                self.wait_while(&if info.check_if_not_found {
+                    search
+                } else {
+                    format!("!{}", search)
+                });
+                if !info.check_if_not_found {
+                    self.track_image("", &center);
+                    if info.move_mouse_if_found {
+                        let (x, y) = center;
+                        self.line(&format!("MouseMove {}, {}, 0", x, y));
+                    }
+                }
+            }
+            Action::WaitForPixel(info) => {
+                let search = self.pixel_search(info);
+
+                self.wait_while(&if info.check_if_not_found {
+                    search
+                } else {
+                    format!("!{}", search)
+                });
+                if info.move_mouse_if_found && !info.check_if_not_found {
+                    self.line("MouseMove foundX, foundY, 0");
+                }
+            }
+            Action::Break => self.line("break"),
+            Action::Play(path) => self.leave_out(
+                index,
+                action,
+                &format!(
+                    "Playing another macro can't be exported, export {} on its own and launch it",
+                    path.display()
+                ),
+            ),
+            Action::Launch(info) => {
+                let command = ahk_string(&command_line(&info.program, &info.args));
+                let working_dir = info
+                    .working_dir
+                    .as_ref()
+                    .map(|dir| ahk_string(&dir.to_string_lossy()));
+
+                match (info.wait_for_exit, info.timeout) {
+                    (true, Some(timeout)) => {
+                        self.line(&format!(
+                            "Run {}, {}, , &pid",
+                            command,
+                            working_dir.unwrap_or_default()
+                        ));
+                        self.line(&format!(
+                            "ProcessWaitClose pid, {}",
+                            timeout as f64 / 1000.0
+                        ));
+                    }
+                    (wait, _) => {
+                        let run = if wait { "RunWait" } else { "Run" };
+                        self.line(&match working_dir {
+                            Some(working_dir) => format!("{} {}, {}", run, command, working_dir),
+                            None => format!("{} {}", run, command),
+                        });
+                    }
+                }
+            }
+            Action::WaitForWindow(info) => self.line(&format!("WinWait {}", window_title(info))),
+            Action::ActivateWindow(info) => {
+                self.line(&format!("WinActivate {}", window_title(info)))
+            }
+            Action::MoveWindow(info, rect) => self.line(&format!(
+                "WinMove {}, {}, {}, {}, {}",
+                rect.x,
+                rect.y,
+                rect.width,
+                rect.height,
+                window_title(info)
+            )),
+            Action::SetClipboard(text) => {
+                let text = self.clipboard_text(text);
+                self.line(&format!("A_Clipboard := {}", text));
+            }
+            Action::CopyClipboard(name) => {
+                self.line(&format!("{} := A_Clipboard", copied_variable(name)));
+                self.copied.push(name);
+            }
+            Action::WaitForClipboardChange => {
+                self.line("clipboardBefore := A_Clipboard");
+                self.wait_while("A_Clipboard == clipboardBefore");
+            }
+            Action::Drag(info) => self.drag(index, action, info),
+            Action::IfImage(_)
+            | Action::IfPixel(_)
+            | Action::IfClipboard(_)
+            | Action::Repeat(_)
+            | Action::Else
+            | Action::EndIf
+            | Action::EndRepeat => unreachable!(),
+        }
+    }
+
+    fn wait_while(&mut self, condition: &str) {
+        self.line(&format!("while {}", condition));
+        self.depth += 1;
+        self.line(&format!("Sleep {}", WAIT_INTERVAL));
+        self.depth -= 1;
+    }
+
+    fn click(
+        &mut self,
+        index: usize,
+        point: &'a Option<MousePointKind>,
+        button: &str,
+        state: Option<&str>,
+    ) {
+        let mut options = vec![];
+        let mut relative = false;
+
+        if let Some(point) = point {
+            let (x, y, by) = self.position(index, point);
+            options.push(x);
+            options.push(y);
+            relative = by;
+        }
+        options.push(format!("\"{}\"", button));
+        if let Some(state) = state {
+            options.push(state.to_string());
+        }
+        if relative {
+            options.push("\"Rel\"".into());
+        }
+
+        self.line(&format!("Click {}", options.join(", ")));
+    }
+
+    fn wheel(
+        &mut self,
+        index: usize,
+        amount: i32,
+        point: &'a Option<MousePointKind>,
+        direction: &str,
+    ) {
+        // Windows counts 120 to a notch, AutoHotkey only scrolls whole notches
+        let notches = (amount.abs() as f64 / 120.0).round() as i32;
+
+        if amount % 120 != 0 {
+            self.export.warn(
+                index,
+                format!(
+                    "Scrolling {} is rounded to {} whole notches",
+                    amount, notches
+                ),
+            );
+        }
+        if notches == 0 {
+            return;
+        }
+
+        let mut options = vec![];
+        let mut relative = false;
+
+        if let Some(point) = point {
+            let (x, y, by) = self.position(index, point);
+            options.push(x);
+            options.push(y);
+            relative = by;
+        }
+        options.push(format!("\"{}\"", direction));
+        options.push(notches.to_string());
+        if relative {
+            options.push("\"Rel\"".into());
+        }
+
+        self.line(&format!("Click {}", options.join(", ")));
+    }
+
+    fn drag(&mut self, index: usize, action: &Action, info: &'a DragInfo) {
+        let Some(button) = click_button(info.button) else {
+            return self.leave_out(index, action, "AutoHotkey can't drag with this button");
+        };
+
+        let (x, y, relative) = self.position(index, &info.from);
+        self.line(&if relative {
+            format!("MouseMove {}, {}, 0, \"R\"", x, y)
+        } else {
+            format!("MouseMove {}, {}, 0", x, y)
+        });
+        self.line(&format!("Click \"{}\", \"Down\"", button));
+        self.line("MouseGetPos &dragX, &dragY");
+
+        // Playback moves smoothly, here every point along the way gets the same share of time
+        let step = info.duration / (info.path.len() as u32 + 1);
+
+        for point in &info.path {
+            self.line(&format!("Sleep {}", step));
+            self.line(&format!(
+                "MouseMove {}, {}, 0",
+                offset("dragX", point.x),
+                offset("dragY", point.y)
+            ));
+        }
+
+        self.line(&format!("Sleep {}", step));
+        let (x, y) = match &info.to {
+            MousePointKind::By(point) => (offset("dragX", point.x), offset("dragY", point.y)),
+            to => {
+                let (x, y, _) = self.position(index, to);
+                (x, y)
+            }
+        };
+        self.line(&format!("MouseMove {}, {}, 0", x, y));
+        self.line(&format!("Click \"{}\", \"Up\"", button));
+    }
+
+    /// Expressions for the screen position of the point, and whether they're an offset from the
+    /// cursor instead.
+    fn position(&mut self, index: usize, point: &'a MousePointKind) -> (String, String, bool) {
+        match point {
+            MousePointKind::To(point) => (point.x.to_string(), point.y.to_string(), false),
+            MousePointKind::By(point) => (point.x.to_string(), point.y.to_string(), true),
+            MousePointKind::Window(info, point) => {
+                self.line(&format!(
+                    "WinGetClientPos &windowX, &windowY, , , {}",
+                    window_title(info)
+                ));
+                (
+                    offset("windowX", point.x),
+                    offset("windowY", point.y),
+                    false,
+                )
+            }
+            MousePointKind::Monitor(monitor, point) => {
+                if !self.warned_monitors.contains(&monitor.name.as_str()) {
+                    self.warned_monitors.push(&monitor.name);
+                    self.export.warn(
+                        index,
+                        format!(
+                            "Positions on monitor {} are exported where they were recorded",
+                            monitor.describe()
+                        ),
+                    );
+                }
+
+                (
+                    (monitor.rect.x + point.x).to_string(),
+                    (monitor.rect.y + point.y).to_string(),
+                    false,
+                )
+            }
+        }
+    }
+
+    /// The corners of the area to search, as `X1, Y1, X2, Y2`.
+    fn search_area(
+        &mut self,
+        left_top: Option<(i32, i32)>,
+        width_height: Option<(i32, i32)>,
+        anchor: &SearchAnchor,
+    ) -> String {
+        match (left_top, width_height, anchor) {
+            (Some((x, y)), Some((width, height)), anchor) => {
+                let (origin_x, origin_y) = match anchor {
+                    SearchAnchor::Screen => ("", ""),
+                    SearchAnchor::Window(info) => {
+                        self.line(&format!(
+                            "WinGetClientPos &windowX, &windowY, , , {}",
+                            window_title(info)
+                        ));
+                        ("windowX", "windowY")
+                    }
+                    SearchAnchor::LastFoundImage => ("imageX", "imageY"),
+                };
+
+                format!(
+                    "{}, {}, {}, {}",
+                    offset(origin_x, x),
+                    offset(origin_y, y),
+                    offset(origin_x, x + width),
+                    offset(origin_y, y + height)
+                )
+            }
+            (_, _, SearchAnchor::Window(info)) => {
+                self.line(&format!(
+                    "WinGetClientPos &windowX, &windowY, &windowWidth, &windowHeight, {}",
+                    window_title(info)
+                ));
+                "windowX, windowY, windowX + windowWidth, windowY + windowHeight".into()
+            }
+            _ => "0, 0, A_ScreenWidth, A_ScreenHeight".into(),
+        }
+    }
+
+    /// The call searching for the image and where the center of a found image is, None if the
+    /// action has no image.
+    fn image_search(
+        &mut self,
+        index: usize,
+        info: &'a ImageInfo,
+    ) -> Option<(String, (String, String))> {
+        let Some(screenshot) = &info.screenshot_raw else {
+            self.export
+                .warn(index, "There's no image to search for, it's never found");
+            return None;
+        };
+        let screenshot = &screenshot.color;
+
+        let name = format!("{}{}.png", self.image_prefix, self.export.images.len() + 1);
+        self.export.images.push((name.clone(), screenshot));
+
+        let area = self.search_area(
+            info.search_location_left_top,
+            info.search_location_width_height,
+            &info.search_anchor,
+        );
+        // Shades each color can be off by, out of 255
+        let variation = ((1.0 - info.image_similarity.clamp(0.0, 1.0)) * 255.0).round();
+        let options = if variation > 0.0 {
+            format!("\"*{} \" ", variation)
+        } else {
+            String::new()
+        };
+
+        Some((
+            format!(
+                "ImageSearch(&foundX, &foundY, {}, {}A_ScriptDir {})",
+                area,
+                options,
+                ahk_string(&format!("\\{}", name))
+            ),
+            (
+                format!("foundX + {}", screenshot.width / 2),
+                format!("foundY + {}", screenshot.height / 2),
+            ),
+        ))
+    }
+
+    fn pixel_search(&mut self, info: &PixelInfo) -> String {
+        let (red, green, blue) = info.color;
+        let area = self.search_area(
+            Some(info.search_location_left_top),
+            Some(info.search_location_width_height),
+            &info.search_anchor,
+        );
+
+        format!(
+            "PixelSearch(&foundX, &foundY, {}, 0x{:02X}{:02X}{:02X})",
+            area, red, green, blue
+        )
+    }
+
+    /// Keeps where the image was found for searches relative to it, under the condition if
+    /// there is one.
+    fn track_image(&mut self, condition: &str, (x, y): &(String, String)) {
+        if !self.tracks_images {
+            return;
+        }
+
+        let line = format!("imageX := {}, imageY := {}", x, y);
+        if condition.is_empty() {
+            self.line(&line);
+        } else {
+            self.line(condition);
+            self.depth += 1;
+            self.line(&line);
+            self.depth -= 1;
+        }
+    }
+
+    /// The text with `{name}` replaced by values copied earlier.
+    fn clipboard_text(&self, text: &str) -> String {
+        let mut parts = vec![];
+        let mut literal = String::new();
+        let mut rest = text;
+
+        'text: while let Some(c) = rest.chars().next() {
+            for name in &self.copied {
+                let placeholder = format!("{{{}}}", name);
+
+                if let Some(after) = rest.strip_prefix(&placeholder) {
+                    if !literal.is_empty() {
+                        parts.push(ahk_string(&literal));
+                        literal.clear();
+                    }
+                    parts.push(copied_variable(name));
+                    rest = after;
+                    continue 'text;
+                }
+            }
+
+            literal.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+
+        if !literal.is_empty() || parts.is_empty() {
+            parts.push(ahk_string(&literal));
+        }
+
+        parts.join(" ")
+    }
+}
+
+/// The name AutoHotkey's `Send` knows the key by.
+pub fn key_name(key_code: i32) -> String {
+    if let Some((_, name)) = KEY_NAMES.iter().find(|(code, _)| *code == key_code) {
+        return name.to_string();
+    }
+
+    match key_code {
+        0x30..=0x39 | 0x41..=0x5A => (key_code as u8 as char).to_ascii_lowercase().to_string(),
+        VK_F1..=VK_F24 => format!("F{}", key_code - VK_F1 + 1),
+        _ => format!("vk{:02X}", key_code),
+    }
+}
+
+/// The key code of a name `Send` knows, ignoring case. Names it shares with other tools, such as
+/// letters, digits, function keys or "Esc", come from `Key::from_name`.
+pub fn key_code(name: &str) -> Option<i32> {
+    if let Some((code, _)) = KEY_NAMES
+        .iter()
+        .find(|(_, key)| key.eq_ignore_ascii_case(name))
+    {
+        return Some(*code);
+    }
+
+    match name.to_ascii_uppercase().strip_prefix("VK") {
+        Some(hex) if !hex.is_empty() => i32::from_str_radix(hex, 16).ok(),
+        _ => Key::from_name(name).map(Key::to_vk),
+    }
+}
+
+fn click_button(button: i32) -> Option<&'static str> {
+    Some(match button {
+        VK_LBUTTON => "Left",
+        VK_RBUTTON => "Right",
+        VK_MBUTTON => "Middle",
+        VK_XBUTTON1 => "X1",
+        VK_XBUTTON2 => "X2",
+        _ => return None,
+    })
+}
+
+/// `origin + amount`, just the amount without an origin.
+fn offset(origin: &str, amount: i32) -> String {
+    match (origin.is_empty(), amount) {
+        (true, amount) => amount.to_string(),
+        (false, 0) => origin.to_string(),
+        (false, amount) if amount < 0 => format!("{} - {}", origin, -amount),
+        (false, amount) => format!("{} + {}", origin, amount),
+    }
+}
+
+/// WinTitle matching the window, with the title match mode set to regular expressions.
+fn window_title(info: &WindowInfo) -> String {
+    let mut title = info.title.clone();
+
+    if !info.class.is_empty() {
+        if !title.is_empty() {
+            title.push(' ');
+        }
+        title += "ahk_class ";
+        title += &info.class;
+    }
+    if title.is_empty() {
+        title = ".*".into();
+    }
+
+    ahk_string(&title)
+}
+
+fn copied_variable(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+
+    format!("copied_{}", name)
+}
+
+/// The program and arguments as a Windows command line.
+fn command_line(program: &Path, args: &[String]) -> String {
+    std::iter::once(program.to_string_lossy().into_owned())
+        .chain(args.iter().cloned())
+        .map(|arg| {
+            if arg.is_empty() || arg.contains([' ', '\t', '"']) {
+                format!("\"{}\"", arg.replace('"', "\\\""))
+            } else {
+                arg
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn ahk_string(text: &str) -> String {
+    let mut quoted = String::from('"');
+
+    for c in text.chars() {
+        match c {
+            '`' => quoted += "``",
+            '"' => quoted += "`\"",
+            '\n' => quoted += "`n",
+            '\r' => quoted += "`r",
+            '\t' => quoted += "`t",
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::{body, input_and_blocks};
+
+    #[test]
+    fn writes_version_2_syntax() {
+        let actions = input_and_blocks();
+        let export = export_autohotkey(&actions, "image");
+
+        assert!(export.text.contains("#Requires AutoHotkey v2.0"));
+        assert_eq!(
+            body(&export),
+            [
+                "Loop 3 {",
+                "    Click 100, 200, \"Right\"",
+                "    Send \"{LShift down}\"",
+                "    Send \"{a}\"",
+                "    Send \"{Enter}\"",
+                "    Send \"{LShift up}\"",
+                "    Sleep 250",
+                "    if RegExMatch(A_Clipboard, \"^done$\") {",
+                "        break",
+                "    } else {",
+                "    }",
+                "}",
+                "MouseMove -5, 10, 0, \"R\"",
+                "Click \"WheelDown\", 2",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn sleeps_in_milliseconds_with_jitter() {
+        let actions = [
+            Action::Delay(250, DelayJitter::Uniform(50)),
+            Action::Delay(250, DelayJitter::Normal(20)),
+        ];
+        let export = export_autohotkey(&actions, "image");
+
+        assert_eq!(
+            body(&export),
+            [
+                "Sleep Max(0, 250 + Random(-50, 50))",
+                "Sleep Max(0, Round(250 + 20 * Sqrt(-2 * Ln(Random(0.0001, 1.0))) * Cos(6.2832 * Random(0.0, 1.0))))",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn exports_searches_and_windows() {
+        let window = WindowInfo {
+            title: "Untitled".into(),
+            class: "Notepad".into(),
+        };
+        let actions = [
+            Action::WaitForPixel(PixelInfo {
+                color: (255, 0, 16),
+                search_location_left_top: (10, 20),
+                search_location_width_height: (30, 40),
+                move_mouse_if_found: true,
+                search_anchor: SearchAnchor::Window(window.clone()),
+                ..Default::default()
+            }),
+            Action::CopyClipboard("order".into()),
+            Action::SetClipboard("#{order} \"{other}\"".into()),
+            Action::Launch(LaunchInfo {
+                program: r"C:\Program Files\app.exe".into(),
+                args: vec!["-v".into()],
+                wait_for_exit: true,
+                timeout: Some(1500),
+                ..Default::default()
+            }),
+        ];
+        let export = export_autohotkey(&actions, "image");
+
+        assert_eq!(
+            body(&export),
+            [
+                "WinGetClientPos &windowX, &windowY, , , \"Untitled ahk_class Notepad\"",
+                "while !PixelSearch(&foundX, &foundY, windowX + 10, windowY + 20, windowX + 40, windowY + 60, 0xFF0010)",
+                "    Sleep 100",
+                "MouseMove foundX, foundY, 0",
+                "copied_order := A_Clipboard",
+                "A_Clipboard := \"#\" copied_order \" `\"{other}`\"\"",
+                "Run \"`\"C:\\Program Files\\app.exe`\" -v\", , , &pid",
+                "ProcessWaitClose pid, 1.5",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn comments_out_plays_stray_blocks_and_partial_notches() {
+        let actions = [
+            Action::Play("other.floof".into()),
+            Action::EndIf,
+            Action::Break,
+            Action::Mouse(MouseActionKind::Wheel(60, None)),
+            Action::WaitForImage(ImageInfo::default()),
+        ];
+        let export = export_autohotkey(&actions, "image");
+
+        assert_eq!(export.warnings.len(), 5);
+        assert!(export.warnings[0].starts_with("Action 1: "));
+        assert!(export.images.is_empty());
+        assert_eq!(
+            body(&export)
+                .lines()
+                .filter(|line| line.starts_with("; Left out"))
+                .count(),
+            3
+        );
+    }
+}
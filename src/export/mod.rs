@@ -0,0 +1,192 @@
+//! Macros as scripts for other automation tools, for machines the recorder can't run on. Not
+//! everything a macro can do has a counterpart, what doesn't is left out with a warning.
+
+use std::{error::Error, ffi::OsStr, fs::write, path::Path};
+
+use crate::{actions::Action, images::RawScreenshot, script::braced_blocks};
+
+pub mod autohotkey;
+pub mod pyautogui;
+pub mod xdotool;
+
+use self::{
+    autohotkey::AutoHotkeyExporter, pyautogui::PyAutoGuiExporter, xdotool::XdotoolExporter,
+};
+
+/// A tool macros can be exported for.
+pub trait Exporter {
+    /// Name of the scripts, shown in the file dialog.
+    fn name(&self) -> &'static str;
+
+    /// Extension of the scripts, without the dot.
+    fn extension(&self) -> &'static str;
+
+    /// Writes the actions as a script. Screenshots are named after the prefix and their number,
+    /// and looked for next to the script.
+    fn export<'a>(&self, actions: &'a [Action], image_prefix: &str) -> Export<'a>;
+}
+
+/// Every tool macros can be exported for.
+pub fn exporters() -> Vec<Box<dyn Exporter>> {
+    vec![
+        Box::new(AutoHotkeyExporter),
+        Box::new(XdotoolExporter),
+        Box::new(PyAutoGuiExporter),
+    ]
+}
+
+pub struct Export<'a> {
+    pub text: String,
+    /// Names used in the script and the screenshots to save as PNG files under them.
+    pub images: Vec<(String, &'a RawScreenshot)>,
+    /// What couldn't be exported or only roughly, one line each.
+    pub warnings: Vec<String>,
+}
+
+impl<'a> Export<'a> {
+    pub fn warn(&mut self, index: usize, message: impl AsRef<str>) {
+        self.warnings
+            .push(format!("Action {}: {}", index + 1, message.as_ref()));
+    }
+}
+
+/// Exports the macro for the tool the path's extension belongs to, with its images next to it,
+/// and gives back the warnings.
+pub fn export_macro(path: &Path, actions: &[Action]) -> Result<Vec<String>, Box<dyn Error>> {
+    let exporter = exporters()
+        .into_iter()
+        .find(|exporter| path.extension() == Some(OsStr::new(exporter.extension())))
+        .ok_or_else(|| format!("Can't export to {}, unknown extension", path.display()))?;
+    let folder = path.parent().unwrap_or(Path::new(""));
+    let name = path.file_stem().unwrap_or_default().to_string_lossy();
+    let export = exporter.export(actions, &format!("{}-image", name));
+
+    for (name, screenshot) in &export.images {
+        write(folder.join(name), screenshot.to_png()?)?;
+    }
+    write(path, &export.text)?;
+
+    Ok(export.warnings)
+}
+
+/// How an action fits into the blocks of an exported script.
+pub enum Part {
+    /// An if or repeat opening a block.
+    Open,
+    Else,
+    /// An end closing the block opened last.
+    Close,
+    Action,
+    /// Something scripts have no way to write, with the reason.
+    LeftOut(&'static str),
+}
+
+/// Sorts the actions into blocks. Blocks that aren't closed in order, elses after the first and
+/// breaks outside of a repeat are left out, scripting languages don't have them.
+pub fn parts(actions: &[Action]) -> Vec<Part> {
+    // Whether each open block is a loop, and whether it has had its else
+    let mut open: Vec<(bool, bool)> = vec![];
+
+    actions
+        .iter()
+        .zip(braced_blocks(actions))
+        .map(|(action, braced)| match action {
+            Action::IfImage(_)
+            | Action::IfPixel(_)
+            | Action::IfClipboard(_)
+            | Action::Repeat(_)
+                if braced =>
+            {
+                open.push((matches!(action, Action::Repeat(_)), false));
+                Part::Open
+            }
+            Action::Else if braced => match open.last_mut() {
+                Some((_, had_else)) if !*had_else => {
+                    *had_else = true;
+                    Part::Else
+                }
+                _ => Part::LeftOut("Ifs can only have one else"),
+            },
+            Action::EndIf | Action::EndRepeat if braced => {
+                open.pop();
+                Part::Close
+            }
+            Action::IfImage(_)
+            | Action::IfPixel(_)
+            | Action::IfClipboard(_)
+            | Action::Repeat(_)
+            | Action::Else
+            | Action::EndIf
+            | Action::EndRepeat => Part::LeftOut("Its block isn't closed in order"),
+            Action::Break if !open.iter().any(|&(is_loop, _)| is_loop) => {
+                Part::LeftOut("Breaks only work inside a repeat")
+            }
+            _ => Part::Action,
+        })
+        .collect()
+}
+
+/// The action as it reads in the action list, for comments in place of what's left out.
+pub fn describe(action: &Action) -> String {
+    action
+        .get_grid_formatted()
+        .into_iter()
+        .filter(|column| !column.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{actions::*, recorder::keymap::*};
+
+    /// Input, a delay and nested blocks, which every tool can write.
+    pub fn input_and_blocks() -> Vec<Action> {
+        vec![
+            Action::Repeat(3),
+            Action::Mouse(MouseActionKind::Button(MouseActionButton {
+                point: Some(MousePointKind::To(Point { x: 100, y: 200 })),
+                button: VK_RBUTTON,
+                state: MouseActionButtonState::Clicked,
+            })),
+            Action::Keyboard(VK_LSHIFT, KeyState::Down, None),
+            Action::Keyboard(0x41, KeyState::Pressed, None),
+            Action::Keyboard(VK_RETURN, KeyState::Pressed, None),
+            Action::Keyboard(VK_LSHIFT, KeyState::Up, None),
+            Action::Delay(250, DelayJitter::None),
+            Action::IfClipboard("^done$".into()),
+            Action::Break,
+            Action::Else,
+            Action::EndIf,
+            Action::EndRepeat,
+            Action::Mouse(MouseActionKind::Moved(MousePointKind::By(Point {
+                x: -5,
+                y: 10,
+            }))),
+            Action::Mouse(MouseActionKind::Wheel(-240, None)),
+        ]
+    }
+
+    /// The script after its header, which ends at the first empty line.
+    pub fn body(export: &Export) -> String {
+        export
+            .text
+            .lines()
+            .skip_while(|line| !line.is_empty())
+            .skip(1)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn every_exporter_writes_input_and_blocks() {
+        let actions = input_and_blocks();
+
+        for exporter in exporters() {
+            let export = exporter.export(&actions, "image");
+
+            assert!(export.warnings.is_empty(), "{}", exporter.name());
+        }
+    }
+}
@@ -0,0 +1,852 @@
+//! Python scripts using `pyautogui`, with `pyperclip` for the clipboard. Images are searched for
+//! with `locateOnScreen`, similarities below 1 need OpenCV installed for its confidence. pyautogui
+//! can't find windows on Linux, so window actions are left out and searches relative to a window
+//! look at the screen instead.
+
+use super::{describe, parts, Export, Exporter, Part};
+use crate::{actions::*, recorder::keymap::*};
+
+const INDENT: &str = "    ";
+
+/// pyautogui's names for keys that aren't letters, digits, number pad digits or function keys.
+/// Keys without one can't be pressed by pyautogui.
+const KEY_NAMES: [(i32, &str); 39] = [
+    (VK_BACK, "backspace"),
+    (VK_TAB, "tab"),
+    (VK_RETURN, "enter"),
+    (VK_SHIFT, "shift"),
+    (VK_CONTROL, "ctrl"),
+    (VK_MENU, "alt"),
+    (VK_PAUSE, "pause"),
+    (VK_CAPITAL, "capslock"),
+    (VK_ESCAPE, "esc"),
+    (VK_SPACE, "space"),
+    (VK_PRIOR, "pageup"),
+    (VK_NEXT, "pagedown"),
+    (VK_END, "end"),
+    (VK_HOME, "home"),
+    (VK_LEFT, "left"),
+    (VK_UP, "up"),
+    (VK_RIGHT, "right"),
+    (VK_DOWN, "down"),
+    (VK_SNAPSHOT, "printscreen"),
+    (VK_INSERT, "insert"),
+    (VK_DELETE, "delete"),
+    (VK_LWIN, "winleft"),
+    (VK_RWIN, "winright"),
+    (VK_APPS, "apps"),
+    (VK_SLEEP, "sleep"),
+    (VK_MULTIPLY, "multiply"),
+    (VK_ADD, "add"),
+    (VK_SUBTRACT, "subtract"),
+    (VK_DECIMAL, "decimal"),
+    (VK_DIVIDE, "divide"),
+    (VK_NUMLOCK, "numlock"),
+    (VK_SCROLL, "scrolllock"),
+    (VK_LSHIFT, "shiftleft"),
+    (VK_RSHIFT, "shiftright"),
+    (VK_LCONTROL, "ctrlleft"),
+    (VK_RCONTROL, "ctrlright"),
+    (VK_LMENU, "altleft"),
+    (VK_RMENU, "altright"),
+    (VK_SEPARATOR, "separator"),
+];
+
+/// Polling interval of waits, in seconds.
+const WAIT_INTERVAL: f64 = 0.1;
+
+const LOCATE: &str = "\
+def locate(image, **options):
+    # None instead of an exception when the image isn't on the screen
+    try:
+        return pyautogui.locateOnScreen(os.path.join(HERE, image), **options)
+    except pyautogui.ImageNotFoundException:
+        return None
+";
+
+const FIND_PIXEL: &str = "\
+def find_pixel(region, color):
+    screenshot = pyautogui.screenshot(region=region)
+    for y in range(screenshot.height):
+        for x in range(screenshot.width):
+            if screenshot.getpixel((x, y))[:3] == color:
+                return region[0] + x, region[1] + y
+    return None
+";
+
+pub struct PyAutoGuiExporter;
+
+impl Exporter for PyAutoGuiExporter {
+    fn name(&self) -> &'static str {
+        "pyautogui script"
+    }
+
+    fn extension(&self) -> &'static str {
+        "py"
+    }
+
+    fn export<'a>(&self, actions: &'a [Action], image_prefix: &str) -> Export<'a> {
+        export_pyautogui(actions, image_prefix)
+    }
+}
+
+/// Writes the actions as a Python script. Screenshots are named after the prefix and their
+/// number, and looked for next to the script.
+pub fn export_pyautogui<'a>(actions: &'a [Action], image_prefix: &str) -> Export<'a> {
+    let mut script = PyAutoGui {
+        export: Export {
+            text: String::new(),
+            images: vec![],
+            warnings: vec![],
+        },
+        blocks: vec![],
+        image_prefix,
+        tracks_images: actions.iter().any(|action| {
+            matches!(
+                action,
+                Action::WaitForImage(ImageInfo {
+                    search_anchor: SearchAnchor::LastFoundImage,
+                    ..
+                }) | Action::IfImage(ImageInfo {
+                    search_anchor: SearchAnchor::LastFoundImage,
+                    ..
+                }) | Action::WaitForPixel(PixelInfo {
+                    search_anchor: SearchAnchor::LastFoundImage,
+                    ..
+                }) | Action::IfPixel(PixelInfo {
+                    search_anchor: SearchAnchor::LastFoundImage,
+                    ..
+                })
+            )
+        }),
+        copied: vec![],
+        warned_monitors: vec![],
+    };
+
+    script.line("# Exported from Fluffy Macro Recorder");
+    script.line("import os");
+    script.line("import random");
+    script.line("import re");
+    script.line("import subprocess");
+    script.line("import time");
+    script.line("");
+    script.line("import pyautogui");
+    script.line("import pyperclip");
+    script.line("");
+    script.line("# Delays are part of the macro, pyautogui shouldn't add its own");
+    script.line("pyautogui.PAUSE = 0");
+    script.line("HERE = os.path.dirname(os.path.abspath(__file__))");
+    script.line("");
+    if actions.iter().any(|action| {
+        matches!(
+            action,
+            Action::WaitForImage(ImageInfo {
+                screenshot_raw: Some(_),
+                ..
+            }) | Action::IfImage(ImageInfo {
+                screenshot_raw: Some(_),
+                ..
+            })
+        )
+    }) {
+        script.export.text += LOCATE;
+        script.line("");
+    }
+    if actions
+        .iter()
+        .any(|action| matches!(action, Action::WaitForPixel(_) | Action::IfPixel(_)))
+    {
+        script.export.text += FIND_PIXEL;
+        script.line("");
+    }
+
+    for (index, (action, part)) in actions.iter().zip(parts(actions)).enumerate() {
+        match part {
+            Part::Open => script.block(index, action),
+            Part::Else => {
+                script.close_block();
+                script.line("else:");
+                script.blocks.push(false);
+            }
+            Part::Close => script.close_block(),
+            Part::Action => script.action(index, action),
+            Part::LeftOut(reason) => script.leave_out(index, action, reason),
+        }
+    }
+
+    script.export
+}
+
+struct PyAutoGui<'a, 'b> {
+    export: Export<'a>,
+    /// Whether each open block has a line in it yet.
+    blocks: Vec<bool>,
+    image_prefix: &'b str,
+    /// Whether the center of the last found image is kept for searches relative to it.
+    tracks_images: bool,
+    /// Names of clipboard values copied so far.
+    copied: Vec<&'a str>,
+    warned_monitors: Vec<&'a str>,
+}
+
+impl<'a, 'b> PyAutoGui<'a, 'b> {
+    fn line(&mut self, line: &str) {
+        if !line.is_empty() {
+            for _ in 0..self.blocks.len() {
+                self.export.text.push_str(INDENT);
+            }
+        }
+        self.export.text.push_str(line);
+        self.export.text.push('\n');
+
+        if let Some(filled) = self.blocks.last_mut() {
+            *filled = true;
+        }
+    }
+
+    fn open_block(&mut self, header: &str) {
+        self.line(header);
+        self.blocks.push(false);
+    }
+
+    /// Python doesn't allow empty blocks, they get a `pass`.
+    fn close_block(&mut self) {
+        if self.blocks.last() == Some(&false) {
+            self.line("pass");
+        }
+        self.blocks.pop();
+    }
+
+    fn leave_out(&mut self, index: usize, action: &Action, reason: &str) {
+        self.export.warn(index, format!("{}, left out", reason));
+        self.line(&format!("# Left out: {}", describe(action)));
+    }
+
+    /// Opens the block of an if or repeat.
+    fn block(&mut self, index: usize, action: &'a Action) {
+        match action {
+            Action::IfImage(info) => {
+                let Some(search) = self.image_search(index, info) else {
+                    self.open_block(if info.check_if_not_found {
+                        "if True:"
+                    } else {
+                        "if False:"
+                    });
+                    return;
+                };
+
+                self.line(&format!("found = {}", search));
+                self.track_image(true);
+                self.open_block(if info.check_if_not_found {
+                    "if not found:"
+                } else {
+                    "if found:"
+                });
+                if info.move_mouse_if_found && !info.check_if_not_found {
+                    self.line("pyautogui.moveTo(pyautogui.center(found))");
+                }
+            }
+            Action::IfPixel(info) => {
+                let search = self.pixel_search(index, info);
+
+                self.line(&format!("found = {}", search));
+                self.open_block(if info.check_if_not_found {
+                    "if not found:"
+                } else {
+                    "if found:"
+                });
+                if info.move_mouse_if_found && !info.check_if_not_found {
+                    self.line("pyautogui.moveTo(*found)");
+                }
+            }
+            Action::IfClipboard(regex) => self.open_block(&format!(
+                "if re.search({}, pyperclip.paste()):",
+                python_string(regex)
+            )),
+            Action::Repeat(0) => self.open_block("while True:"),
+            Action::Repeat(amount) => self.open_block(&format!("for _ in range({}):", amount)),
+            _ => unreachable!(),
+        }
+    }
+
+    fn action(&mut self, index: usize, action: &'a Action) {
+        match action {
+            Action::Delay(delay, jitter) => self.line(&match jitter {
+                DelayJitter::None => format!("time.sleep({})", seconds(*delay)),
+                DelayJitter::Uniform(range) => format!(
+                    "time.sleep(max(0, {} + random.uniform(-{}, {})) / 1000)",
+                    delay, range, range
+                ),
+                DelayJitter::Normal(deviation) => format!(
+                    "time.sleep(max(0, random.gauss({}, {})) / 1000)",
+                    delay, deviation
+                ),
+            }),
+            Action::Mouse(kind) => match kind {
+                MouseActionKind::Moved(point) => {
+                    self.move_to(index, action, point);
+                }
+                MouseActionKind::Button(action_button) => {
+                    let Some(button) = click_button(action_button.button) else {
+                        return self.leave_out(index, action, "pyautogui can't click this button");
+                    };
+                    if let Some(point) = &action_button.point {
+                        if !self.move_to(index, action, point) {
+                            return;
+                        }
+                    }
+
+                    let function = match action_button.state {
+                        MouseActionButtonState::Pressed => "mouseDown",
+                        MouseActionButtonState::Released => "mouseUp",
+                        MouseActionButtonState::Clicked => "click",
+                    };
+                    self.line(&format!("pyautogui.{}(button=\"{}\")", function, button));
+                }
+                MouseActionKind::Wheel(amount, point) => {
+                    self.wheel(index, action, *amount, point, "scroll")
+                }
+                MouseActionKind::HorizontalWheel(amount, point) => {
+                    self.wheel(index, action, *amount, point, "hscroll")
+                }
+            },
+            Action::Keyboard(key_code, state, _) => {
+                let Some(key) = key_name(*key_code) else {
+                    return self.leave_out(index, action, "pyautogui has no name for this key");
+                };
+
+                self.line(&match state {
+                    KeyState::Down => format!("pyautogui.keyDown(\"{}\")", key),
+                    KeyState::Up => format!("pyautogui.keyUp(\"{}\")", key),
+                    KeyState::Pressed => format!("pyautogui.press(\"{}\")", key),
+                });
+            }
+            Action::WaitForImage(info) => {
+                let Some(search) = self.image_search(index, info) else {
+                    return;
+                };
+
+                if info.check_if_not_found {
+                    self.line(&format!("while {}:", search));
+                } else {
+                    self.line(&format!("while not (found := {}):", search));
+                }
+                self.blocks.push(false);
+                self.line(&format!("time.sleep({})", WAIT_INTERVAL));
+                self.blocks.pop();
+                if !info.check_if_not_found {
+                    self.track_image(false);
+                    if info.move_mouse_if_found {
+                        self.line("pyautogui.moveTo(pyautogui.center(found))");
+                    }
+                }
+            }
+            Action::WaitForPixel(info) => {
+                let search = self.pixel_search(index, info);
+
+                if info.check_if_not_found {
+                    self.line(&format!("while {}:", search));
+                } else {
+                    self.line(&format!("while not (found := {}):", search));
+                }
+                self.blocks.push(false);
+                self.line(&format!("time.sleep({})", WAIT_INTERVAL));
+                self.blocks.pop();
+                if info.move_mouse_if_found && !info.check_if_not_found {
+                    self.line("pyautogui.moveTo(*found)");
+                }
+            }
+            Action::Break => self.line("break"),
+            Action::Play(path) => self.leave_out(
+                index,
+                action,
+                &format!(
+                    "Playing another macro can't be exported, export {} on its own and run it",
+                    path.display()
+                ),
+            ),
+            Action::Launch(info) => {
+                let mut args = vec![python_string(&info.program.to_string_lossy())];
+                args.extend(info.args.iter().map(|arg| python_string(arg)));
+                let mut process = format!("subprocess.Popen([{}]", args.join(", "));
+                if let Some(working_dir) = &info.working_dir {
+                    process += &format!(", cwd={}", python_string(&working_dir.to_string_lossy()));
+                }
+                process.push(')');
+
+                match (info.wait_for_exit, info.timeout) {
+                    (true, Some(timeout)) => {
+                        self.open_block("try:");
+                        self.line(&format!("{}.wait(timeout={})", process, seconds(timeout)));
+                        self.close_block();
+                        self.open_block("except subprocess.TimeoutExpired:");
+                        self.close_block();
+                    }
+                    (true, None) => self.line(&format!("{}.wait()", process)),
+                    (false, _) => self.line(&process),
+                }
+            }
+            Action::WaitForWindow(_) | Action::ActivateWindow(_) | Action::MoveWindow(..) => {
+                self.leave_out(index, action, "pyautogui can't find windows")
+            }
+            Action::SetClipboard(text) => {
+                let text = self.clipboard_text(text);
+                self.line(&format!("pyperclip.copy({})", text));
+            }
+            Action::CopyClipboard(name) => {
+                self.line(&format!("{} = pyperclip.paste()", copied_variable(name)));
+                self.copied.push(name);
+            }
+            Action::WaitForClipboardChange => {
+                self.line("clipboard_before = pyperclip.paste()");
+                self.line("while pyperclip.paste() == clipboard_before:");
+                self.blocks.push(false);
+                self.line(&format!("time.sleep({})", WAIT_INTERVAL));
+                self.blocks.pop();
+            }
+            Action::Drag(info) => self.drag(index, action, info),
+            Action::IfImage(_)
+            | Action::IfPixel(_)
+            | Action::IfClipboard(_)
+            | Action::Repeat(_)
+            | Action::Else
+            | Action::EndIf
+            | Action::EndRepeat => unreachable!(),
+        }
+    }
+
+    /// Moves the mouse to the point, and whether it could. Points in windows are left out with
+    /// the action.
+    fn move_to(&mut self, index: usize, action: &Action, point: &'a MousePointKind) -> bool {
+        match point {
+            MousePointKind::To(point) => {
+                self.line(&format!("pyautogui.moveTo({}, {})", point.x, point.y))
+            }
+            MousePointKind::By(point) => {
+                self.line(&format!("pyautogui.moveRel({}, {})", point.x, point.y))
+            }
+            MousePointKind::Window(..) => {
+                self.leave_out(index, action, "pyautogui can't find windows");
+                return false;
+            }
+            MousePointKind::Monitor(monitor, point) => {
+                self.warn_monitor(index, monitor);
+                self.line(&format!(
+                    "pyautogui.moveTo({}, {})",
+                    monitor.rect.x + point.x,
+                    monitor.rect.y + point.y
+                ));
+            }
+        }
+
+        true
+    }
+
+    fn wheel(
+        &mut self,
+        index: usize,
+        action: &Action,
+        amount: i32,
+        point: &'a Option<MousePointKind>,
+        function: &str,
+    ) {
+        // Windows counts 120 to a notch, pyautogui scrolls whole notches
+        let notches = (amount as f64 / 120.0).round() as i32;
+
+        if amount % 120 != 0 {
+            self.export.warn(
+                index,
+                format!(
+                    "Scrolling {} is rounded to {} whole notches",
+                    amount,
+                    notches.abs()
+                ),
+            );
+        }
+        if notches == 0 {
+            return;
+        }
+        if let Some(point) = point {
+            if !self.move_to(index, action, point) {
+                return;
+            }
+        }
+
+        self.line(&format!("pyautogui.{}({})", function, notches));
+    }
+
+    fn drag(&mut self, index: usize, action: &Action, info: &'a DragInfo) {
+        let Some(button) = click_button(info.button) else {
+            return self.leave_out(index, action, "pyautogui can't drag with this button");
+        };
+        if matches!(info.to, MousePointKind::Window(..)) {
+            return self.leave_out(index, action, "pyautogui can't find windows");
+        }
+        if !self.move_to(index, action, &info.from) {
+            return;
+        }
+
+        self.line(&format!("pyautogui.mouseDown(button=\"{}\")", button));
+        self.line("drag_x, drag_y = pyautogui.position()");
+
+        // Playback moves smoothly through every point, pyautogui gives each the same share of time
+        let step = seconds(info.duration / (info.path.len() as u32 + 1));
+
+        for point in &info.path {
+            self.line(&format!(
+                "pyautogui.moveTo({}, {}, duration={})",
+                offset("drag_x", point.x),
+                offset("drag_y", point.y),
+                step
+            ));
+        }
+
+        let (x, y) = match &info.to {
+            MousePointKind::To(point) => (point.x.to_string(), point.y.to_string()),
+            MousePointKind::By(point) => (offset("drag_x", point.x), offset("drag_y", point.y)),
+            MousePointKind::Monitor(monitor, point) => {
+                self.warn_monitor(index, monitor);
+                (
+                    (monitor.rect.x + point.x).to_string(),
+                    (monitor.rect.y + point.y).to_string(),
+                )
+            }
+            MousePointKind::Window(..) => unreachable!(),
+        };
+        self.line(&format!(
+            "pyautogui.moveTo({}, {}, duration={})",
+            x, y, step
+        ));
+        self.line(&format!("pyautogui.mouseUp(button=\"{}\")", button));
+    }
+
+    fn warn_monitor(&mut self, index: usize, monitor: &'a MonitorInfo) {
+        if !self.warned_monitors.contains(&monitor.name.as_str()) {
+            self.warned_monitors.push(&monitor.name);
+            self.export.warn(
+                index,
+                format!(
+                    "Positions on monitor {} are exported where they were recorded",
+                    monitor.describe()
+                ),
+            );
+        }
+    }
+
+    /// The area to search as a `(left, top, width, height)` tuple, None for the whole screen.
+    fn search_region(
+        &mut self,
+        index: usize,
+        left_top: Option<(i32, i32)>,
+        width_height: Option<(i32, i32)>,
+        anchor: &SearchAnchor,
+    ) -> Option<String> {
+        if let SearchAnchor::Window(_) = anchor {
+            self.export.warn(
+                index,
+                "pyautogui can't find windows, the screen is searched instead",
+            );
+        }
+
+        let ((x, y), (width, height)) = (left_top?, width_height?);
+        let (origin_x, origin_y) = match anchor {
+            SearchAnchor::LastFoundImage => ("image_x", "image_y"),
+            _ => ("", ""),
+        };
+
+        Some(format!(
+            "({}, {}, {}, {})",
+            offset(origin_x, x),
+            offset(origin_y, y),
+            width,
+            height
+        ))
+    }
+
+    /// The call searching for the image, None if the action has no image.
+    fn image_search(&mut self, index: usize, info: &'a ImageInfo) -> Option<String> {
+        let Some(screenshot) = &info.screenshot_raw else {
+            self.export
+                .warn(index, "There's no image to search for, it's never found");
+            return None;
+        };
+
+        let name = format!("{}{}.png", self.image_prefix, self.export.images.len() + 1);
+        self.export.images.push((name.clone(), &screenshot.color));
+
+        let mut options = vec![python_string(&name)];
+        if let Some(region) = self.search_region(
+            index,
+            info.search_location_left_top,
+            info.search_location_width_height,
+            &info.search_anchor,
+        ) {
+            options.push(format!("region={}", region));
+        }
+        if info.image_similarity < 1.0 {
+            options.push(format!(
+                "confidence={}",
+                info.image_similarity.clamp(0.0, 1.0)
+            ));
+        }
+
+        Some(format!("locate({})", options.join(", ")))
+    }
+
+    fn pixel_search(&mut self, index: usize, info: &PixelInfo) -> String {
+        let (red, green, blue) = info.color;
+        let region = self
+            .search_region(
+                index,
+                Some(info.search_location_left_top),
+                Some(info.search_location_width_height),
+                &info.search_anchor,
+            )
+            .unwrap();
+
+        format!("find_pixel({}, ({}, {}, {}))", region, red, green, blue)
+    }
+
+    /// Keeps the center of the found image for searches relative to it, only if it was found.
+    fn track_image(&mut self, conditional: bool) {
+        if !self.tracks_images {
+            return;
+        }
+
+        let line = "image_x, image_y = pyautogui.center(found)";
+        if conditional {
+            self.open_block("if found:");
+            self.line(line);
+            self.close_block();
+        } else {
+            self.line(line);
+        }
+    }
+
+    /// The text as a Python expression, with `{name}` replaced by values copied earlier.
+    fn clipboard_text(&self, text: &str) -> String {
+        let mut parts = vec![];
+        let mut literal = String::new();
+        let mut rest = text;
+
+        'text: while let Some(c) = rest.chars().next() {
+            for name in &self.copied {
+                let placeholder = format!("{{{}}}", name);
+
+                if let Some(after) = rest.strip_prefix(&placeholder) {
+                    if !literal.is_empty() {
+                        parts.push(python_string(&literal));
+                        literal.clear();
+                    }
+                    parts.push(copied_variable(name));
+                    rest = after;
+                    continue 'text;
+                }
+            }
+
+            literal.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+
+        if !literal.is_empty() || parts.is_empty() {
+            parts.push(python_string(&literal));
+        }
+
+        parts.join(" + ")
+    }
+}
+
+/// The name pyautogui knows the key by, None if it has none.
+pub fn key_name(key_code: i32) -> Option<String> {
+    if let Some((_, name)) = KEY_NAMES.iter().find(|(code, _)| *code == key_code) {
+        return Some(name.to_string());
+    }
+
+    Some(match key_code {
+        0x30..=0x39 | 0x41..=0x5A => (key_code as u8 as char).to_ascii_lowercase().to_string(),
+        VK_NUMPAD0..=VK_NUMPAD9 => format!("num{}", key_code - VK_NUMPAD0),
+        VK_F1..=VK_F24 => format!("f{}", key_code - VK_F1 + 1),
+        _ => return None,
+    })
+}
+
+fn click_button(button: i32) -> Option<&'static str> {
+    Some(match button {
+        VK_LBUTTON => "left",
+        VK_RBUTTON => "right",
+        VK_MBUTTON => "middle",
+        _ => return None,
+    })
+}
+
+fn seconds(milliseconds: u32) -> String {
+    (milliseconds as f64 / 1000.0).to_string()
+}
+
+/// `origin + amount`, just the amount without an origin.
+fn offset(origin: &str, amount: i32) -> String {
+    match (origin.is_empty(), amount) {
+        (true, amount) => amount.to_string(),
+        (false, 0) => origin.to_string(),
+        (false, amount) if amount < 0 => format!("{} - {}", origin, -amount),
+        (false, amount) => format!("{} + {}", origin, amount),
+    }
+}
+
+fn copied_variable(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+
+    format!("copied_{}", name)
+}
+
+fn python_string(text: &str) -> String {
+    let mut quoted = String::from('"');
+
+    for c in text.chars() {
+        match c {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            '\n' => quoted += "\\n",
+            '\r' => quoted += "\\r",
+            '\t' => quoted += "\\t",
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::input_and_blocks;
+    use crate::images::{RawScreenshot, RawScreenshotPair};
+
+    #[test]
+    fn writes_pyautogui_calls() {
+        let actions = input_and_blocks();
+        let export = export_pyautogui(&actions, "image");
+
+        assert!(export.text.ends_with(
+            &[
+                "for _ in range(3):",
+                "    pyautogui.moveTo(100, 200)",
+                "    pyautogui.click(button=\"right\")",
+                "    pyautogui.keyDown(\"shiftleft\")",
+                "    pyautogui.press(\"a\")",
+                "    pyautogui.press(\"enter\")",
+                "    pyautogui.keyUp(\"shiftleft\")",
+                "    time.sleep(0.25)",
+                "    if re.search(\"^done$\", pyperclip.paste()):",
+                "        break",
+                "    else:",
+                "        pass",
+                "pyautogui.moveRel(-5, 10)",
+                "pyautogui.scroll(-2)\n",
+            ]
+            .join("\n")
+        ));
+        assert!(!export.text.contains("def locate"));
+    }
+
+    #[test]
+    fn sleeps_in_seconds_with_jitter() {
+        let actions = [
+            Action::Delay(1500, DelayJitter::None),
+            Action::Delay(250, DelayJitter::Uniform(50)),
+            Action::Delay(250, DelayJitter::Normal(20)),
+        ];
+        let export = export_pyautogui(&actions, "image");
+
+        assert!(export.text.ends_with(
+            &[
+                "time.sleep(1.5)",
+                "time.sleep(max(0, 250 + random.uniform(-50, 50)) / 1000)",
+                "time.sleep(max(0, random.gauss(250, 20)) / 1000)\n",
+            ]
+            .join("\n")
+        ));
+    }
+
+    #[test]
+    fn exports_image_searches() {
+        let color = RawScreenshot {
+            pixels: vec![0; 4 * 4 * 6],
+            width: 4,
+            height: 6,
+            x: 0,
+            y: 0,
+        };
+        let actions = [
+            Action::WaitForImage(ImageInfo {
+                screenshot_raw: Some(RawScreenshotPair::from_color(color).unwrap()),
+                image_similarity: 0.9,
+                move_mouse_if_found: true,
+                ..Default::default()
+            }),
+            Action::IfPixel(PixelInfo {
+                color: (255, 0, 16),
+                search_location_left_top: (-10, 20),
+                search_location_width_height: (30, 40),
+                search_anchor: SearchAnchor::LastFoundImage,
+                check_if_not_found: true,
+                ..Default::default()
+            }),
+            Action::SetClipboard("missing".into()),
+            Action::EndIf,
+        ];
+        let export = export_pyautogui(&actions, "macro-image");
+
+        assert!(export.text.contains("def locate("));
+        assert!(export.text.contains("def find_pixel("));
+        assert_eq!(export.images.len(), 1);
+        assert_eq!(export.images[0].0, "macro-image1.png");
+        assert!(export.text.ends_with(
+            &[
+                "while not (found := locate(\"macro-image1.png\", confidence=0.9)):",
+                "    time.sleep(0.1)",
+                "image_x, image_y = pyautogui.center(found)",
+                "pyautogui.moveTo(pyautogui.center(found))",
+                "found = find_pixel((image_x - 10, image_y + 20, 30, 40), (255, 0, 16))",
+                "if not found:",
+                "    pyperclip.copy(\"missing\")\n",
+            ]
+            .join("\n")
+        ));
+    }
+
+    #[test]
+    fn comments_out_windows_and_x_buttons() {
+        let actions = [
+            Action::ActivateWindow(WindowInfo::default()),
+            Action::Mouse(MouseActionKind::Button(MouseActionButton {
+                point: None,
+                button: VK_XBUTTON1,
+                state: MouseActionButtonState::Clicked,
+            })),
+            Action::Else,
+            Action::Launch(LaunchInfo {
+                program: "gedit".into(),
+                wait_for_exit: true,
+                timeout: Some(500),
+                ..Default::default()
+            }),
+        ];
+        let export = export_pyautogui(&actions, "image");
+
+        assert_eq!(export.warnings.len(), 3);
+        assert!(export.text.ends_with(
+            &[
+                "try:",
+                "    subprocess.Popen([\"gedit\"]).wait(timeout=0.5)",
+                "except subprocess.TimeoutExpired:",
+                "    pass\n",
+            ]
+            .join("\n")
+        ));
+    }
+}
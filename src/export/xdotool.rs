@@ -0,0 +1,615 @@
+//! Bash scripts driving `xdotool`, for X11 desktops. xdotool can't look at the screen, so image
+//! and pixel searches are left out, and the clipboard goes through `xclip`. Windows are found by a
+//! single regular expression, the class if there is one and the title otherwise.
+
+use super::{describe, parts, Export, Exporter, Part};
+use crate::{actions::*, keys::Key, recorder::keymap::*};
+
+const INDENT: &str = "    ";
+
+/// Polling interval of waits, in seconds.
+const WAIT_INTERVAL: f64 = 0.1;
+
+const READ_CLIPBOARD: &str = "xclip -selection clipboard -o 2> /dev/null";
+
+pub struct XdotoolExporter;
+
+impl Exporter for XdotoolExporter {
+    fn name(&self) -> &'static str {
+        "xdotool script"
+    }
+
+    fn extension(&self) -> &'static str {
+        "sh"
+    }
+
+    fn export<'a>(&self, actions: &'a [Action], _image_prefix: &str) -> Export<'a> {
+        export_xdotool(actions)
+    }
+}
+
+/// Writes the actions as a bash script. There are no images to save, searches are left out.
+pub fn export_xdotool(actions: &[Action]) -> Export<'_> {
+    let mut script = Xdotool {
+        export: Export {
+            text: String::new(),
+            images: vec![],
+            warnings: vec![],
+        },
+        blocks: vec![],
+        copied: vec![],
+        warned_monitors: vec![],
+    };
+
+    script.line("#!/usr/bin/env bash");
+    script.line("# Exported from Fluffy Macro Recorder");
+    script.line("set -e");
+    script.line("");
+
+    for (index, (action, part)) in actions.iter().zip(parts(actions)).enumerate() {
+        match part {
+            Part::Open => script.block(index, action),
+            Part::Else => {
+                script.fill_block();
+                let block = script.blocks.pop().unwrap();
+                script.line("else");
+                script.blocks.push((block.0, false));
+            }
+            Part::Close => {
+                script.fill_block();
+                let (end, _) = script.blocks.pop().unwrap();
+                script.line(end);
+            }
+            Part::Action => script.action(index, action),
+            Part::LeftOut(reason) => script.leave_out(index, action, reason),
+        }
+    }
+
+    script.export
+}
+
+struct Xdotool<'a> {
+    export: Export<'a>,
+    /// The keyword ending each open block, and whether it has a line in it yet.
+    blocks: Vec<(&'static str, bool)>,
+    /// Names of clipboard values copied so far.
+    copied: Vec<&'a str>,
+    warned_monitors: Vec<&'a str>,
+}
+
+impl<'a> Xdotool<'a> {
+    fn line(&mut self, line: &str) {
+        if !line.is_empty() {
+            for _ in 0..self.blocks.len() {
+                self.export.text.push_str(INDENT);
+            }
+        }
+        self.export.text.push_str(line);
+        self.export.text.push('\n');
+
+        if let Some((_, filled)) = self.blocks.last_mut() {
+            *filled = true;
+        }
+    }
+
+    /// Bash doesn't allow empty blocks, they get a command that does nothing.
+    fn fill_block(&mut self) {
+        if let Some((_, false)) = self.blocks.last() {
+            self.line(":");
+        }
+    }
+
+    fn open_block(&mut self, header: &str, end: &'static str) {
+        self.line(header);
+        self.blocks.push((end, false));
+    }
+
+    fn leave_out(&mut self, index: usize, action: &Action, reason: &str) {
+        self.export.warn(index, format!("{}, left out", reason));
+        self.line(&format!("# Left out: {}", describe(action)));
+    }
+
+    /// Opens the block of an if or repeat.
+    fn block(&mut self, index: usize, action: &'a Action) {
+        match action {
+            Action::IfImage(ImageInfo {
+                check_if_not_found, ..
+            })
+            | Action::IfPixel(PixelInfo {
+                check_if_not_found, ..
+            }) => {
+                self.export.warn(
+                    index,
+                    "xdotool can't search the screen, it's treated as never found",
+                );
+                self.open_block(
+                    if *check_if_not_found {
+                        "if true; then"
+                    } else {
+                        "if false; then"
+                    },
+                    "fi",
+                );
+            }
+            Action::IfClipboard(regex) => self.open_block(
+                &format!(
+                    "if {} | grep -qzP -- {}; then",
+                    READ_CLIPBOARD,
+                    quote(regex)
+                ),
+                "fi",
+            ),
+            Action::Repeat(0) => self.open_block("while true; do", "done"),
+            Action::Repeat(amount) => {
+                self.open_block(&format!("for _ in $(seq {}); do", amount), "done")
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn action(&mut self, index: usize, action: &'a Action) {
+        match action {
+            Action::Delay(delay, jitter) => self.line(&match jitter {
+                DelayJitter::None => format!("sleep {}", seconds(*delay)),
+                DelayJitter::Uniform(range) => {
+                    random_sleep(&format!("{} + (2 * rand() - 1) * {}", delay, range))
+                }
+                // Box-Muller, awk only has uniform random numbers
+                DelayJitter::Normal(deviation) => random_sleep(&format!(
+                    "{} + {} * sqrt(-2 * log(1 - rand())) * cos(6.2832 * rand())",
+                    delay, deviation
+                )),
+            }),
+            Action::Mouse(kind) => match kind {
+                MouseActionKind::Moved(point) => {
+                    let command = self.pointer(index, point);
+                    self.line(&format!("xdotool {}", command));
+                }
+                MouseActionKind::Button(action_button) => {
+                    let Some(button) = button_number(action_button.button) else {
+                        return self.leave_out(index, action, "xdotool can't click this button");
+                    };
+                    let command = match action_button.state {
+                        MouseActionButtonState::Pressed => "mousedown",
+                        MouseActionButtonState::Released => "mouseup",
+                        MouseActionButtonState::Clicked => "click",
+                    };
+
+                    let command = format!("{} {}", command, button);
+                    self.at(index, &action_button.point, &command);
+                }
+                // X11 scrolls with buttons, up and down are 4 and 5, left and right 6 and 7
+                MouseActionKind::Wheel(amount, point) => {
+                    let button = if *amount > 0 { 4 } else { 5 };
+                    self.wheel(index, *amount, point, button);
+                }
+                MouseActionKind::HorizontalWheel(amount, point) => {
+                    let button = if *amount > 0 { 7 } else { 6 };
+                    self.wheel(index, *amount, point, button);
+                }
+            },
+            Action::Keyboard(key_code, state, _) => {
+                let Some(key) = keysym_name(*key_code) else {
+                    return self.leave_out(index, action, "xdotool has no name for this key");
+                };
+
+                self.line(&match state {
+                    KeyState::Down => format!("xdotool keydown {}", key),
+                    KeyState::Up => format!("xdotool keyup {}", key),
+                    KeyState::Pressed => format!("xdotool key {}", key),
+                });
+            }
+            Action::WaitForImage(_) | Action::WaitForPixel(_) => {
+                self.leave_out(index, action, "xdotool can't search the screen")
+            }
+            Action::Break => self.line("break"),
+            Action::Play(path) => self.leave_out(
+                index,
+                action,
+                &format!(
+                    "Playing another macro can't be exported, export {} on its own and run it",
+                    path.display()
+                ),
+            ),
+            Action::Launch(info) => {
+                let mut command = std::iter::once(info.program.to_string_lossy().into_owned())
+                    .chain(info.args.iter().cloned())
+                    .map(|arg| quote(&arg))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if let Some(working_dir) = &info.working_dir {
+                    command = format!(
+                        "(cd {} && exec {})",
+                        quote(&working_dir.to_string_lossy()),
+                        command
+                    );
+                }
+
+                // Playback doesn't care how programs exit, so failing ones don't stop the script
+                match (info.wait_for_exit, info.timeout) {
+                    (true, Some(timeout)) => {
+                        self.line(&format!("{} &", command));
+                        self.line(&format!(
+                            "timeout {} tail --pid=$! -f /dev/null || true",
+                            seconds(timeout)
+                        ));
+                    }
+                    (true, None) => self.line(&format!("{} || true", command)),
+                    (false, _) => self.line(&format!("{} &", command)),
+                }
+            }
+            Action::WaitForWindow(info) => {
+                let pattern = self.window_pattern(index, info);
+                self.line(&format!("xdotool search --sync{} > /dev/null", pattern));
+            }
+            Action::ActivateWindow(info) => {
+                let pattern = self.window_pattern(index, info);
+                self.line(&format!("xdotool search{} windowactivate", pattern));
+            }
+            Action::MoveWindow(info, rect) => {
+                let pattern = self.window_pattern(index, info);
+                self.line(&format!(
+                    "xdotool search{} windowmove %1 {} {} windowsize %1 {} {}",
+                    pattern, rect.x, rect.y, rect.width, rect.height
+                ));
+            }
+            Action::SetClipboard(text) => {
+                let text = self.clipboard_text(text);
+                self.line(&format!(
+                    "printf '%s' {} | xclip -selection clipboard",
+                    text
+                ));
+            }
+            Action::CopyClipboard(name) => {
+                self.line(&format!(
+                    "{}=\"$({})\" || true",
+                    copied_variable(name),
+                    READ_CLIPBOARD
+                ));
+                self.copied.push(name);
+            }
+            Action::WaitForClipboardChange => {
+                self.line(&format!(
+                    "clipboard_before=\"$({})\" || true",
+                    READ_CLIPBOARD
+                ));
+                self.line(&format!(
+                    "while [ \"$({})\" = \"$clipboard_before\" ]; do sleep {}; done",
+                    READ_CLIPBOARD, WAIT_INTERVAL
+                ));
+            }
+            Action::Drag(info) => self.drag(index, action, info),
+            Action::IfImage(_)
+            | Action::IfPixel(_)
+            | Action::IfClipboard(_)
+            | Action::Repeat(_)
+            | Action::Else
+            | Action::EndIf
+            | Action::EndRepeat => unreachable!(),
+        }
+    }
+
+    /// Runs the xdotool command, after moving the pointer if the action has a position.
+    fn at(&mut self, index: usize, point: &'a Option<MousePointKind>, command: &str) {
+        match point {
+            Some(point) => {
+                let pointer = self.pointer(index, point);
+                self.line(&format!("xdotool {} {}", pointer, command));
+            }
+            None => self.line(&format!("xdotool {}", command)),
+        }
+    }
+
+    fn wheel(&mut self, index: usize, amount: i32, point: &'a Option<MousePointKind>, button: u8) {
+        // Windows counts 120 to a notch, X11 only scrolls whole notches
+        let notches = (amount.abs() as f64 / 120.0).round() as i32;
+
+        if amount % 120 != 0 {
+            self.export.warn(
+                index,
+                format!(
+                    "Scrolling {} is rounded to {} whole notches",
+                    amount, notches
+                ),
+            );
+        }
+
+        match notches {
+            0 => {}
+            1 => self.at(index, point, &format!("click {}", button)),
+            notches => self.at(
+                index,
+                point,
+                &format!("click --repeat {} {}", notches, button),
+            ),
+        }
+    }
+
+    fn drag(&mut self, index: usize, action: &Action, info: &'a DragInfo) {
+        let Some(button) = button_number(info.button) else {
+            return self.leave_out(index, action, "xdotool can't drag with this button");
+        };
+
+        let pointer = self.pointer(index, &info.from);
+        self.line(&format!("xdotool {}", pointer));
+        self.line(&format!("xdotool mousedown {}", button));
+        self.line("eval \"$(xdotool getmouselocation --shell)\"");
+
+        // Playback moves smoothly, here every point along the way gets the same share of time
+        let step = seconds(info.duration / (info.path.len() as u32 + 1));
+
+        for point in &info.path {
+            self.line(&format!("sleep {}", step));
+            self.line(&format!(
+                "xdotool mousemove {} {}",
+                offset("X", point.x),
+                offset("Y", point.y)
+            ));
+        }
+
+        self.line(&format!("sleep {}", step));
+        let pointer = match &info.to {
+            MousePointKind::By(point) => format!(
+                "mousemove {} {}",
+                offset("X", point.x),
+                offset("Y", point.y)
+            ),
+            to => self.pointer(index, to),
+        };
+        self.line(&format!("xdotool {}", pointer));
+        self.line(&format!("xdotool mouseup {}", button));
+    }
+
+    /// The xdotool commands moving the pointer to the point.
+    fn pointer(&mut self, index: usize, point: &'a MousePointKind) -> String {
+        match point {
+            MousePointKind::To(point) => format!("mousemove {} {}", point.x, point.y),
+            MousePointKind::By(point) => format!("mousemove_relative -- {} {}", point.x, point.y),
+            MousePointKind::Window(info, point) => format!(
+                "search{} mousemove --window %1 {} {}",
+                self.window_pattern(index, info),
+                point.x,
+                point.y
+            ),
+            MousePointKind::Monitor(monitor, point) => {
+                if !self.warned_monitors.contains(&monitor.name.as_str()) {
+                    self.warned_monitors.push(&monitor.name);
+                    self.export.warn(
+                        index,
+                        format!(
+                            "Positions on monitor {} are exported where they were recorded",
+                            monitor.describe()
+                        ),
+                    );
+                }
+
+                format!(
+                    "mousemove {} {}",
+                    monitor.rect.x + point.x,
+                    monitor.rect.y + point.y
+                )
+            }
+        }
+    }
+
+    /// Options of `xdotool search` finding the window.
+    fn window_pattern(&mut self, index: usize, info: &WindowInfo) -> String {
+        if !info.class.is_empty() {
+            if !info.title.is_empty() {
+                self.export.warn(
+                    index,
+                    "xdotool finds windows by one pattern, only the class is matched",
+                );
+            }
+            format!(" --onlyvisible --class {}", quote(&info.class))
+        } else {
+            format!(" --onlyvisible --name {}", quote(&info.title))
+        }
+    }
+
+    /// The text as a bash string, with `{name}` replaced by values copied earlier.
+    fn clipboard_text(&self, text: &str) -> String {
+        let mut quoted = String::from('"');
+        let mut rest = text;
+
+        'text: while let Some(c) = rest.chars().next() {
+            for name in &self.copied {
+                let placeholder = format!("{{{}}}", name);
+
+                if let Some(after) = rest.strip_prefix(&placeholder) {
+                    quoted += &format!("${{{}}}", copied_variable(name));
+                    rest = after;
+                    continue 'text;
+                }
+            }
+
+            if matches!(c, '"' | '\\' | '$' | '`') {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+
+        quoted.push('"');
+        quoted
+    }
+}
+
+/// The keysym name xdotool knows the key by, None if it has none.
+pub fn keysym_name(key_code: i32) -> Option<String> {
+    Key::from_vk(key_code)?.keysym_name()
+}
+
+/// The key code of a keysym name, ignoring case. Other key names work too, like "ctrl".
+pub fn keysym_code(name: &str) -> Option<i32> {
+    Key::from_keysym_name(name).map(Key::to_vk)
+}
+
+fn button_number(button: i32) -> Option<u8> {
+    Some(match button {
+        VK_LBUTTON => 1,
+        VK_MBUTTON => 2,
+        VK_RBUTTON => 3,
+        VK_XBUTTON1 => 8,
+        VK_XBUTTON2 => 9,
+        _ => return None,
+    })
+}
+
+fn seconds(milliseconds: u32) -> String {
+    (milliseconds as f64 / 1000.0).to_string()
+}
+
+/// Sleeps for a number of milliseconds worked out by awk, which seeds from bash since it would
+/// only seed from the current second on its own.
+fn random_sleep(milliseconds: &str) -> String {
+    format!(
+        "sleep \"$(awk -v seed=$RANDOM 'BEGIN {{ srand(seed); t = {}; print (t < 0 ? 0 : t) / 1000 }}')\"",
+        milliseconds
+    )
+}
+
+/// `$((variable + amount))`, just the variable if there's nothing to add.
+fn offset(variable: &str, amount: i32) -> String {
+    match amount {
+        0 => format!("${}", variable),
+        amount if amount < 0 => format!("$(({} - {}))", variable, -amount),
+        amount => format!("$(({} + {}))", variable, amount),
+    }
+}
+
+fn copied_variable(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    format!("copied_{}", name)
+}
+
+/// The text in single quotes, which bash takes literally.
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::{body, input_and_blocks};
+
+    #[test]
+    fn writes_keysym_names_and_seconds() {
+        let actions = input_and_blocks();
+        let export = export_xdotool(&actions);
+
+        assert_eq!(
+            body(&export),
+            [
+                "for _ in $(seq 3); do",
+                "    xdotool mousemove 100 200 click 3",
+                "    xdotool keydown Shift_L",
+                "    xdotool key a",
+                "    xdotool key Return",
+                "    xdotool keyup Shift_L",
+                "    sleep 0.25",
+                "    if xclip -selection clipboard -o 2> /dev/null | grep -qzP -- '^done$'; then",
+                "        break",
+                "    else",
+                "        :",
+                "    fi",
+                "done",
+                "xdotool mousemove_relative -- -5 10",
+                "xdotool click --repeat 2 5",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn reads_back_keysym_names() {
+        for (key_code, name) in [
+            (VK_RETURN, "Return"),
+            (VK_LCONTROL, "Control_L"),
+            (VK_F5, "F5"),
+            (0x41, "a"),
+        ] {
+            assert_eq!(keysym_name(key_code).as_deref(), Some(name));
+            assert_eq!(keysym_code(name), Some(key_code));
+        }
+    }
+
+    #[test]
+    fn sleeps_in_seconds_with_jitter() {
+        let actions = [
+            Action::Delay(1500, DelayJitter::None),
+            Action::Delay(250, DelayJitter::Uniform(50)),
+        ];
+        let export = export_xdotool(&actions);
+
+        assert_eq!(
+            body(&export),
+            [
+                "sleep 1.5",
+                "sleep \"$(awk -v seed=$RANDOM 'BEGIN { srand(seed); t = 250 + (2 * rand() - 1) * 50; print (t < 0 ? 0 : t) / 1000 }')\"",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn exports_windows_and_clipboard() {
+        let actions = [
+            Action::Mouse(MouseActionKind::Moved(MousePointKind::Window(
+                WindowInfo {
+                    title: "Untitled".into(),
+                    class: String::new(),
+                },
+                Point { x: 10, y: 20 },
+            ))),
+            Action::CopyClipboard("order".into()),
+            Action::SetClipboard("#{order} \"$HOME\" it's".into()),
+            Action::Launch(LaunchInfo {
+                program: "/usr/bin/gedit".into(),
+                args: vec!["notes.txt".into()],
+                working_dir: Some("/tmp".into()),
+                wait_for_exit: true,
+                timeout: Some(1500),
+            }),
+        ];
+        let export = export_xdotool(&actions);
+
+        assert_eq!(
+            body(&export),
+            [
+                "xdotool search --onlyvisible --name 'Untitled' mousemove --window %1 10 20",
+                "copied_order=\"$(xclip -selection clipboard -o 2> /dev/null)\" || true",
+                "printf '%s' \"#${copied_order} \\\"\\$HOME\\\" it's\" | xclip -selection clipboard",
+                "(cd '/tmp' && exec '/usr/bin/gedit' 'notes.txt') &",
+                "timeout 1.5 tail --pid=$! -f /dev/null || true",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn comments_out_screen_searches_and_unknown_keys() {
+        let actions = [
+            Action::IfImage(ImageInfo::default()),
+            Action::EndIf,
+            Action::WaitForPixel(PixelInfo::default()),
+            Action::Keyboard(0xFF, KeyState::Pressed, None),
+            Action::Break,
+        ];
+        let export = export_xdotool(&actions);
+
+        assert_eq!(export.warnings.len(), 4);
+        assert_eq!(
+            body(&export)
+                .lines()
+                .filter(|line| line.starts_with("# Left out"))
+                .count(),
+            3
+        );
+        assert!(body(&export).starts_with("if false; then\n    :\nfi\n"));
+    }
+}
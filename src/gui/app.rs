@@ -1,4 +1,7 @@
-use crate::recorder::record_actions;
+use crate::{
+    modals::warning_window::RecordConfirmationWindow,
+    recorder::{record_actions, splice_recording},
+};
 
 use super::*;
 
@@ -23,27 +26,54 @@ impl App for Recorder {
                     .unwrap();
                 play_back_actions(&self.action_list, &self.settings);
                 frame.set_visible(true);
-                self.hotkey_detector_sender =
-                    Some(start_hotkey_detector(&mut self.settings.hotkeys));
+                self.hotkey_detector_sender = Some(start_hotkey_detector(&mut self.settings));
             }
-            if action == RecordPlayAction::Record {
+            if let RecordPlayAction::Record(mode) = action {
                 self.hotkey_detector_sender
                     .take()
                     .unwrap()
                     .send(())
                     .unwrap();
-                self.action_list = record_actions(&self.settings);
+                let (recording, start_position) = record_actions(&self.settings);
+                match mode {
+                    RecordMode::Replace => {
+                        self.action_list = recording;
+                        // The convert points window asks for it when it isn't known
+                        self.metadata.start_position =
+                            start_position.unwrap_or(Point { x: 0, y: 0 });
+                    }
+                    RecordMode::Append => {
+                        let index = self.action_list.len();
+                        splice_recording(&mut self.action_list, index, recording, start_position);
+                    }
+                    RecordMode::InsertAfter(row) => {
+                        splice_recording(&mut self.action_list, row + 1, recording, start_position);
+                    }
+                }
+                self.regenerate_indents();
                 frame.set_visible(true);
-                self.hotkey_detector_sender =
-                    Some(start_hotkey_detector(&mut self.settings.hotkeys));
+                self.hotkey_detector_sender = Some(start_hotkey_detector(&mut self.settings));
             }
             self.next_play_record_action = None;
         }
 
-        if play_key_pressed() && self.action_list.len() > 0 {
-            frame.set_visible(false);
-            frame.set_fullscreen(false);
-            self.next_play_record_action = Some(RecordPlayAction::Play);
+        if !self.are_any_modals_open() {
+            if play_key_pressed(&self.settings) && self.action_list.len() > 0 {
+                frame.set_visible(false);
+                frame.set_fullscreen(false);
+                self.next_play_record_action = Some(RecordPlayAction::Play);
+            } else if record_key_pressed(&self.settings) {
+                self.right_click_dialog = None;
+
+                if self.action_list.len() > 0 {
+                    self.modal = Some(RecordConfirmationWindow::new());
+                } else {
+                    frame.set_visible(false);
+                    frame.set_fullscreen(false);
+                    self.next_play_record_action =
+                        Some(RecordPlayAction::Record(RecordMode::Replace));
+                }
+            }
         }
 
         if self.transparent {
@@ -1,7 +1,7 @@
 use eframe::{egui::*, *};
 use once_cell::sync::OnceCell;
 
-use std::{path::*, rc::Rc, sync::mpsc::Sender};
+use std::{ops::RangeInclusive, path::*, rc::Rc, sync::mpsc::Sender};
 
 pub static PIXELS_PER_POINT: OnceCell<f32> = OnceCell::new();
 pub const ROW_LABEL_X_OFFSET: f32 = 85.0;
@@ -13,9 +13,11 @@ pub mod top_panel;
 use crate::{
     actions::{Action, KeyState, MouseActionKind, Point},
     hotkeys::start_hotkey_detector,
-    load_from_file,
+    load_macro_file,
+    macro_file::MacroMetadata,
     modals::{warning_window::DefaultErrorWindow, ModalWindow},
-    play_back_actions, play_key_pressed,
+    play_back_actions, play_key_pressed, record_key_pressed,
+    recorder::simplify::{movement_rows, simplify_movement_with, MouseSimplification},
     right_click_dialog::ActionRightClickDialog,
     save_macro,
     settings::{self, Settings},
@@ -23,12 +25,16 @@ use crate::{
 
 pub struct Recorder {
     pub selected_row: Option<usize>,
+    /// The other end of a shift click selection.
+    pub selection_anchor: Option<usize>,
     action_list: Vec<Action>,
     indent_list: Vec<i32>,
     pub right_click_dialog: Option<Rc<ActionRightClickDialog>>,
     pub next_play_record_action: Option<RecordPlayAction>,
     pub settings: Settings,
     pub current_macro_path: Option<PathBuf>,
+    /// Kept from the opened macro so saving it again doesn't lose it.
+    pub metadata: MacroMetadata,
     pub transparent: bool,
     pub scroll_to_me_row: Option<usize>,
     pub modal: Option<Rc<dyn ModalWindow>>,
@@ -40,6 +46,69 @@ impl Recorder {
     pub fn action_list(&mut self) -> &mut Vec<Action> {
         &mut self.action_list
     }
+
+    pub fn selected_rows(&self) -> Option<RangeInclusive<usize>> {
+        let selected_row = self.selected_row?;
+        let anchor = self
+            .selection_anchor
+            .filter(|anchor| *anchor < self.action_list.len())
+            .unwrap_or(selected_row);
+
+        Some(anchor.min(selected_row)..=anchor.max(selected_row))
+    }
+
+    /// Simplifies the mouse movement in the selection, or around the selected row if it's the
+    /// only one. Uses path tolerance if simplifying is turned off in the settings.
+    pub fn simplify_selection(&mut self) {
+        let Some(mut rows) = self.selected_rows() else {
+            return;
+        };
+        if rows.start() == rows.end() {
+            rows = movement_rows(&self.action_list, *rows.start());
+        }
+
+        let simplification = match self.settings.mouse_simplification {
+            MouseSimplification::None => MouseSimplification::PathTolerance,
+            simplification => simplification,
+        };
+        let settings = self.settings.clone();
+        self.transform_rows(rows, |actions| {
+            *actions = simplify_movement_with(actions.clone(), simplification, &settings);
+        });
+    }
+
+    /// Changes the selected rows if there's more than one, otherwise the whole macro.
+    pub fn transform_selection(&mut self, transform: impl FnOnce(&mut Vec<Action>)) {
+        match self.selected_rows() {
+            Some(rows) if rows.start() != rows.end() => self.transform_rows(rows, transform),
+            _ => {
+                transform(&mut self.action_list);
+
+                if self.selected_row >= Some(self.action_list.len()) {
+                    self.selected_row = None;
+                }
+                self.regenerate_indents();
+            }
+        }
+    }
+
+    /// Replaces the rows with their transformed version and selects it.
+    fn transform_rows(
+        &mut self,
+        rows: RangeInclusive<usize>,
+        transform: impl FnOnce(&mut Vec<Action>),
+    ) {
+        let mut section = self.action_list[rows.clone()].to_vec();
+        transform(&mut section);
+
+        let start = *rows.start();
+        let length = section.len();
+        self.action_list.splice(rows, section);
+
+        self.selected_row = (length > 0).then_some(start);
+        self.selection_anchor = (length > 0).then(|| start + length - 1);
+        self.regenerate_indents();
+    }
 }
 
 const TOP_PANEL_HEIGHT: f32 = 65.0;
@@ -49,7 +118,15 @@ const SIDE_PANEL_WIDTH: f32 = 65.0;
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub enum RecordPlayAction {
     Play,
-    Record,
+    Record(RecordMode),
+}
+
+/// Where a new recording goes in the action list.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum RecordMode {
+    Replace,
+    Append,
+    InsertAfter(usize),
 }
 
 impl Recorder {
@@ -111,20 +188,19 @@ impl Recorder {
             None
         };
 
-        let hotkey_detector_sender = Some(start_hotkey_detector(
-            settings
-                .as_mut()
-                .map_or(&mut vec![], |settings| &mut settings.hotkeys),
-        ));
+        let mut settings = settings.unwrap_or_default();
+        let hotkey_detector_sender = Some(start_hotkey_detector(&mut settings));
 
         Self {
             selected_row: None,
+            selection_anchor: None,
             action_list: vec![],
             indent_list: vec![],
             right_click_dialog: None,
             next_play_record_action: None,
-            settings: settings.unwrap_or(Default::default()),
+            settings,
             current_macro_path: None,
+            metadata: Default::default(),
             transparent: false,
             scroll_to_me_row: None,
             modal: warning_window,
@@ -147,6 +223,7 @@ impl Recorder {
     }
 
     fn create_action(&mut self, action: Action) {
+        self.selection_anchor = None;
         if let Some(row) = self.selected_row {
             self.action_list.insert(row + 1, action);
             self.selected_row = Some(row + 1);
@@ -228,8 +305,8 @@ impl Recorder {
     ) {
         let mut start_pos = 0.0;
         for row in row_range.clone().into_iter() {
-            let button_color = if let Some(selected_row) = self.selected_row {
-                if selected_row == row {
+            let button_color = if let Some(selected_rows) = self.selected_rows() {
+                if selected_rows.contains(&row) {
                     if self.moving_row {
                         Color32::from_rgba_premultiplied(220, 239, 250, 255)
                     } else {
@@ -254,11 +331,20 @@ impl Recorder {
             }
 
             if response.clicked() {
+                if ui.input().modifiers.shift && self.selected_row.is_some() {
+                    self.selection_anchor = self.selection_anchor.or(self.selected_row);
+                } else {
+                    self.selection_anchor = None;
+                }
                 self.selected_row = Some(row);
                 self.right_click_dialog = None;
             }
 
             if response.secondary_clicked() {
+                // Right clicking inside the selection keeps it for the dialog
+                if !self.selected_rows().is_some_and(|rows| rows.contains(&row)) {
+                    self.selection_anchor = None;
+                }
                 self.selected_row = Some(row);
 
                 self.right_click_dialog = Some(Rc::new(
@@ -333,6 +419,7 @@ impl Recorder {
         if ui.input().key_pressed(Key::Delete) || ui.input().key_pressed(Key::Backspace) {
             if let Some(selected_row) = self.selected_row {
                 self.action_list.remove(selected_row);
+                self.selection_anchor = None;
 
                 if Some(self.action_list.len()) <= self.selected_row {
                     self.selected_row = None;
@@ -346,6 +433,7 @@ impl Recorder {
         if ui.input().key_pressed(Key::Escape) {
             self.right_click_dialog = None;
             self.selected_row = None;
+            self.selection_anchor = None;
         }
 
         if ui.input().modifiers.ctrl && ui.input().key_pressed(Key::S) && self.action_list.len() > 0
@@ -366,7 +454,7 @@ impl Recorder {
                 .unwrap()
                 .send(())
                 .unwrap();
-            self.hotkey_detector_sender = Some(start_hotkey_detector(&mut self.settings.hotkeys));
+            self.hotkey_detector_sender = Some(start_hotkey_detector(&mut self.settings));
         }
 
         if let (true, Some(..)) = (ui.input().key_pressed(Key::Enter), self.selected_row) {
@@ -382,6 +470,7 @@ impl Recorder {
         }
 
         if ui.input().key_pressed(Key::ArrowUp) {
+            self.selection_anchor = None;
             if let Some(selected_row) = self.selected_row {
                 if selected_row > 0 {
                     self.selected_row = Some(selected_row - 1);
@@ -392,6 +481,7 @@ impl Recorder {
         }
 
         if ui.input().key_pressed(Key::ArrowDown) {
+            self.selection_anchor = None;
             if let Some(selected_row) = self.selected_row {
                 if selected_row < self.action_list.len() - 1 {
                     self.selected_row = Some(selected_row + 1);
@@ -407,7 +497,8 @@ impl Recorder {
     }
 
     fn try_save(&mut self, path: PathBuf, frame: &mut eframe::Frame) {
-        let save_result = save_macro(&path, &self.action_list);
+        self.metadata.touch(&self.settings.author);
+        let save_result = save_macro(&path, &self.action_list, &self.metadata);
 
         if let Err(error) = save_result {
             self.modal = Some(DefaultErrorWindow::new(
@@ -475,7 +566,10 @@ impl Recorder {
         for (action, indent) in self.action_list.iter().zip(self.indent_list.iter_mut()) {
             *indent = indent_count;
             match *action {
-                Action::IfImage(..) | Action::IfPixel(..) | Action::Repeat(..) => indent_count += 1,
+                Action::IfImage(..)
+                | Action::IfPixel(..)
+                | Action::IfClipboard(..)
+                | Action::Repeat(..) => indent_count += 1,
                 Action::EndIf | Action::EndRepeat => indent_count -= 1,
                 _ => {}
             }
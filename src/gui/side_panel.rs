@@ -76,7 +76,7 @@ impl Recorder {
                         || (ui.input().key_pressed(Key::Num2) && !self.are_any_modals_open())
                     {
                         self.create_action_window(
-                            Action::Keyboard(0x41, KeyState::Pressed),
+                            Action::Keyboard(0x41, KeyState::Pressed, None),
                             screen_dimensions,
                             ctx,
                         );
@@ -119,6 +119,17 @@ impl Recorder {
                         self.modal =
                             Some(Rc::new(ActionListWindow::new(ActionListCategory::Other, 6)));
                     }
+
+                    ui.allocate_space(vec2(0.0, 30.0));
+
+                    if ui.button(RichText::new("System").size(19.0)).clicked()
+                        || (ui.input().key_pressed(Key::Num7) && !self.are_any_modals_open())
+                    {
+                        self.modal = Some(Rc::new(ActionListWindow::new(
+                            ActionListCategory::System,
+                            7,
+                        )));
+                    }
                 });
             });
     }
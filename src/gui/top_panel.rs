@@ -3,6 +3,11 @@ use crate::modals::{
     settings_window::SettingsWindow,
     warning_window::{DefaultErrorWindow, RecordConfirmationWindow},
 };
+use crate::{
+    export::{export_macro, exporters},
+    import::import_macro,
+    script::SCRIPT_EXTENSION,
+};
 
 use super::*;
 
@@ -79,7 +84,8 @@ impl Recorder {
                         if self.action_list.len() > 0 {
                             self.modal = Some(RecordConfirmationWindow::new());
                         } else {
-                            self.next_play_record_action = Some(RecordPlayAction::Record);
+                            self.next_play_record_action =
+                                Some(RecordPlayAction::Record(RecordMode::Replace));
                             frame.set_visible(false);
                             frame.set_fullscreen(false);
                         }
@@ -98,15 +104,17 @@ impl Recorder {
                         self.right_click_dialog = None;
                         let path = rfd::FileDialog::new()
                             .add_filter("fluffy macro", &["floof"])
+                            .add_filter("fluffy macro script", &[SCRIPT_EXTENSION])
                             .add_filter("All files", &["*"])
                             .pick_file();
                         if let Some(path) = path {
-                            let load_result = load_from_file(&path);
+                            let load_result = load_macro_file(&path);
 
                             match load_result {
                                 Ok(result) => {
                                     self.current_macro_path = Some(path);
-                                    self.action_list = result;
+                                    self.action_list = result.actions;
+                                    self.metadata = result.metadata;
                                     self.regenerate_indents();
                                     self.update_title(frame);
                                 }
@@ -120,6 +128,41 @@ impl Recorder {
                         }
                     }
 
+                    ui.allocate_space(vec2(20.0, 0.0));
+
+                    if ui.button("Import").clicked() {
+                        self.right_click_dialog = None;
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("AutoHotkey script", &["ahk"])
+                            .add_filter("xdotool script", &["sh"])
+                            .pick_file()
+                        {
+                            match import_macro(&path) {
+                                Ok(import) => {
+                                    // Saving shouldn't write over the script it came from
+                                    self.current_macro_path = None;
+                                    self.action_list = import.actions;
+                                    self.metadata = Default::default();
+                                    self.regenerate_indents();
+                                    frame.set_window_title("Fluffy Macro Recorder");
+
+                                    if !import.warnings.is_empty() {
+                                        self.modal = Some(DefaultErrorWindow::new(
+                                            "Import Warnings".into(),
+                                            import.warnings,
+                                        ))
+                                    }
+                                }
+                                Err(error) => {
+                                    self.modal = Some(DefaultErrorWindow::new(
+                                        "Import Error".into(),
+                                        vec!["Error importing script:".into(), error.to_string()],
+                                    ))
+                                }
+                            }
+                        }
+                    }
+
                     if self.action_list.len() > 0 {
                         ui.allocate_space(vec2(20.0, 0.0));
 
@@ -141,7 +184,7 @@ impl Recorder {
                                 .send(())
                                 .unwrap();
                             self.hotkey_detector_sender =
-                                Some(start_hotkey_detector(&mut self.settings.hotkeys));
+                                Some(start_hotkey_detector(&mut self.settings));
                         }
 
                         ui.allocate_space(vec2(20.0, 0.0));
@@ -150,6 +193,7 @@ impl Recorder {
                             self.right_click_dialog = None;
                             if let Some(path) = rfd::FileDialog::new()
                                 .add_filter("fluffy macro", &["floof"])
+                                .add_filter("fluffy macro script", &[SCRIPT_EXTENSION])
                                 .save_file()
                             {
                                 self.try_save(path, frame);
@@ -159,7 +203,39 @@ impl Recorder {
                                     .send(())
                                     .unwrap();
                                 self.hotkey_detector_sender =
-                                    Some(start_hotkey_detector(&mut self.settings.hotkeys));
+                                    Some(start_hotkey_detector(&mut self.settings));
+                            }
+                        }
+
+                        ui.allocate_space(vec2(20.0, 0.0));
+
+                        if ui.button("Export").clicked() {
+                            self.right_click_dialog = None;
+                            let dialog = exporters().iter().fold(
+                                rfd::FileDialog::new(),
+                                |dialog, exporter| {
+                                    dialog.add_filter(exporter.name(), &[exporter.extension()])
+                                },
+                            );
+                            if let Some(path) = dialog.save_file() {
+                                match export_macro(&path, &self.action_list) {
+                                    Ok(warnings) if warnings.is_empty() => {}
+                                    Ok(warnings) => {
+                                        self.modal = Some(DefaultErrorWindow::new(
+                                            "Export Warnings".into(),
+                                            warnings,
+                                        ))
+                                    }
+                                    Err(error) => {
+                                        self.modal = Some(DefaultErrorWindow::new(
+                                            "Export Error".into(),
+                                            vec![
+                                                "Error exporting macro:".into(),
+                                                error.to_string(),
+                                            ],
+                                        ))
+                                    }
+                                }
                             }
                         }
                     }
@@ -9,13 +9,14 @@ use std::{
     },
     thread,
 };
-use winapi::um::winuser::GetAsyncKeyState;
 
 use crate::{
     actions::Action,
+    input::key_pressed,
     load_from_file,
     modals::{warning_window::DefaultErrorWindow, ModalWindow},
     play_back_actions,
+    settings::Settings,
 };
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -25,11 +26,18 @@ pub struct HotkeyMacro {
     pub repeat_if_held: bool,
 }
 
-pub fn start_hotkey_detector(hotkeys: &mut Vec<HotkeyMacro>) -> Sender<()> {
+pub fn start_hotkey_detector(settings: &mut Settings) -> Sender<()> {
     let (sender, receiver) = channel();
 
     let mut loaded_hotkeys = vec![];
 
+    // Hotkey macros play once each, but can still be stopped with the stop hotkey
+    let playback_settings = Settings {
+        stop_hotkey: settings.stop_hotkey.clone(),
+        ..Default::default()
+    };
+
+    let hotkeys = &mut settings.hotkeys;
     for (index, hotkey) in hotkeys.clone().into_iter().enumerate() {
         let Some(path) = &hotkey.path else {
             continue;
@@ -44,6 +52,7 @@ pub fn start_hotkey_detector(hotkeys: &mut Vec<HotkeyMacro>) -> Sender<()> {
             action_list,
             key_combination: hotkey.hotkeys,
             repeat_if_held: hotkey.repeat_if_held,
+            settings: playback_settings.clone(),
         });
     }
 
@@ -82,7 +91,7 @@ fn action_executor(hotkey_macro: LoadedHotkeyMacro, receiver: Receiver<()>) {
         }
 
         if hotkeys_pressed(&hotkey_macro.key_combination) {
-            play_back_actions(&hotkey_macro.action_list, &Default::default());
+            play_back_actions(&hotkey_macro.action_list, &hotkey_macro.settings);
         }
 
         if !hotkey_macro.repeat_if_held {
@@ -98,9 +107,14 @@ fn action_executor(hotkey_macro: LoadedHotkeyMacro, receiver: Receiver<()>) {
     }
 }
 
-fn hotkeys_pressed(hotkeys: &[i32]) -> bool {
+/// Whether every key of the combination is held down, an empty combination is never pressed.
+pub fn hotkeys_pressed(hotkeys: &[i32]) -> bool {
+    if hotkeys.is_empty() {
+        return false;
+    }
+
     for hotkey in hotkeys {
-        if !unsafe { GetAsyncKeyState(*hotkey) < 0 } {
+        if !key_pressed(*hotkey) {
             return false;
         }
     }
@@ -111,4 +125,5 @@ struct LoadedHotkeyMacro {
     action_list: Vec<Action>,
     key_combination: Vec<i32>,
     repeat_if_held: bool,
+    settings: Settings,
 }
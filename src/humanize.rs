@@ -0,0 +1,154 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+
+use crate::actions::{DelayJitter, Point};
+
+/// The same seed gives the same delays and mouse paths on every run.
+pub fn new_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+pub fn jittered_delay(delay: u32, jitter: DelayJitter, rng: &mut StdRng) -> u32 {
+    let offset = match jitter {
+        DelayJitter::None => 0.0,
+        DelayJitter::Uniform(range) => rng.gen_range(-(range as f64)..=range as f64),
+        DelayJitter::Normal(deviation) => {
+            Normal::new(0.0, deviation as f64).map_or(0.0, |normal| normal.sample(rng))
+        }
+    };
+
+    (delay as f64 + offset).max(0.0).round() as u32
+}
+
+/// Cubic bezier from one point to another, bowed to a random side like a hand movement.
+pub struct MouseCurve {
+    points: [(f64, f64); 4],
+}
+
+impl MouseCurve {
+    pub fn new(from: Point, to: Point, rng: &mut StdRng) -> Self {
+        let from = (from.x as f64, from.y as f64);
+        let to = (to.x as f64, to.y as f64);
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let distance = dx.hypot(dy);
+
+        let normal = if distance > 0.0 {
+            (-dy / distance, dx / distance)
+        } else {
+            (0.0, 0.0)
+        };
+        let bow = rng.gen_range(-0.25..=0.25) * distance;
+
+        let control = |along: f64, bow: f64| {
+            (
+                from.0 + dx * along + normal.0 * bow,
+                from.1 + dy * along + normal.1 * bow,
+            )
+        };
+
+        Self {
+            points: [
+                from,
+                control(rng.gen_range(0.2..=0.4), bow),
+                control(rng.gen_range(0.6..=0.8), bow * rng.gen_range(0.5..=1.0)),
+                to,
+            ],
+        }
+    }
+
+    pub fn distance(&self) -> f64 {
+        let [from, _, _, to] = self.points;
+        (to.0 - from.0).hypot(to.1 - from.1)
+    }
+
+    /// Position at a progress from 0 to 1, eased so the movement speeds up and slows down.
+    pub fn at(&self, progress: f64) -> Point {
+        let t = progress.clamp(0.0, 1.0);
+        let t = t * t * (3.0 - 2.0 * t);
+        let u = 1.0 - t;
+
+        let weights = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+        let (x, y) = self
+            .points
+            .iter()
+            .zip(weights)
+            .fold((0.0, 0.0), |(x, y), (point, weight)| {
+                (x + point.0 * weight, y + point.1 * weight)
+            });
+
+        Point {
+            x: x.round() as i32,
+            y: y.round() as i32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Jittered delays and points along a curve, everything the generator decides.
+    fn run(seed: u64) -> (Vec<u32>, Vec<Point>) {
+        let mut rng = new_rng(Some(seed));
+
+        let delays = (0..20)
+            .flat_map(|_| [DelayJitter::Uniform(30), DelayJitter::Normal(30)])
+            .map(|jitter| jittered_delay(100, jitter, &mut rng))
+            .collect();
+
+        let curve = MouseCurve::new(Point { x: 0, y: 0 }, Point { x: 300, y: 200 }, &mut rng);
+        let path = (0..=10).map(|step| curve.at(step as f64 / 10.0)).collect();
+
+        (delays, path)
+    }
+
+    #[test]
+    fn same_seed_repeats_delays_and_paths() {
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    #[test]
+    fn curves_start_and_end_at_their_points() {
+        let (_, path) = run(7);
+
+        assert_eq!(path.first(), Some(&Point { x: 0, y: 0 }));
+        assert_eq!(path.last(), Some(&Point { x: 300, y: 200 }));
+    }
+
+    #[test]
+    fn uniform_jitter_stays_within_its_range() {
+        let mut rng = new_rng(Some(1));
+
+        for _ in 0..1000 {
+            let delay = jittered_delay(100, DelayJitter::Uniform(30), &mut rng);
+            assert!((70..=130).contains(&delay), "{}", delay);
+        }
+    }
+
+    #[test]
+    fn normal_jitter_stays_near_the_delay() {
+        let mut rng = new_rng(Some(1));
+        let delays = (0..1000)
+            .map(|_| jittered_delay(100, DelayJitter::Normal(10), &mut rng))
+            .collect::<Vec<_>>();
+
+        assert!(delays.iter().all(|delay| (50..=150).contains(delay)));
+        let mean = delays.iter().sum::<u32>() as f64 / delays.len() as f64;
+        assert!((mean - 100.0).abs() < 2.0, "{}", mean);
+    }
+
+    #[test]
+    fn clamps_jittered_delays_at_zero() {
+        let mut rng = new_rng(Some(1));
+        let delays = (0..1000)
+            .map(|_| jittered_delay(10, DelayJitter::Uniform(500), &mut rng))
+            .collect::<Vec<_>>();
+
+        assert!(delays.contains(&0));
+        assert!(delays.iter().all(|delay| *delay <= 510));
+    }
+}
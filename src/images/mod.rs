@@ -7,11 +7,19 @@ use rayon::slice::ParallelSlice;
 use rayon::{prelude::ParallelIterator, slice::ParallelSliceMut};
 use serde::de::{MapAccess, SeqAccess, Visitor};
 use serde::ser::SerializeStruct;
-use std::ffi::c_void;
-use std::mem::zeroed;
-use std::ptr::null_mut;
-use winapi::shared::windef::POINT;
-use winapi::um::{wingdi::*, winuser::*};
+use std::io::Cursor;
+
+use crate::{actions::Point, input::cursor_position};
+
+#[cfg(windows)]
+mod win32;
+#[cfg(target_os = "linux")]
+mod x11;
+
+#[cfg(windows)]
+use win32 as backend;
+#[cfg(target_os = "linux")]
+use x11 as backend;
 
 const RESIZE_FACTOR: u32 = 3;
 
@@ -152,6 +160,63 @@ impl RawScreenshot {
         });
         self
     }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let rgba = self.clone().to_rgba8();
+        let image = RgbaImage::from_raw(self.width as u32, self.height as u32, rgba.pixels)
+            .ok_or("The screenshot's size doesn't match its pixels")?;
+
+        encode_png(DynamicImage::ImageRgba8(image))
+    }
+
+    pub fn from_png(png: &[u8], x: i32, y: i32) -> Result<Self, Box<dyn std::error::Error>> {
+        let image = load_from_memory_with_format(png, ImageFormat::Png)?.to_rgba8();
+
+        // Swapping red and blue again gives back BGRA
+        Ok(Self {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            pixels: image.into_raw(),
+            x,
+            y,
+        }
+        .to_rgba8())
+    }
+}
+
+impl RawScreenshotPair {
+    /// Pairs the screenshot with the grayscale version images are searched for with.
+    pub fn from_color(color: RawScreenshot) -> Result<Self, Box<dyn std::error::Error>> {
+        let image = RgbaImage::from_raw(
+            color.width as u32,
+            color.height as u32,
+            color.pixels.clone(),
+        )
+        .ok_or("The screenshot's size doesn't match its pixels")?;
+
+        Ok(Self {
+            gray: GrayImageSerializable(DynamicImage::ImageRgba8(image).to_luma8()),
+            color,
+        })
+    }
+}
+
+impl GrayImageSerializable {
+    pub fn to_png(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        encode_png(DynamicImage::ImageLuma8(self.0.clone()))
+    }
+
+    pub fn from_png(png: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self(
+            load_from_memory_with_format(png, ImageFormat::Png)?.to_luma8(),
+        ))
+    }
+}
+
+fn encode_png(image: DynamicImage) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut png = Cursor::new(vec![]);
+    image.write_to(&mut png, ImageOutputFormat::Png)?;
+    Ok(png.into_inner())
 }
 
 impl Clone for RawScreenshot {
@@ -184,48 +249,13 @@ fn lesser(one: f32, two: f32) -> f32 {
 
 pub const IMAGE_PANEL_IMAGE_SIZE: f32 = 225.0;
 
-// From https://stackoverflow.com/questions/3291167/how-can-i-take-a-screenshot-in-a-windows-application
 pub fn screenshot(corner1: Pos2, corner2: Pos2) -> RawScreenshot {
-    unsafe {
-        let left_x = lesser(corner1.x, corner2.x) as i32;
-        let top_y = lesser(corner1.y, corner2.y) as i32;
-
-        let width = (corner1.x - corner2.x).abs() as i32;
-        let height = (corner1.y - corner2.y).abs() as i32;
-
-        let dc_screen = GetDC(null_mut());
-        let dc_target = CreateCompatibleDC(dc_screen);
-        let bmp_target = CreateCompatibleBitmap(dc_screen, width, height);
-        let old_bmp = SelectObject(dc_target, bmp_target as *mut c_void);
-        BitBlt(
-            dc_target,
-            0,
-            0,
-            width,
-            height,
-            dc_screen,
-            left_x,
-            top_y,
-            SRCCOPY | CAPTUREBLT,
-        );
-        SelectObject(dc_target, old_bmp);
-        DeleteDC(dc_target);
-        ReleaseDC(null_mut(), dc_screen);
-
-        let mut pixels = vec![0u8; (width * height) as usize * 4];
-        GetBitmapBits(
-            bmp_target,
-            width * height * 4,
-            pixels.as_mut_ptr() as *mut c_void,
-        );
-
-        RawScreenshot {
-            pixels,
-            width: width as usize,
-            height: height as usize,
-            x: left_x,
-            y: top_y,
-        }
+    RawScreenshot {
+        pixels: screenshot_raw(corner1, corner2),
+        width: (corner1.x - corner2.x).abs() as usize,
+        height: (corner1.y - corner2.y).abs() as usize,
+        x: lesser(corner1.x, corner2.x) as i32,
+        y: lesser(corner1.y, corner2.y) as i32,
     }
 }
 
@@ -246,43 +276,19 @@ pub fn screenshot_to_color_image(screenshot: RawScreenshot) -> ColorImage {
     ColorImage::from_rgba_unmultiplied([width, height], &pixels_bgra)
 }
 
-// From https://stackoverflow.com/questions/3291167/how-can-i-take-a-screenshot-in-a-windows-application
+/// The pixels between the corners, in BGRA order.
 pub fn screenshot_raw(corner1: Pos2, corner2: Pos2) -> Vec<u8> {
-    unsafe {
-        let left_x = lesser(corner1.x, corner2.x) as i32;
-        let top_y = lesser(corner1.y, corner2.y) as i32;
-
-        let width = (corner1.x - corner2.x).abs() as i32;
-        let height = (corner1.y - corner2.y).abs() as i32;
-
-        let dc_screen = GetDC(null_mut());
-        let dc_target = CreateCompatibleDC(dc_screen);
-        let bmp_target = CreateCompatibleBitmap(dc_screen, width, height);
-        let old_bmp = SelectObject(dc_target, bmp_target as *mut c_void);
-        BitBlt(
-            dc_target,
-            0,
-            0,
-            width,
-            height,
-            dc_screen,
-            left_x,
-            top_y,
-            SRCCOPY | CAPTUREBLT,
-        );
-        SelectObject(dc_target, old_bmp);
-        DeleteDC(dc_target);
-        ReleaseDC(null_mut(), dc_screen);
-
-        let mut pixels = vec![0u8; (width * height) as usize * 4];
-        GetBitmapBits(
-            bmp_target,
-            width * height * 4,
-            pixels.as_mut_ptr() as *mut c_void,
-        );
-
-        pixels
-    }
+    backend::capture(
+        lesser(corner1.x, corner2.x) as i32,
+        lesser(corner1.y, corner2.y) as i32,
+        (corner1.x - corner2.x).abs() as i32,
+        (corner1.y - corner2.y).abs() as i32,
+    )
+}
+
+/// The size of the area covered by every monitor.
+pub fn screen_size() -> (i32, i32) {
+    backend::screen_size()
 }
 
 pub fn find_image(
@@ -290,12 +296,7 @@ pub fn find_image(
     search_coordinates: Option<(Pos2, Pos2)>,
 ) -> (f32, (i32, i32)) {
     let search_coordinates = search_coordinates.unwrap_or_else(|| {
-        let (width, height) = unsafe {
-            (
-                GetSystemMetrics(SM_CXVIRTUALSCREEN),
-                GetSystemMetrics(SM_CYVIRTUALSCREEN),
-            )
-        };
+        let (width, height) = screen_size();
         (pos2(0.0, 0.0), pos2(width as f32, height as f32))
     });
 
@@ -364,12 +365,7 @@ pub fn fast_find_image(
     search_coordinates: Option<(Pos2, Pos2)>,
 ) -> (f32, (i32, i32)) {
     let search_coordinates = search_coordinates.unwrap_or_else(|| {
-        let (width, height) = unsafe {
-            (
-                GetSystemMetrics(SM_CXVIRTUALSCREEN),
-                GetSystemMetrics(SM_CYVIRTUALSCREEN),
-            )
-        };
+        let (width, height) = screen_size();
         (pos2(0.0, 0.0), pos2(width as f32, height as f32))
     });
 
@@ -429,14 +425,8 @@ pub fn fast_find_image(
 }
 
 pub fn get_color_under_mouse() -> Color32 {
-    unsafe {
-        let dc_screen = GetDC(null_mut());
-        let mut point = zeroed();
-        GetCursorPos(&mut point);
+    let point = cursor_position().unwrap_or(Point { x: 0, y: 0 });
+    let bgra = backend::capture(point.x, point.y, 1, 1);
 
-        let color = GetPixel(dc_screen, point.x, point.y);
-        ReleaseDC(null_mut(), dc_screen);
-
-        Color32::from_rgba_premultiplied(GetRValue(color), GetGValue(color), GetBValue(color), 255)
-    }
+    Color32::from_rgb(bgra[2], bgra[1], bgra[0])
 }
@@ -0,0 +1,46 @@
+use std::{ffi::c_void, ptr::null_mut};
+
+use winapi::um::{wingdi::*, winuser::*};
+
+// From https://stackoverflow.com/questions/3291167/how-can-i-take-a-screenshot-in-a-windows-application
+pub fn capture(x: i32, y: i32, width: i32, height: i32) -> Vec<u8> {
+    unsafe {
+        let dc_screen = GetDC(null_mut());
+        let dc_target = CreateCompatibleDC(dc_screen);
+        let bmp_target = CreateCompatibleBitmap(dc_screen, width, height);
+        let old_bmp = SelectObject(dc_target, bmp_target as *mut c_void);
+        BitBlt(
+            dc_target,
+            0,
+            0,
+            width,
+            height,
+            dc_screen,
+            x,
+            y,
+            SRCCOPY | CAPTUREBLT,
+        );
+        SelectObject(dc_target, old_bmp);
+        DeleteDC(dc_target);
+        ReleaseDC(null_mut(), dc_screen);
+
+        let mut pixels = vec![0u8; (width * height) as usize * 4];
+        GetBitmapBits(
+            bmp_target,
+            width * height * 4,
+            pixels.as_mut_ptr() as *mut c_void,
+        );
+        DeleteObject(bmp_target as *mut c_void);
+
+        pixels
+    }
+}
+
+pub fn screen_size() -> (i32, i32) {
+    unsafe {
+        (
+            GetSystemMetrics(SM_CXVIRTUALSCREEN),
+            GetSystemMetrics(SM_CYVIRTUALSCREEN),
+        )
+    }
+}
@@ -0,0 +1,59 @@
+use std::error::Error;
+
+use x11rb::{
+    connection::Connection,
+    protocol::xproto::{ConnectionExt as _, ImageFormat},
+};
+
+/// Blank pixels are returned if the screen can't be read, like on Windows.
+pub fn capture(x: i32, y: i32, width: i32, height: i32) -> Vec<u8> {
+    match try_capture(x, y, width, height) {
+        Ok(pixels) => pixels,
+        Err(error) => {
+            println!("Failed to take a screenshot: {}", error);
+            vec![0; (width * height).max(0) as usize * 4]
+        }
+    }
+}
+
+fn try_capture(x: i32, y: i32, width: i32, height: i32) -> Result<Vec<u8>, Box<dyn Error>> {
+    let (connection, screen_num) = x11rb::connect(None)?;
+    let root = connection.setup().roots[screen_num].root;
+
+    let reply = connection
+        .get_image(
+            ImageFormat::Z_PIXMAP,
+            root,
+            x as i16,
+            y as i16,
+            width as u16,
+            height as u16,
+            u32::MAX,
+        )?
+        .reply()?;
+
+    // Depth 24 and 32 screens store each pixel as four bytes in BGRX order, the padding byte
+    // becomes an opaque alpha
+    if reply.data.len() != (width * height) as usize * 4 {
+        return Err(format!("Unsupported screen depth {}", reply.depth).into());
+    }
+
+    let mut pixels = reply.data;
+    for bgra in pixels.chunks_mut(4) {
+        bgra[3] = 255;
+    }
+
+    Ok(pixels)
+}
+
+pub fn screen_size() -> (i32, i32) {
+    let Ok((connection, screen_num)) = x11rb::connect(None) else {
+        return (0, 0);
+    };
+    let screen = &connection.setup().roots[screen_num];
+
+    (
+        screen.width_in_pixels as i32,
+        screen.height_in_pixels as i32,
+    )
+}
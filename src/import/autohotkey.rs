@@ -0,0 +1,572 @@
+//! AutoHotkey v1 and v2 scripts. `Send`, `Click`, `MouseMove`, `Sleep`, `Loop` and `break` are
+//! understood with literal arguments, other commands are skipped along with their blocks. Hotkeys
+//! and labels are skipped on their own, so the commands under them run as one macro.
+
+use super::{character_key, Import};
+use crate::{actions::*, export::autohotkey::key_code, recorder::keymap::*, script::ScriptError};
+
+/// Commands whose block may start on the next line.
+const BLOCK_COMMANDS: [&str; 8] = [
+    "if", "else", "while", "for", "loop", "try", "catch", "finally",
+];
+
+/// What the line after a command without an opening brace belongs to.
+enum Pending {
+    /// The block of a loop, repeating the amount of times.
+    Loop(usize, usize),
+    /// The block or line under a command that's skipped.
+    Skip,
+}
+
+pub fn import_autohotkey(text: &str) -> Result<Import, ScriptError> {
+    let mut import = Import::new();
+    // Lines the open loops started on
+    let mut loops = vec![];
+    let mut pending = None;
+    // How deep into blocks that are skipped
+    let mut skipping = 0;
+    let mut in_comment = false;
+
+    for (index, raw) in text.lines().enumerate() {
+        let number = index + 1;
+        let line = strip_comment(raw).trim();
+
+        if in_comment {
+            in_comment = !line.starts_with("*/");
+            continue;
+        }
+        if line.starts_with("/*") {
+            in_comment = true;
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(pending) = pending.take() {
+            match (pending, line == "{") {
+                (Pending::Loop(start, amount), true) => {
+                    import.actions.push(Action::Repeat(amount));
+                    loops.push(start);
+                }
+                (Pending::Loop(start, _), false) => {
+                    return Err(ScriptError {
+                        line: start,
+                        column: 1,
+                        message: "Loops need their block in braces".into(),
+                    })
+                }
+                (Pending::Skip, true) => skipping += 1,
+                (Pending::Skip, false) => {}
+            }
+            continue;
+        }
+
+        if skipping > 0 {
+            if line.starts_with('}') {
+                skipping -= 1;
+            }
+            if line.ends_with('{') {
+                skipping += 1;
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix('}') {
+            if loops.pop().is_none() {
+                return Err(ScriptError {
+                    line: number,
+                    column: column(raw),
+                    message: "There's no block to close".into(),
+                });
+            }
+            import.actions.push(Action::EndRepeat);
+
+            let rest = rest.trim();
+            if !rest.is_empty() {
+                skip(&mut import, number, rest, &mut skipping, &mut pending);
+            }
+            continue;
+        }
+
+        // Directives only change how scripts run
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.contains("::") || (line.ends_with(':') && !line.contains(char::is_whitespace)) {
+            import.warn(
+                number,
+                format!(
+                    "`{}` is a hotkey or label, the commands under it are imported with the rest",
+                    line
+                ),
+            );
+            continue;
+        }
+
+        let name_end = line
+            .find(|c: char| !c.is_alphanumeric() && c != '_')
+            .unwrap_or(line.len());
+        let name = line[..name_end].to_ascii_lowercase();
+        let mut args = line[name_end..].trim();
+        let opens_block = args.ends_with('{');
+        if opens_block {
+            args = args[..args.len() - 1].trim_end();
+        }
+        args = args.strip_prefix(',').unwrap_or(args).trim();
+        if let Some(inner) = args
+            .strip_prefix('(')
+            .and_then(|args| args.strip_suffix(')'))
+        {
+            args = inner.trim();
+        }
+
+        let understood = match name.as_str() {
+            "send" | "sendinput" | "sendevent" | "sendplay" => {
+                send(&mut import, number, &unquote(args));
+                true
+            }
+            "sendtext" => {
+                import.type_text(number, &unquote(args));
+                true
+            }
+            "click" => click(&mut import, args),
+            "mousemove" => mouse_move(&mut import, args),
+            "sleep" => match args.parse() {
+                Ok(delay) => {
+                    import.actions.push(Action::Delay(delay, DelayJitter::None));
+                    true
+                }
+                Err(_) => false,
+            },
+            "loop" => match (args.is_empty(), args.parse::<usize>()) {
+                // Repeating 0 times would be forever in a macro
+                (false, Ok(0)) | (false, Err(_)) => false,
+                (true, _) | (false, Ok(_)) => {
+                    let amount = args.parse().unwrap_or(0);
+                    if opens_block {
+                        import.actions.push(Action::Repeat(amount));
+                        loops.push(number);
+                    } else {
+                        pending = Some(Pending::Loop(number, amount));
+                    }
+                    true
+                }
+            },
+            "break" if args.is_empty() && !loops.is_empty() => {
+                import.actions.push(Action::Break);
+                true
+            }
+            _ => false,
+        };
+
+        if !understood {
+            skip(&mut import, number, line, &mut skipping, &mut pending);
+        }
+    }
+
+    match loops.last() {
+        Some(&start) => Err(ScriptError {
+            line: start,
+            column: 1,
+            message: "This loop isn't closed".into(),
+        }),
+        None => Ok(import),
+    }
+}
+
+/// Skips a line that isn't understood, along with the block under it.
+fn skip(
+    import: &mut Import,
+    number: usize,
+    line: &str,
+    skipping: &mut usize,
+    pending: &mut Option<Pending>,
+) {
+    let name = line
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    if line.ends_with('{') {
+        *skipping += 1;
+        import.warn(
+            number,
+            format!("`{}` isn't supported, skipped with its block", line),
+        );
+    } else if BLOCK_COMMANDS.contains(&name.as_str()) {
+        *pending = Some(Pending::Skip);
+        import.warn(
+            number,
+            format!("`{}` isn't supported, skipped with its block", line),
+        );
+    } else {
+        import.warn(number, format!("`{}` isn't supported, skipped", line));
+    }
+}
+
+/// Sends keys the way `Send` does, with `^!+#` holding down modifiers for the next key and keys
+/// written by name in braces such as `{Enter}`, `{a down}` or `{Tab 3}`.
+fn send(import: &mut Import, number: usize, keys: &str) {
+    let mut modifiers = vec![];
+    let mut rest = keys;
+
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+
+        let (key_code, state, count) = match c {
+            '^' => {
+                modifiers.push(VK_LCONTROL);
+                continue;
+            }
+            '!' => {
+                modifiers.push(VK_LMENU);
+                continue;
+            }
+            '+' => {
+                modifiers.push(VK_LSHIFT);
+                continue;
+            }
+            '#' => {
+                modifiers.push(VK_LWIN);
+                continue;
+            }
+            '{' => {
+                // Searching from the second character lets `{}}` send a closing brace
+                let Some(end) = rest.get(1..).and_then(|after| after.find('}')) else {
+                    import.warn(number, "A `{` isn't closed, the keys after it are skipped");
+                    break;
+                };
+                let inside = &rest[..end + 1];
+                rest = &rest[end + 2..];
+
+                let (name, option) = match inside.rsplit_once(' ') {
+                    Some((name, option)) if !name.is_empty() => (name, option),
+                    _ => (inside, ""),
+                };
+                let Some(key_code) = key(name) else {
+                    import.warn(number, format!("There's no key named {:?}, skipped", name));
+                    modifiers.clear();
+                    continue;
+                };
+
+                match option.to_ascii_lowercase().as_str() {
+                    "" => (key_code, KeyState::Pressed, 1),
+                    "down" | "downr" | "downtemp" => (key_code, KeyState::Down, 1),
+                    "up" => (key_code, KeyState::Up, 1),
+                    count => match count.parse() {
+                        Ok(count) => (key_code, KeyState::Pressed, count),
+                        Err(_) => {
+                            import
+                                .warn(number, format!("`{{{}}}` isn't supported, skipped", inside));
+                            modifiers.clear();
+                            continue;
+                        }
+                    },
+                }
+            }
+            c => match character_key(c) {
+                Some((key_code, shift)) => {
+                    if shift {
+                        modifiers.push(VK_LSHIFT);
+                    }
+                    (key_code, KeyState::Pressed, 1)
+                }
+                None => {
+                    import.warn(
+                        number,
+                        format!("There's no key to type {:?} with, skipped", c),
+                    );
+                    modifiers.clear();
+                    continue;
+                }
+            },
+        };
+
+        for _ in 0..count {
+            import.chord(&modifiers, key_code, state);
+        }
+        modifiers.clear();
+    }
+}
+
+/// `Click` with any of a position, a count, a button or wheel, `Down` or `Up` and `Rel`.
+fn click(import: &mut Import, args: &str) -> bool {
+    let mut numbers = vec![];
+    let mut button = VK_LBUTTON;
+    // Whether it's horizontal and which way it turns
+    let mut wheel = None;
+    let mut state = MouseActionButtonState::Clicked;
+    let mut relative = false;
+
+    for word in words(args) {
+        if let Ok(number) = word.parse::<i32>() {
+            numbers.push(number);
+            continue;
+        }
+
+        match word.to_ascii_lowercase().as_str() {
+            "left" | "l" => button = VK_LBUTTON,
+            "right" | "r" => button = VK_RBUTTON,
+            "middle" | "m" => button = VK_MBUTTON,
+            "x1" => button = VK_XBUTTON1,
+            "x2" => button = VK_XBUTTON2,
+            "wheelup" | "wu" => wheel = Some((false, 1)),
+            "wheeldown" | "wd" => wheel = Some((false, -1)),
+            "wheelright" | "wr" => wheel = Some((true, 1)),
+            "wheelleft" | "wl" => wheel = Some((true, -1)),
+            "down" | "d" => state = MouseActionButtonState::Pressed,
+            "up" | "u" => state = MouseActionButtonState::Released,
+            "rel" | "relative" => relative = true,
+            _ => return false,
+        }
+    }
+
+    let point = |x, y| {
+        let point = Point { x, y };
+        Some(if relative {
+            MousePointKind::By(point)
+        } else {
+            MousePointKind::To(point)
+        })
+    };
+    let (point, count) = match numbers[..] {
+        [] => (None, 1),
+        [count] => (None, count),
+        [x, y] => (point(x, y), 1),
+        [x, y, count, ..] => (point(x, y), count),
+    };
+
+    match (wheel, state) {
+        // Windows counts 120 to a notch
+        (Some((false, direction)), _) => import.actions.push(Action::Mouse(
+            MouseActionKind::Wheel(direction * 120 * count, point),
+        )),
+        (Some((true, direction)), _) => {
+            import
+                .actions
+                .push(Action::Mouse(MouseActionKind::HorizontalWheel(
+                    direction * 120 * count,
+                    point,
+                )))
+        }
+        (None, MouseActionButtonState::Clicked) if count <= 0 => {
+            if let Some(point) = point {
+                import
+                    .actions
+                    .push(Action::Mouse(MouseActionKind::Moved(point)));
+            }
+        }
+        (None, MouseActionButtonState::Clicked) => {
+            for click in 0..count {
+                import
+                    .actions
+                    .push(Action::Mouse(MouseActionKind::Button(MouseActionButton {
+                        point: if click == 0 { point.clone() } else { None },
+                        button,
+                        state,
+                    })));
+            }
+        }
+        (None, state) => {
+            import
+                .actions
+                .push(Action::Mouse(MouseActionKind::Button(MouseActionButton {
+                    point,
+                    button,
+                    state,
+                })))
+        }
+    }
+
+    true
+}
+
+/// `MouseMove X, Y` with an optional speed and `R` for an offset.
+fn mouse_move(import: &mut Import, args: &str) -> bool {
+    let words = words(args);
+    let relative = words.iter().any(|word| word.eq_ignore_ascii_case("r"));
+    let numbers: Vec<i32> = words
+        .iter()
+        .filter(|word| !word.eq_ignore_ascii_case("r"))
+        .map_while(|word| word.parse().ok())
+        .collect();
+
+    let [x, y, ..] = numbers[..] else {
+        return false;
+    };
+    let point = Point { x, y };
+
+    import
+        .actions
+        .push(Action::Mouse(MouseActionKind::Moved(if relative {
+            MousePointKind::By(point)
+        } else {
+            MousePointKind::To(point)
+        })));
+    true
+}
+
+/// The key code for a name in braces, a single character is the key typing it.
+fn key(name: &str) -> Option<i32> {
+    let mut chars = name.chars();
+
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if let Some((key_code, _)) = character_key(c) {
+            return Some(key_code);
+        }
+    }
+
+    key_code(name)
+}
+
+/// The arguments split at commas and spaces, without quotes.
+fn words(args: &str) -> Vec<&str> {
+    args.split([',', ' ', '\t'])
+        .map(|word| word.trim_matches(['"', '\'']))
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// The text of a quoted v2 string, or a v1 argument as it is, with escapes replaced.
+fn unquote(text: &str) -> String {
+    let inner = ['"', '\'']
+        .iter()
+        .find_map(|&quote| text.strip_prefix(quote)?.strip_suffix(quote))
+        .unwrap_or(text);
+    let mut unescaped = String::new();
+    let mut chars = inner.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '`' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some(c) => unescaped.push(c),
+            None => {}
+        }
+    }
+
+    unescaped
+}
+
+/// The line without a comment, which starts with a `;` at the start or after a space.
+fn strip_comment(line: &str) -> &str {
+    let mut previous = ' ';
+
+    for (index, c) in line.char_indices() {
+        if c == ';' && previous.is_whitespace() {
+            return &line[..index];
+        }
+        previous = c;
+    }
+
+    line
+}
+
+fn column(line: &str) -> usize {
+    line.len() - line.trim_start().len() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import(text: &str) -> Import {
+        import_autohotkey(text).unwrap()
+    }
+
+    #[test]
+    fn imports_v1_and_v2_commands() {
+        let v1 = "#NoEnv\nF1::\nLoop, 2\n{\n  Click, 100, 200 ; into the field\n  Send, ^a{Del}Hi{Enter}\n  Sleep, 250\n}\nMouseMove, 5, -5, 0, R\nreturn\n";
+        let v2 = "Loop 2 {\n    Click 100, 200, \"Left\"\n    Send \"^a{Delete}Hi{Enter}\"\n    Sleep(250)\n}\nMouseMove 5, -5, 0, \"R\"\n";
+        let expected = format!(
+            "{:?}",
+            [
+                Action::Repeat(2),
+                Action::Mouse(MouseActionKind::Button(MouseActionButton {
+                    point: Some(MousePointKind::To(Point { x: 100, y: 200 })),
+                    button: VK_LBUTTON,
+                    state: MouseActionButtonState::Clicked,
+                })),
+                Action::Keyboard(VK_LCONTROL, KeyState::Down, None),
+                Action::Keyboard(0x41, KeyState::Pressed, None),
+                Action::Keyboard(VK_LCONTROL, KeyState::Up, None),
+                Action::Keyboard(VK_DELETE, KeyState::Pressed, None),
+                Action::Keyboard(VK_LSHIFT, KeyState::Down, None),
+                Action::Keyboard(0x48, KeyState::Pressed, None),
+                Action::Keyboard(VK_LSHIFT, KeyState::Up, None),
+                Action::Keyboard(0x49, KeyState::Pressed, None),
+                Action::Keyboard(VK_RETURN, KeyState::Pressed, None),
+                Action::Delay(250, DelayJitter::None),
+                Action::EndRepeat,
+                Action::Mouse(MouseActionKind::Moved(MousePointKind::By(Point {
+                    x: 5,
+                    y: -5
+                }))),
+            ]
+        );
+
+        let v1 = import(v1);
+        assert_eq!(format!("{:?}", v1.actions), expected);
+        assert_eq!(v1.warnings.len(), 2);
+        let v2 = import(v2);
+        assert_eq!(format!("{:?}", v2.actions), expected);
+        assert!(v2.warnings.is_empty());
+    }
+
+    #[test]
+    fn imports_keys_and_clicks() {
+        let text = "Send \"{Shift down}{F5}{Numpad3 2}{}}{Esc up}\"\nClick \"Right Down\"\nClick 3, \"WheelDown\"\nClick 10, 20, 0";
+
+        assert_eq!(
+            format!("{:?}", import(text).actions),
+            format!(
+                "{:?}",
+                [
+                    Action::Keyboard(VK_SHIFT, KeyState::Down, None),
+                    Action::Keyboard(VK_F5, KeyState::Pressed, None),
+                    Action::Keyboard(VK_NUMPAD3, KeyState::Pressed, None),
+                    Action::Keyboard(VK_NUMPAD3, KeyState::Pressed, None),
+                    Action::Keyboard(VK_ESCAPE, KeyState::Up, None),
+                    Action::Mouse(MouseActionKind::Button(MouseActionButton {
+                        point: None,
+                        button: VK_RBUTTON,
+                        state: MouseActionButtonState::Pressed,
+                    })),
+                    Action::Mouse(MouseActionKind::Wheel(-360, None)),
+                    Action::Mouse(MouseActionKind::Moved(MousePointKind::To(Point {
+                        x: 10,
+                        y: 20
+                    }))),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn skips_what_it_doesnt_understand() {
+        let import = import(
+            "x := 5\nif (x > 3)\n{\n    Send \"a\"\n    Loop {\n    }\n}\nLoop Files, \"*.txt\" {\n}\nSleep x\nbreak\n",
+        );
+
+        assert!(import.actions.is_empty());
+        assert_eq!(import.warnings.len(), 5);
+        assert_eq!(import_autohotkey("Loop 3\nSend \"a\"").unwrap_err().line, 1);
+        assert_eq!(import_autohotkey("Loop {\n").unwrap_err().line, 1);
+        assert_eq!(
+            import_autohotkey("Send \"a\"\n  }").unwrap_err(),
+            ScriptError {
+                line: 2,
+                column: 3,
+                message: "There's no block to close".into()
+            }
+        );
+    }
+}
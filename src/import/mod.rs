@@ -0,0 +1,94 @@
+//! Scripts of other automation tools turned into actions, to bring an existing script library
+//! into the recorder. Only commands with a counterpart in actions are understood, everything else
+//! is skipped with a warning.
+
+use std::{error::Error, fs::read_to_string, path::Path};
+
+use crate::{
+    actions::{Action, KeyState},
+    recorder::keymap::{VK_LSHIFT, VK_RETURN, VK_SPACE, VK_TAB},
+};
+
+pub mod autohotkey;
+pub mod xdotool;
+
+use self::{autohotkey::import_autohotkey, xdotool::import_xdotool};
+
+#[derive(Debug)]
+pub struct Import {
+    pub actions: Vec<Action>,
+    /// What was skipped or only roughly imported, one line each.
+    pub warnings: Vec<String>,
+}
+
+impl Import {
+    fn new() -> Self {
+        Self {
+            actions: vec![],
+            warnings: vec![],
+        }
+    }
+
+    fn warn(&mut self, line: usize, message: impl AsRef<str>) {
+        self.warnings
+            .push(format!("Line {}: {}", line, message.as_ref()));
+    }
+
+    /// Sends the key with the modifiers held down around it.
+    fn chord(&mut self, modifiers: &[i32], key_code: i32, state: KeyState) {
+        for modifier in modifiers {
+            self.actions
+                .push(Action::Keyboard(*modifier, KeyState::Down, None));
+        }
+        self.actions.push(Action::Keyboard(key_code, state, None));
+        for modifier in modifiers.iter().rev() {
+            self.actions
+                .push(Action::Keyboard(*modifier, KeyState::Up, None));
+        }
+    }
+
+    /// Types the text with key presses, holding shift for capital letters. Characters without a
+    /// key of their own are skipped.
+    fn type_text(&mut self, line: usize, text: &str) {
+        for c in text.chars() {
+            match character_key(c) {
+                Some((key_code, false)) => {
+                    self.actions
+                        .push(Action::Keyboard(key_code, KeyState::Pressed, None))
+                }
+                Some((key_code, true)) => self.actions.extend([
+                    Action::Keyboard(VK_LSHIFT, KeyState::Down, None),
+                    Action::Keyboard(key_code, KeyState::Pressed, None),
+                    Action::Keyboard(VK_LSHIFT, KeyState::Up, None),
+                ]),
+                None => self.warn(
+                    line,
+                    format!("There's no key to type {:?} with, skipped", c),
+                ),
+            }
+        }
+    }
+}
+
+/// Imports the AutoHotkey or xdotool script, going by its extension.
+pub fn import_macro(path: &Path) -> Result<Import, Box<dyn Error>> {
+    let text = read_to_string(path)?;
+
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("ahk") => Ok(import_autohotkey(&text)?),
+        Some("sh") => Ok(import_xdotool(&text)?),
+        _ => Err(format!("Can't import {}, unknown extension", path.display()).into()),
+    }
+}
+
+/// The key typing the character, and whether it needs shift.
+fn character_key(c: char) -> Option<(i32, bool)> {
+    Some(match c {
+        'a'..='z' => (c.to_ascii_uppercase() as i32, false),
+        'A'..='Z' | '0'..='9' => (c as i32, c.is_ascii_uppercase()),
+        ' ' => (VK_SPACE, false),
+        '\t' => (VK_TAB, false),
+        '\n' => (VK_RETURN, false),
+        _ => return None,
+    })
+}
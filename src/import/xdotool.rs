@@ -0,0 +1,542 @@
+//! Shell scripts running `xdotool`, with commands chained on a line and `sleep` between them.
+//! Loops written as `for ... in $(seq N); do`, `for ... in {1..N}; do` or `while true; do` are
+//! understood too. Commands working on windows are skipped with the rest of their line, other
+//! shell commands are skipped along with their blocks.
+
+use super::Import;
+use crate::{actions::*, export::xdotool::keysym_code, recorder::keymap::*, script::ScriptError};
+
+/// Commands ending the list of keys before them.
+const COMMANDS: [&str; 16] = [
+    "mousemove",
+    "mousemove_relative",
+    "click",
+    "mousedown",
+    "mouseup",
+    "key",
+    "keydown",
+    "keyup",
+    "type",
+    "sleep",
+    "search",
+    "getmouselocation",
+    "getactivewindow",
+    "windowactivate",
+    "windowmove",
+    "windowsize",
+];
+
+pub fn import_xdotool(text: &str) -> Result<Import, ScriptError> {
+    let mut import = Import::new();
+    // Lines the open loops started on
+    let mut loops = vec![];
+    // How deep into blocks that are skipped
+    let mut skipping = 0;
+
+    for (index, raw) in text.lines().enumerate() {
+        let number = index + 1;
+        let line = raw.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if skipping > 0 {
+            if opens_block(line) {
+                skipping += 1;
+            }
+            if matches!(line, "fi" | "done" | "}") {
+                skipping -= 1;
+            }
+            continue;
+        }
+
+        if let Some(amount) = loop_amount(line) {
+            import.actions.push(Action::Repeat(amount));
+            loops.push(number);
+            continue;
+        }
+        match line {
+            "done" => {
+                if loops.pop().is_none() {
+                    return Err(ScriptError {
+                        line: number,
+                        column: raw.len() - raw.trim_start().len() + 1,
+                        message: "There's no loop to close".into(),
+                    });
+                }
+                import.actions.push(Action::EndRepeat);
+                continue;
+            }
+            "break" if !loops.is_empty() => {
+                import.actions.push(Action::Break);
+                continue;
+            }
+            _ => {}
+        }
+        if opens_block(line) {
+            skipping += 1;
+            import.warn(
+                number,
+                format!("`{}` isn't supported, skipped with its block", line),
+            );
+            continue;
+        }
+
+        let commands = shell_words(line).map_err(|column| ScriptError {
+            line: number,
+            column: raw.len() - raw.trim_start().len() + column,
+            message: "This quote isn't closed".into(),
+        })?;
+
+        for words in commands {
+            match words.first().map(String::as_str) {
+                Some("xdotool") => chain(&mut import, number, &words[1..]),
+                // Shell options only change how the script runs
+                Some("set") => {}
+                Some("sleep") if words.len() == 2 => match seconds(&words[1]) {
+                    Some(delay) => import.actions.push(Action::Delay(delay, DelayJitter::None)),
+                    None => import.warn(number, format!("`{}` isn't supported, skipped", line)),
+                },
+                Some(_) => import.warn(
+                    number,
+                    format!("`{}` isn't supported, skipped", words.join(" ")),
+                ),
+                None => {}
+            }
+        }
+    }
+
+    match loops.last() {
+        Some(&start) => Err(ScriptError {
+            line: start,
+            column: 1,
+            message: "This loop isn't closed".into(),
+        }),
+        None => Ok(import),
+    }
+}
+
+/// Adds the actions of chained xdotool commands.
+fn chain(import: &mut Import, number: usize, words: &[String]) {
+    let mut index = 0;
+    // Where a position the next mouse button or wheel happens at was moved to
+    let mut moved: Option<usize> = None;
+
+    while let Some(command) = words.get(index) {
+        let start = index;
+        index += 1;
+
+        let mut repeat = 1;
+        let mut delay = None;
+        let mut supported = true;
+
+        while let Some(option) = words.get(index).and_then(|word| word.strip_prefix("--")) {
+            index += 1;
+
+            match option {
+                // Ends the options, so offsets can be negative
+                "" => break,
+                "repeat" | "delay" | "window" | "screen" | "terminator" | "file" => {
+                    let value = words.get(index);
+                    index += 1;
+
+                    match (option, value) {
+                        ("repeat", Some(value)) if value.parse::<i32>().is_ok() => {
+                            repeat = value.parse().unwrap()
+                        }
+                        ("delay", Some(value)) if value.parse::<u32>().is_ok() => {
+                            delay = value.parse().ok()
+                        }
+                        _ => supported = false,
+                    }
+                }
+                "sync" | "clearmodifiers" => {}
+                _ => supported = false,
+            }
+        }
+
+        let at = moved.take();
+        let args = &words[index.min(words.len())..];
+        let arg = |offset: usize| args.get(offset).map(String::as_str);
+        let added = import.actions.len();
+
+        supported &= match command.as_str() {
+            "mousemove" | "mousemove_relative" => {
+                match (arg(0).map(str::parse), arg(1).map(str::parse)) {
+                    (Some(Ok(x)), Some(Ok(y))) => {
+                        let point = Point { x, y };
+                        import.actions.push(Action::Mouse(MouseActionKind::Moved(
+                            if command == "mousemove" {
+                                MousePointKind::To(point)
+                            } else {
+                                MousePointKind::By(point)
+                            },
+                        )));
+                        moved = Some(added);
+                        index += 2;
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            "click" | "mousedown" | "mouseup" => match arg(0).map(str::parse::<u8>) {
+                Some(Ok(button)) => {
+                    index += 1;
+                    button_action(import, command, button, repeat, delay, at)
+                }
+                _ => false,
+            },
+            "key" | "keydown" | "keyup" => {
+                let end = words[index..]
+                    .iter()
+                    .position(|word| COMMANDS.contains(&word.as_str()))
+                    .map_or(words.len(), |end| index + end);
+
+                for time in 0..repeat {
+                    for (sequence, keys) in words[index..end].iter().enumerate() {
+                        if let (Some(delay), true) = (delay, time > 0 || sequence > 0) {
+                            import.actions.push(Action::Delay(delay, DelayJitter::None));
+                        }
+                        key_sequence(import, number, command, keys);
+                    }
+                }
+                index = end;
+                true
+            }
+            "type" => {
+                for text in &words[index..] {
+                    import.type_text(number, text);
+                }
+                index = words.len();
+                true
+            }
+            "sleep" => match arg(0).and_then(seconds) {
+                Some(delay) => {
+                    import.actions.push(Action::Delay(delay, DelayJitter::None));
+                    index += 1;
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        };
+
+        if !supported {
+            import.actions.truncate(added);
+            import.warn(
+                number,
+                format!(
+                    "`xdotool {}` isn't supported, skipped with the rest of the line",
+                    words[start..].join(" ")
+                ),
+            );
+            return;
+        }
+    }
+}
+
+/// Adds a click, press or release of a button, or turns the wheel for buttons 4 to 7. A position
+/// moved to right before is taken in as the action's point.
+fn button_action(
+    import: &mut Import,
+    command: &str,
+    button: u8,
+    repeat: i32,
+    delay: Option<u32>,
+    moved: Option<usize>,
+) -> bool {
+    let point = |import: &mut Import| match moved {
+        Some(index) => match import.actions.remove(index) {
+            Action::Mouse(MouseActionKind::Moved(point)) => Some(point),
+            _ => unreachable!(),
+        },
+        None => None,
+    };
+
+    let (button, state) = match (button, command) {
+        // X11 scrolls with buttons, up and down are 4 and 5, left and right 6 and 7
+        (4..=7, "click") => {
+            let amount = match button {
+                4 | 7 => 120 * repeat,
+                _ => -120 * repeat,
+            };
+            let point = point(import);
+
+            import.actions.push(Action::Mouse(if button >= 6 {
+                MouseActionKind::HorizontalWheel(amount, point)
+            } else {
+                MouseActionKind::Wheel(amount, point)
+            }));
+            return true;
+        }
+        (1, _) => (VK_LBUTTON, command),
+        (2, _) => (VK_MBUTTON, command),
+        (3, _) => (VK_RBUTTON, command),
+        (8, _) => (VK_XBUTTON1, command),
+        (9, _) => (VK_XBUTTON2, command),
+        _ => return false,
+    };
+    let (state, repeat) = match state {
+        "mousedown" => (MouseActionButtonState::Pressed, 1),
+        "mouseup" => (MouseActionButtonState::Released, 1),
+        _ => (MouseActionButtonState::Clicked, repeat),
+    };
+    let mut point = point(import);
+
+    for time in 0..repeat {
+        if let (Some(delay), true) = (delay, time > 0) {
+            import.actions.push(Action::Delay(delay, DelayJitter::None));
+        }
+        import
+            .actions
+            .push(Action::Mouse(MouseActionKind::Button(MouseActionButton {
+                point: point.take(),
+                button,
+                state,
+            })));
+    }
+
+    true
+}
+
+/// Keys joined by `+` such as `ctrl+shift+t`, pressed with the ones before the last held down.
+fn key_sequence(import: &mut Import, number: usize, command: &str, keys: &str) {
+    let mut key_codes = vec![];
+
+    for name in keys.split('+') {
+        match keysym_code(name) {
+            Some(key_code) => key_codes.push(key_code),
+            None => {
+                return import.warn(
+                    number,
+                    format!("There's no key named {:?}, {} skipped", name, keys),
+                )
+            }
+        }
+    }
+
+    match command {
+        "keydown" => {
+            for key_code in key_codes {
+                import
+                    .actions
+                    .push(Action::Keyboard(key_code, KeyState::Down, None));
+            }
+        }
+        "keyup" => {
+            for key_code in key_codes {
+                import
+                    .actions
+                    .push(Action::Keyboard(key_code, KeyState::Up, None));
+            }
+        }
+        _ => {
+            if let Some((key_code, modifiers)) = key_codes.split_last() {
+                import.chord(modifiers, *key_code, KeyState::Pressed);
+            }
+        }
+    }
+}
+
+/// How many times a loop header repeats, 0 for forever.
+fn loop_amount(line: &str) -> Option<usize> {
+    if matches!(line, "while true; do" | "while :; do") {
+        return Some(0);
+    }
+
+    let (_, list) = line.strip_prefix("for ")?.split_once(" in ")?;
+    let list = list.strip_suffix("; do")?.trim();
+
+    if let Some(amount) = list
+        .strip_prefix("$(seq ")
+        .and_then(|list| list.strip_suffix(')'))
+    {
+        return amount.trim().parse().ok().filter(|&amount| amount > 0);
+    }
+
+    list.strip_prefix("{1..")?
+        .strip_suffix('}')?
+        .parse()
+        .ok()
+        .filter(|&amount| amount > 0)
+}
+
+fn opens_block(line: &str) -> bool {
+    line.ends_with("; then")
+        || line.ends_with("; do")
+        || line.ends_with('{')
+        || matches!(line, "then" | "do")
+}
+
+/// Milliseconds in seconds written like `sleep` takes them.
+fn seconds(text: &str) -> Option<u32> {
+    text.parse::<f64>()
+        .ok()
+        .filter(|seconds| *seconds >= 0.0)
+        .map(|seconds| (seconds * 1000.0).round() as u32)
+}
+
+/// The line's commands, split at `;`, as words with quotes and escapes taken out. The column of a
+/// quote that isn't closed on failure.
+fn shell_words(line: &str) -> Result<Vec<Vec<String>>, usize> {
+    let mut commands = vec![vec![]];
+    let mut word: Option<String> = None;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some((_, '\'')) => break,
+                        Some((_, c)) => word.push(c),
+                        None => return Err(index + 1),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c @ ('"' | '\\' | '$' | '`'))) => word.push(c),
+                            Some((_, c)) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err(index + 1),
+                        },
+                        Some((_, c)) => word.push(c),
+                        None => return Err(index + 1),
+                    }
+                }
+            }
+            '\\' => {
+                if let Some((_, c)) = chars.next() {
+                    word.get_or_insert_with(String::new).push(c);
+                }
+            }
+            '#' if word.is_none() => break,
+            ';' | ' ' | '\t' => {
+                if let Some(word) = word.take() {
+                    commands.last_mut().unwrap().push(word);
+                }
+                if c == ';' {
+                    commands.push(vec![]);
+                }
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if let Some(word) = word {
+        commands.last_mut().unwrap().push(word);
+    }
+    Ok(commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::xdotool::export_xdotool;
+
+    #[test]
+    fn imports_chained_commands_and_loops() {
+        let text = "#!/bin/sh\ncd ~/work\nfor i in {1..2}; do\n    xdotool key ctrl+shift+t; sleep 0.25 # wait\n    xdotool mousedown 1 mousemove_relative -- 0 -20 mouseup 1\ndone\nxdotool type 'Hi you'\n";
+        let import = import_xdotool(text).unwrap();
+
+        assert_eq!(
+            format!("{:?}", import.actions),
+            format!(
+                "{:?}",
+                [
+                    Action::Repeat(2),
+                    Action::Keyboard(VK_CONTROL, KeyState::Down, None),
+                    Action::Keyboard(VK_SHIFT, KeyState::Down, None),
+                    Action::Keyboard(0x54, KeyState::Pressed, None),
+                    Action::Keyboard(VK_SHIFT, KeyState::Up, None),
+                    Action::Keyboard(VK_CONTROL, KeyState::Up, None),
+                    Action::Delay(250, DelayJitter::None),
+                    Action::Mouse(MouseActionKind::Button(MouseActionButton {
+                        point: None,
+                        button: VK_LBUTTON,
+                        state: MouseActionButtonState::Pressed,
+                    })),
+                    Action::Mouse(MouseActionKind::Button(MouseActionButton {
+                        point: Some(MousePointKind::By(Point { x: 0, y: -20 })),
+                        button: VK_LBUTTON,
+                        state: MouseActionButtonState::Released,
+                    })),
+                    Action::EndRepeat,
+                    Action::Keyboard(VK_LSHIFT, KeyState::Down, None),
+                    Action::Keyboard(0x48, KeyState::Pressed, None),
+                    Action::Keyboard(VK_LSHIFT, KeyState::Up, None),
+                    Action::Keyboard(0x49, KeyState::Pressed, None),
+                    Action::Keyboard(VK_SPACE, KeyState::Pressed, None),
+                    Action::Keyboard(0x59, KeyState::Pressed, None),
+                    Action::Keyboard(0x4F, KeyState::Pressed, None),
+                    Action::Keyboard(0x55, KeyState::Pressed, None),
+                ]
+            )
+        );
+        assert_eq!(import.warnings.len(), 1);
+    }
+
+    #[test]
+    fn reads_back_what_it_exports() {
+        let actions = [
+            Action::Repeat(3),
+            Action::Mouse(MouseActionKind::Button(MouseActionButton {
+                point: Some(MousePointKind::To(Point { x: 100, y: 200 })),
+                button: VK_RBUTTON,
+                state: MouseActionButtonState::Clicked,
+            })),
+            Action::Keyboard(VK_LSHIFT, KeyState::Down, None),
+            Action::Keyboard(0x41, KeyState::Pressed, None),
+            Action::Keyboard(VK_F5, KeyState::Up, None),
+            Action::Delay(250, DelayJitter::None),
+            Action::Break,
+            Action::EndRepeat,
+            Action::Mouse(MouseActionKind::Moved(MousePointKind::By(Point {
+                x: -5,
+                y: 10,
+            }))),
+            Action::Mouse(MouseActionKind::HorizontalWheel(-240, None)),
+            Action::Mouse(MouseActionKind::Wheel(
+                120,
+                Some(MousePointKind::To(Point { x: 1, y: 2 })),
+            )),
+        ];
+        let export = export_xdotool(&actions);
+        let import = import_xdotool(&export.text).unwrap();
+
+        assert_eq!(format!("{:?}", import.actions), format!("{:?}", actions));
+        assert!(import.warnings.is_empty(), "{:?}", import.warnings);
+    }
+
+    #[test]
+    fn skips_what_it_doesnt_understand() {
+        let import = import_xdotool(
+            "xdotool key a search --name 'x' windowactivate\nif true; then\n  while true; do\n  done\nfi\nxdotool key nope\nbreak\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            format!("{:?}", import.actions),
+            format!("{:?}", [Action::Keyboard(0x41, KeyState::Pressed, None)])
+        );
+        assert_eq!(import.warnings.len(), 4);
+        assert_eq!(
+            import_xdotool("xdotool type \"open").unwrap_err(),
+            ScriptError {
+                line: 1,
+                column: 14,
+                message: "This quote isn't closed".into()
+            }
+        );
+        assert_eq!(import_xdotool("sleep 1\ndone").unwrap_err().line, 2);
+        assert_eq!(import_xdotool("while true; do").unwrap_err().line, 1);
+    }
+}
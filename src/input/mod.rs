@@ -0,0 +1,62 @@
+//! Keyboard and mouse input sent to the desktop, and the state of the cursor and keys. Keys and
+//! mouse buttons are virtual-key codes like everywhere else, the backends translate them.
+
+use std::error::Error;
+
+use crate::actions::{KeyState, MouseActionButtonState, Point, ScanCode};
+
+#[cfg(windows)]
+mod win32;
+#[cfg(target_os = "linux")]
+mod x11;
+
+#[cfg(windows)]
+use win32 as backend;
+#[cfg(target_os = "linux")]
+use x11 as backend;
+
+pub fn cursor_position() -> Result<Point, Box<dyn Error>> {
+    backend::cursor_position()
+}
+
+pub fn set_cursor_position(point: Point) -> Result<(), Box<dyn Error>> {
+    backend::set_cursor_position(point)
+}
+
+/// Whether the key or mouse button is held down right now.
+pub fn key_pressed(key_code: i32) -> bool {
+    backend::key_pressed(key_code)
+}
+
+/// Sent by scan code, the key is pressed at the same place on the keyboard whatever the layout,
+/// which games tend to read instead of the virtual key. Keys recorded without a scan code get the
+/// one of the current layout.
+pub fn send_key(
+    key_code: i32,
+    state: KeyState,
+    scan_code: Option<ScanCode>,
+    by_scan_code: bool,
+) -> Result<(), Box<dyn Error>> {
+    let ups: &[bool] = match state {
+        KeyState::Down => &[false],
+        KeyState::Up => &[true],
+        KeyState::Pressed => &[false, true],
+    };
+
+    backend::send_key(key_code, ups, scan_code, by_scan_code)
+}
+
+pub fn send_mouse_button(button: i32, state: MouseActionButtonState) -> Result<(), Box<dyn Error>> {
+    let presses: &[bool] = match state {
+        MouseActionButtonState::Pressed => &[true],
+        MouseActionButtonState::Released => &[false],
+        MouseActionButtonState::Clicked => &[true, false],
+    };
+
+    backend::send_mouse_button(button, presses)
+}
+
+/// Turns the wheel by the amount, 120 is one notch away from the user or to the right.
+pub fn send_wheel(amount: i32, horizontal: bool) -> Result<(), Box<dyn Error>> {
+    backend::send_wheel(amount, horizontal)
+}
@@ -0,0 +1,161 @@
+use std::{error::Error, mem::zeroed};
+
+use winapi::um::winuser::*;
+
+use crate::actions::{Point, ScanCode};
+
+pub fn cursor_position() -> Result<Point, Box<dyn Error>> {
+    let mut point = unsafe { zeroed() };
+    if unsafe { GetCursorPos(&mut point) } == 0 {
+        return Err("Failed to get the cursor position".into());
+    }
+
+    Ok(Point {
+        x: point.x,
+        y: point.y,
+    })
+}
+
+pub fn set_cursor_position(point: Point) -> Result<(), Box<dyn Error>> {
+    if unsafe { SetCursorPos(point.x, point.y) } == 0 {
+        return Err("Failed to move the cursor".into());
+    }
+
+    Ok(())
+}
+
+pub fn key_pressed(key_code: i32) -> bool {
+    unsafe { GetAsyncKeyState(key_code) < 0 }
+}
+
+fn send(inputs: &mut [INPUT]) -> Result<(), Box<dyn Error>> {
+    let sent = unsafe {
+        SendInput(
+            inputs.len() as u32,
+            inputs.as_mut_ptr(),
+            std::mem::size_of::<INPUT>() as i32,
+        )
+    };
+
+    if sent as usize != inputs.len() {
+        return Err("Input was blocked by another program".into());
+    }
+
+    Ok(())
+}
+
+fn mouse_input(flags: u32, mouse_data: u32) -> INPUT {
+    INPUT {
+        type_: INPUT_MOUSE,
+        u: unsafe {
+            std::mem::transmute_copy(&MOUSEINPUT {
+                dx: 0,
+                dy: 0,
+                mouseData: mouse_data,
+                dwFlags: flags,
+                dwExtraInfo: 0,
+                time: 0,
+            })
+        },
+    }
+}
+
+fn keyboard_input(
+    key_code: i32,
+    scan_code: Option<ScanCode>,
+    by_scan_code: bool,
+    up: bool,
+) -> INPUT {
+    let scan_code = match scan_code {
+        _ if !by_scan_code => None,
+        Some(scan_code) => Some(scan_code),
+        None => match unsafe { MapVirtualKeyW(key_code as u32, MAPVK_VK_TO_VSC_EX) } {
+            0 => None,
+            mapped => Some(ScanCode {
+                code: (mapped & 0xFF) as u16,
+                extended: mapped >> 8 == 0xE0,
+            }),
+        },
+    };
+
+    let mut flags = if up { KEYEVENTF_KEYUP } else { 0 };
+    let (key_code, scan) = match scan_code {
+        Some(ScanCode { code, extended }) => {
+            flags |= KEYEVENTF_SCANCODE;
+            if extended {
+                flags |= KEYEVENTF_EXTENDEDKEY;
+            }
+            (0, code)
+        }
+        None => (key_code as u16, 0),
+    };
+
+    let mut keybd_input: INPUT_u = unsafe { zeroed() };
+    unsafe {
+        *keybd_input.ki_mut() = KEYBDINPUT {
+            wVk: key_code,
+            dwExtraInfo: 0,
+            wScan: scan,
+            time: 0,
+            dwFlags: flags,
+        };
+    };
+    INPUT {
+        type_: INPUT_KEYBOARD,
+        u: keybd_input,
+    }
+}
+
+pub fn send_key(
+    key_code: i32,
+    ups: &[bool],
+    scan_code: Option<ScanCode>,
+    by_scan_code: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut inputs = ups
+        .iter()
+        .map(|up| keyboard_input(key_code, scan_code, by_scan_code, *up))
+        .collect::<Vec<_>>();
+
+    send(&mut inputs)
+}
+
+/// The input flags and mouse data for pressing or releasing a button.
+fn mouse_button_flags(button: i32, pressed: bool) -> Option<(u32, u32)> {
+    Some(match (button, pressed) {
+        (VK_LBUTTON, true) => (MOUSEEVENTF_LEFTDOWN, 0),
+        (VK_LBUTTON, false) => (MOUSEEVENTF_LEFTUP, 0),
+        (VK_RBUTTON, true) => (MOUSEEVENTF_RIGHTDOWN, 0),
+        (VK_RBUTTON, false) => (MOUSEEVENTF_RIGHTUP, 0),
+        (VK_MBUTTON, true) => (MOUSEEVENTF_MIDDLEDOWN, 0),
+        (VK_MBUTTON, false) => (MOUSEEVENTF_MIDDLEUP, 0),
+        (VK_XBUTTON1, true) => (MOUSEEVENTF_XDOWN, XBUTTON1 as u32),
+        (VK_XBUTTON1, false) => (MOUSEEVENTF_XUP, XBUTTON1 as u32),
+        (VK_XBUTTON2, true) => (MOUSEEVENTF_XDOWN, XBUTTON2 as u32),
+        (VK_XBUTTON2, false) => (MOUSEEVENTF_XUP, XBUTTON2 as u32),
+        _ => return None,
+    })
+}
+
+pub fn send_mouse_button(button: i32, presses: &[bool]) -> Result<(), Box<dyn Error>> {
+    let mut inputs = presses
+        .iter()
+        .map(|pressed| {
+            let (flags, mouse_data) = mouse_button_flags(button, *pressed)
+                .ok_or_else(|| format!("Can't play back mouse button {}", button))?;
+            Ok(mouse_input(flags, mouse_data))
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+    send(&mut inputs)
+}
+
+pub fn send_wheel(amount: i32, horizontal: bool) -> Result<(), Box<dyn Error>> {
+    let flags = if horizontal {
+        MOUSEEVENTF_HWHEEL
+    } else {
+        MOUSEEVENTF_WHEEL
+    };
+
+    send(&mut [mouse_input(flags, amount as u32)])
+}
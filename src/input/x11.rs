@@ -0,0 +1,196 @@
+use std::error::Error;
+
+use once_cell::sync::Lazy;
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        xproto::{self, ConnectionExt as _, KeyButMask, Window},
+        xtest::ConnectionExt as _,
+    },
+    rust_connection::RustConnection,
+    wrapper::ConnectionExt as _,
+    CURRENT_TIME,
+};
+
+use crate::{
+    actions::{Point, ScanCode},
+    keys::Key,
+    recorder::keymap::*,
+};
+
+// X11 key codes are evdev codes shifted by the minimum key code
+const KEYCODE_OFFSET: u16 = 8;
+
+// The device id XTest uses for the core pointer and keyboard
+const CORE_DEVICE: u8 = 0;
+
+/// Key states are polled in tight loops, so the connection is opened once and kept.
+static CONNECTION: Lazy<Option<(RustConnection, Window)>> = Lazy::new(|| {
+    let (connection, screen_num) = x11rb::connect(None).ok()?;
+    let root = connection.setup().roots[screen_num].root;
+    Some((connection, root))
+});
+
+fn connection() -> Result<&'static (RustConnection, Window), Box<dyn Error>> {
+    CONNECTION
+        .as_ref()
+        .ok_or_else(|| "Failed to connect to the X server".into())
+}
+
+/// Sends fake input events and waits until the server has handled them.
+fn fake_input(events: &[(u8, u8, Point)]) -> Result<(), Box<dyn Error>> {
+    let (connection, root) = connection()?;
+
+    for (type_, detail, point) in events {
+        connection.xtest_fake_input(
+            *type_,
+            *detail,
+            CURRENT_TIME,
+            *root,
+            point.x as i16,
+            point.y as i16,
+            CORE_DEVICE,
+        )?;
+    }
+    connection.sync()?;
+
+    Ok(())
+}
+
+pub fn cursor_position() -> Result<Point, Box<dyn Error>> {
+    let (connection, root) = connection()?;
+    let reply = connection.query_pointer(*root)?.reply()?;
+
+    Ok(Point {
+        x: reply.root_x as i32,
+        y: reply.root_y as i32,
+    })
+}
+
+pub fn set_cursor_position(point: Point) -> Result<(), Box<dyn Error>> {
+    // Detail 0 makes the motion absolute
+    fake_input(&[(xproto::MOTION_NOTIFY_EVENT, 0, point)])
+}
+
+/// The pointer state only knows about the first three buttons.
+fn button_mask(button: i32) -> Option<KeyButMask> {
+    match button {
+        VK_LBUTTON => Some(KeyButMask::BUTTON1),
+        VK_MBUTTON => Some(KeyButMask::BUTTON2),
+        VK_RBUTTON => Some(KeyButMask::BUTTON3),
+        _ => None,
+    }
+}
+
+fn keycode(key_code: i32) -> Option<u8> {
+    let evdev = Key::from_vk(key_code)?.to_evdev()?;
+    u8::try_from(evdev + KEYCODE_OFFSET).ok()
+}
+
+pub fn key_pressed(key_code: i32) -> bool {
+    let Ok((connection, root)) = connection() else {
+        return false;
+    };
+
+    if matches!(key_code, VK_XBUTTON1 | VK_XBUTTON2) {
+        return false;
+    }
+    if let Some(mask) = button_mask(key_code) {
+        return matches!(
+            connection.query_pointer(*root).map(|cookie| cookie.reply()),
+            Ok(Ok(reply)) if reply.mask & u16::from(mask) != 0
+        );
+    }
+
+    // Either side counts for the generic modifiers
+    let sides: &[i32] = match key_code {
+        VK_SHIFT => &[VK_LSHIFT, VK_RSHIFT],
+        VK_CONTROL => &[VK_LCONTROL, VK_RCONTROL],
+        VK_MENU => &[VK_LMENU, VK_RMENU],
+        _ => &[key_code],
+    };
+
+    let Ok(Ok(reply)) = connection.query_keymap().map(|cookie| cookie.reply()) else {
+        return false;
+    };
+
+    sides
+        .iter()
+        .filter_map(|side| keycode(*side))
+        .any(|keycode| reply.keys[keycode as usize / 8] & (1 << (keycode % 8)) != 0)
+}
+
+/// X11 key codes are already positions on the keyboard, so sending by scan code only changes
+/// which position is pressed for keys recorded on another layout.
+pub fn send_key(
+    key_code: i32,
+    ups: &[bool],
+    scan_code: Option<ScanCode>,
+    by_scan_code: bool,
+) -> Result<(), Box<dyn Error>> {
+    let keycode = scan_code
+        .filter(|_| by_scan_code)
+        .and_then(scan_code_to_evdev)
+        .and_then(|evdev| u8::try_from(evdev + KEYCODE_OFFSET).ok())
+        .or_else(|| keycode(key_code))
+        .ok_or_else(|| format!("Can't play back key {}", key_code))?;
+
+    let events = ups
+        .iter()
+        .map(|up| {
+            let type_ = if *up {
+                xproto::KEY_RELEASE_EVENT
+            } else {
+                xproto::KEY_PRESS_EVENT
+            };
+            (type_, keycode, Point { x: 0, y: 0 })
+        })
+        .collect::<Vec<_>>();
+
+    fake_input(&events)
+}
+
+fn button_number(button: i32) -> Option<u8> {
+    match button {
+        VK_LBUTTON => Some(1),
+        VK_MBUTTON => Some(2),
+        VK_RBUTTON => Some(3),
+        VK_XBUTTON1 => Some(8),
+        VK_XBUTTON2 => Some(9),
+        _ => None,
+    }
+}
+
+fn button_events(number: u8, presses: &[bool]) -> Vec<(u8, u8, Point)> {
+    presses
+        .iter()
+        .map(|pressed| {
+            let type_ = if *pressed {
+                xproto::BUTTON_PRESS_EVENT
+            } else {
+                xproto::BUTTON_RELEASE_EVENT
+            };
+            (type_, number, Point { x: 0, y: 0 })
+        })
+        .collect()
+}
+
+pub fn send_mouse_button(button: i32, presses: &[bool]) -> Result<(), Box<dyn Error>> {
+    let number =
+        button_number(button).ok_or_else(|| format!("Can't play back mouse button {}", button))?;
+
+    fake_input(&button_events(number, presses))
+}
+
+/// The wheel is made of buttons on X11, one click per notch.
+pub fn send_wheel(amount: i32, horizontal: bool) -> Result<(), Box<dyn Error>> {
+    let number = match (horizontal, amount > 0) {
+        (false, true) => 4,
+        (false, false) => 5,
+        (true, false) => 6,
+        (true, true) => 7,
+    };
+    let notches = (amount.unsigned_abs() as usize / 120).max(1);
+
+    fake_input(&button_events(number, &[true, false].repeat(notches)))
+}
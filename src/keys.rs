@@ -0,0 +1,491 @@
+//! Keyboard keys independent of any platform. Recordings store Win32 virtual-key codes, this
+//! translates them to and from names, X11 keysyms and Linux evdev codes.
+
+use std::fmt;
+
+/// A key on the keyboard. Mouse buttons aren't keys, even if Win32 gives them virtual-key codes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// An uppercase letter from A to Z.
+    Letter(char),
+    /// A digit from 0 to 9 above the letters.
+    Digit(u8),
+    /// F1 to F24.
+    Function(u8),
+    /// A digit from 0 to 9 on the number pad.
+    Numpad(u8),
+    Backspace,
+    Tab,
+    Clear,
+    Enter,
+    Shift,
+    Control,
+    Alt,
+    LeftShift,
+    RightShift,
+    LeftControl,
+    RightControl,
+    LeftAlt,
+    RightAlt,
+    Pause,
+    CapsLock,
+    Escape,
+    Space,
+    PageUp,
+    PageDown,
+    End,
+    Home,
+    Left,
+    Up,
+    Right,
+    Down,
+    Select,
+    Print,
+    Execute,
+    PrintScreen,
+    Insert,
+    Delete,
+    Help,
+    LeftWindows,
+    RightWindows,
+    Menu,
+    Sleep,
+    NumpadMultiply,
+    NumpadAdd,
+    NumpadSeparator,
+    NumpadSubtract,
+    NumpadDecimal,
+    NumpadDivide,
+    NumLock,
+    ScrollLock,
+    Mute,
+    VolumeDown,
+    VolumeUp,
+    NextTrack,
+    PreviousTrack,
+    StopMedia,
+    PlayPause,
+    Semicolon,
+    Equals,
+    Comma,
+    Minus,
+    Period,
+    Slash,
+    Grave,
+    LeftBracket,
+    Backslash,
+    RightBracket,
+    Apostrophe,
+    /// The key between left shift and Z on ISO keyboards.
+    Oem102,
+}
+
+struct KeyInfo {
+    key: Key,
+    name: &'static str,
+    vk: i32,
+    evdev: Option<u16>,
+    keysym: Option<(u32, &'static str)>,
+}
+
+const fn info(
+    key: Key,
+    name: &'static str,
+    vk: i32,
+    evdev: Option<u16>,
+    keysym: Option<(u32, &'static str)>,
+) -> KeyInfo {
+    KeyInfo {
+        key,
+        name,
+        vk,
+        evdev,
+        keysym,
+    }
+}
+
+/// Every key except letters, digits, function and number pad keys. The generic modifiers are
+/// typed as their left key on X11 and evdev.
+#[rustfmt::skip]
+const KEYS: [KeyInfo; 63] = [
+    info(Key::Backspace, "Back", 0x08, Some(14), Some((0xFF08, "BackSpace"))),
+    info(Key::Tab, "Tab", 0x09, Some(15), Some((0xFF09, "Tab"))),
+    info(Key::Clear, "Clear", 0x0C, Some(355), Some((0xFF0B, "Clear"))),
+    info(Key::Enter, "Enter", 0x0D, Some(28), Some((0xFF0D, "Return"))),
+    info(Key::Shift, "Shift", 0x10, Some(42), Some((0xFFE1, "Shift_L"))),
+    info(Key::Control, "Control", 0x11, Some(29), Some((0xFFE3, "Control_L"))),
+    info(Key::Alt, "Alt", 0x12, Some(56), Some((0xFFE9, "Alt_L"))),
+    info(Key::Pause, "Pause", 0x13, Some(119), Some((0xFF13, "Pause"))),
+    info(Key::CapsLock, "Caps Lock", 0x14, Some(58), Some((0xFFE5, "Caps_Lock"))),
+    info(Key::Escape, "Escape", 0x1B, Some(1), Some((0xFF1B, "Escape"))),
+    info(Key::Space, "Space", 0x20, Some(57), Some((0x20, "space"))),
+    info(Key::PageUp, "Page Up", 0x21, Some(104), Some((0xFF55, "Prior"))),
+    info(Key::PageDown, "Page Down", 0x22, Some(109), Some((0xFF56, "Next"))),
+    info(Key::End, "End", 0x23, Some(107), Some((0xFF57, "End"))),
+    info(Key::Home, "Home", 0x24, Some(102), Some((0xFF50, "Home"))),
+    info(Key::Left, "Left Arrow", 0x25, Some(105), Some((0xFF51, "Left"))),
+    info(Key::Up, "Up Arrow", 0x26, Some(103), Some((0xFF52, "Up"))),
+    info(Key::Right, "Right Arrow", 0x27, Some(106), Some((0xFF53, "Right"))),
+    info(Key::Down, "Down Arrow", 0x28, Some(108), Some((0xFF54, "Down"))),
+    info(Key::Select, "Select", 0x29, Some(353), Some((0xFF60, "Select"))),
+    info(Key::Print, "Print", 0x2A, None, None),
+    info(Key::Execute, "Execute", 0x2B, None, Some((0xFF62, "Execute"))),
+    info(Key::PrintScreen, "Snapshot", 0x2C, Some(99), Some((0xFF61, "Print"))),
+    info(Key::Insert, "Insert", 0x2D, Some(110), Some((0xFF63, "Insert"))),
+    info(Key::Delete, "Delete", 0x2E, Some(111), Some((0xFFFF, "Delete"))),
+    info(Key::Help, "Help", 0x2F, Some(138), Some((0xFF6A, "Help"))),
+    info(Key::LeftWindows, "Left Windows", 0x5B, Some(125), Some((0xFFEB, "Super_L"))),
+    info(Key::RightWindows, "Right Windows", 0x5C, Some(126), Some((0xFFEC, "Super_R"))),
+    info(Key::Menu, "Applications", 0x5D, Some(127), Some((0xFF67, "Menu"))),
+    info(Key::Sleep, "Sleep", 0x5F, Some(142), Some((0x1008_FF2F, "XF86Sleep"))),
+    info(Key::NumpadMultiply, "Multiply", 0x6A, Some(55), Some((0xFFAA, "KP_Multiply"))),
+    info(Key::NumpadAdd, "Add", 0x6B, Some(78), Some((0xFFAB, "KP_Add"))),
+    info(Key::NumpadSeparator, "Separator", 0x6C, Some(121), Some((0xFFAC, "KP_Separator"))),
+    info(Key::NumpadSubtract, "Subtract", 0x6D, Some(74), Some((0xFFAD, "KP_Subtract"))),
+    info(Key::NumpadDecimal, "Decimal", 0x6E, Some(83), Some((0xFFAE, "KP_Decimal"))),
+    info(Key::NumpadDivide, "Divide", 0x6F, Some(98), Some((0xFFAF, "KP_Divide"))),
+    info(Key::NumLock, "Number Lock", 0x90, Some(69), Some((0xFF7F, "Num_Lock"))),
+    info(Key::ScrollLock, "Scroll", 0x91, Some(70), Some((0xFF14, "Scroll_Lock"))),
+    info(Key::LeftShift, "Left Shift", 0xA0, Some(42), Some((0xFFE1, "Shift_L"))),
+    info(Key::RightShift, "Right Shift", 0xA1, Some(54), Some((0xFFE2, "Shift_R"))),
+    info(Key::LeftControl, "Left Control", 0xA2, Some(29), Some((0xFFE3, "Control_L"))),
+    info(Key::RightControl, "Right Control", 0xA3, Some(97), Some((0xFFE4, "Control_R"))),
+    info(Key::LeftAlt, "Left Alt", 0xA4, Some(56), Some((0xFFE9, "Alt_L"))),
+    info(Key::RightAlt, "Right Alt", 0xA5, Some(100), Some((0xFFEA, "Alt_R"))),
+    info(Key::Mute, "Mute", 0xAD, Some(113), Some((0x1008_FF12, "XF86AudioMute"))),
+    info(Key::VolumeDown, "Volume Down", 0xAE, Some(114), Some((0x1008_FF11, "XF86AudioLowerVolume"))),
+    info(Key::VolumeUp, "Volume Up", 0xAF, Some(115), Some((0x1008_FF13, "XF86AudioRaiseVolume"))),
+    info(Key::NextTrack, "Next Track", 0xB0, Some(163), Some((0x1008_FF17, "XF86AudioNext"))),
+    info(Key::PreviousTrack, "Previous Track", 0xB1, Some(165), Some((0x1008_FF16, "XF86AudioPrev"))),
+    info(Key::StopMedia, "Stop Media", 0xB2, Some(166), Some((0x1008_FF15, "XF86AudioStop"))),
+    info(Key::PlayPause, "Play Pause", 0xB3, Some(164), Some((0x1008_FF14, "XF86AudioPlay"))),
+    info(Key::Semicolon, "Semicolon", 0xBA, Some(39), Some((0x3B, "semicolon"))),
+    info(Key::Equals, "Equals", 0xBB, Some(13), Some((0x3D, "equal"))),
+    info(Key::Comma, "Comma", 0xBC, Some(51), Some((0x2C, "comma"))),
+    info(Key::Minus, "Minus", 0xBD, Some(12), Some((0x2D, "minus"))),
+    info(Key::Period, "Period", 0xBE, Some(52), Some((0x2E, "period"))),
+    info(Key::Slash, "Slash", 0xBF, Some(53), Some((0x2F, "slash"))),
+    info(Key::Grave, "Grave", 0xC0, Some(41), Some((0x60, "grave"))),
+    info(Key::LeftBracket, "Left Bracket", 0xDB, Some(26), Some((0x5B, "bracketleft"))),
+    info(Key::Backslash, "Backslash", 0xDC, Some(43), Some((0x5C, "backslash"))),
+    info(Key::RightBracket, "Right Bracket", 0xDD, Some(27), Some((0x5D, "bracketright"))),
+    info(Key::Apostrophe, "Apostrophe", 0xDE, Some(40), Some((0x27, "apostrophe"))),
+    info(Key::Oem102, "OEM 102", 0xE2, Some(86), Some((0x3C, "less"))),
+];
+
+/// Other names keys go by, written without spaces, underscores or dashes.
+const ALIASES: [(&str, Key); 34] = [
+    ("bs", Key::Backspace),
+    ("kpenter", Key::Enter),
+    ("numpadenter", Key::Enter),
+    ("ctrl", Key::Control),
+    ("lshift", Key::LeftShift),
+    ("rshift", Key::RightShift),
+    ("lctrl", Key::LeftControl),
+    ("lcontrol", Key::LeftControl),
+    ("leftctrl", Key::LeftControl),
+    ("rctrl", Key::RightControl),
+    ("rcontrol", Key::RightControl),
+    ("rightctrl", Key::RightControl),
+    ("lalt", Key::LeftAlt),
+    ("ralt", Key::RightAlt),
+    ("altgr", Key::RightAlt),
+    ("break", Key::Pause),
+    ("capital", Key::CapsLock),
+    ("esc", Key::Escape),
+    ("pgup", Key::PageUp),
+    ("pgdn", Key::PageDown),
+    ("printscreen", Key::PrintScreen),
+    ("prtsc", Key::PrintScreen),
+    ("ins", Key::Insert),
+    ("del", Key::Delete),
+    ("lwin", Key::LeftWindows),
+    ("rwin", Key::RightWindows),
+    ("win", Key::LeftWindows),
+    ("super", Key::LeftWindows),
+    ("appskey", Key::Menu),
+    ("numpadmult", Key::NumpadMultiply),
+    ("numpadsub", Key::NumpadSubtract),
+    ("numpaddot", Key::NumpadDecimal),
+    ("numpaddiv", Key::NumpadDivide),
+    ("intlbackslash", Key::Oem102),
+];
+
+/// Prefixes of number pad digits, written like the aliases.
+const NUMPAD_PREFIXES: [&str; 4] = ["numberpad", "numpad", "kp", "num"];
+
+impl Key {
+    /// The keys to pick from, letters and digits in the middle like the virtual-key codes. The
+    /// generic modifiers are left out, recordings always tell left from right.
+    pub fn all() -> Vec<Key> {
+        let mut keys = specific_keys()
+            .filter(|key| key.vk < 0x30)
+            .map(|key| key.key)
+            .collect::<Vec<_>>();
+        keys.extend((0..=9).map(Key::Digit));
+        keys.extend(('A'..='Z').map(Key::Letter));
+        keys.extend((0..=9).map(Key::Numpad));
+        keys.extend(
+            specific_keys()
+                .filter(|key| key.vk >= 0x30)
+                .map(|key| key.key),
+        );
+        keys.extend((1..=24).map(Key::Function));
+        keys
+    }
+
+    /// Parses the key's name, ignoring case, spaces, underscores and dashes. Knows the display
+    /// names, common short names such as "Ctrl" or "Numpad3" and X11 keysym names.
+    pub fn from_name(name: &str) -> Option<Key> {
+        let name = name
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '_' | '-'))
+            .collect::<String>()
+            .to_ascii_lowercase();
+        let normalized = |other: &str| {
+            other
+                .chars()
+                .filter(|c| !matches!(c, ' ' | '_' | '-'))
+                .collect::<String>()
+                .eq_ignore_ascii_case(&name)
+        };
+
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            match c {
+                'a'..='z' => return Some(Key::Letter(c.to_ascii_uppercase())),
+                '0'..='9' => return Some(Key::Digit(c as u8 - b'0')),
+                _ => {}
+            }
+        }
+
+        if let Some(key) = KEYS.iter().find(|key| normalized(key.name)) {
+            return Some(key.key);
+        }
+        if let Some((_, key)) = ALIASES.iter().find(|(alias, _)| *alias == name) {
+            return Some(*key);
+        }
+        if let Some(key) = specific_keys()
+            .find(|key| matches!(key.keysym, Some((_, keysym)) if normalized(keysym)))
+        {
+            return Some(key.key);
+        }
+
+        if let Some(number) = name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+            if (1..=24).contains(&number) {
+                return Some(Key::Function(number));
+            }
+        }
+        NUMPAD_PREFIXES.iter().find_map(|prefix| {
+            let digit = name.strip_prefix(prefix)?.parse().ok()?;
+            (digit <= 9).then_some(Key::Numpad(digit))
+        })
+    }
+
+    /// The key with this Win32 virtual-key code.
+    pub fn from_vk(vk: i32) -> Option<Key> {
+        Some(match vk {
+            0x30..=0x39 => Key::Digit((vk - 0x30) as u8),
+            0x41..=0x5A => Key::Letter(vk as u8 as char),
+            0x60..=0x69 => Key::Numpad((vk - 0x60) as u8),
+            0x70..=0x87 => Key::Function((vk - 0x70 + 1) as u8),
+            _ => KEYS.iter().find(|key| key.vk == vk)?.key,
+        })
+    }
+
+    pub fn to_vk(self) -> i32 {
+        match self {
+            Key::Letter(letter) => letter.to_ascii_uppercase() as i32,
+            Key::Digit(digit) => 0x30 + digit as i32,
+            Key::Numpad(digit) => 0x60 + digit as i32,
+            Key::Function(number) => 0x70 + number as i32 - 1,
+            _ => self.info().vk,
+        }
+    }
+
+    /// The key with this Linux evdev code, X11 key codes are the evdev code plus 8.
+    pub fn from_evdev(code: u16) -> Option<Key> {
+        for (first, letters) in LETTER_ROWS {
+            if let Some(letter) = code
+                .checked_sub(first)
+                .and_then(|index| letters.as_bytes().get(index as usize))
+            {
+                return Some(Key::Letter(*letter as char));
+            }
+        }
+
+        Some(match code {
+            2..=10 => Key::Digit(code as u8 - 1),
+            11 => Key::Digit(0),
+            59..=68 => Key::Function(code as u8 - 58),
+            87 | 88 => Key::Function(code as u8 - 76),
+            183..=194 => Key::Function(code as u8 - 170),
+            96 => Key::Enter, // Number pad enter
+            _ => {
+                if let Some(digit) = NUMPAD_EVDEV.iter().position(|numpad| *numpad == code) {
+                    return Some(Key::Numpad(digit as u8));
+                }
+                specific_keys().find(|key| key.evdev == Some(code))?.key
+            }
+        })
+    }
+
+    pub fn to_evdev(self) -> Option<u16> {
+        match self {
+            Key::Letter(letter) => LETTER_ROWS.iter().find_map(|(first, letters)| {
+                let index = letters.find(letter.to_ascii_uppercase())?;
+                Some(first + index as u16)
+            }),
+            Key::Digit(0) => Some(11),
+            Key::Digit(digit) => Some(digit as u16 + 1),
+            Key::Numpad(digit) => NUMPAD_EVDEV.get(digit as usize).copied(),
+            Key::Function(number @ 1..=10) => Some(number as u16 + 58),
+            Key::Function(number @ 11..=12) => Some(number as u16 + 76),
+            Key::Function(number) => Some(number as u16 + 170),
+            _ => self.info().evdev,
+        }
+    }
+
+    /// The key with this X11 keysym, both cases of a letter give the letter.
+    pub fn from_keysym(keysym: u32) -> Option<Key> {
+        Some(match keysym {
+            0x30..=0x39 => Key::Digit((keysym - 0x30) as u8),
+            0x41..=0x5A | 0x61..=0x7A => Key::Letter((keysym as u8 as char).to_ascii_uppercase()),
+            0xFFB0..=0xFFB9 => Key::Numpad((keysym - 0xFFB0) as u8),
+            0xFFBE..=0xFFD5 => Key::Function((keysym - 0xFFBE + 1) as u8),
+            0xFF8D => Key::Enter, // KP_Enter
+            _ => {
+                specific_keys()
+                    .find(|key| matches!(key.keysym, Some((code, _)) if code == keysym))?
+                    .key
+            }
+        })
+    }
+
+    /// The keysym typing the key, letters give the lowercase one.
+    pub fn to_keysym(self) -> Option<u32> {
+        match self {
+            Key::Letter(letter) => Some(letter.to_ascii_lowercase() as u32),
+            Key::Digit(digit) => Some(0x30 + digit as u32),
+            Key::Numpad(digit) => Some(0xFFB0 + digit as u32),
+            Key::Function(number) => Some(0xFFBE + number as u32 - 1),
+            _ => self.info().keysym.map(|(keysym, _)| keysym),
+        }
+    }
+
+    /// The key with this keysym name, ignoring case. Falls back to the other names, keysym names
+    /// come first because "Print" is print screen there.
+    pub fn from_keysym_name(name: &str) -> Option<Key> {
+        let numbered = (0..=9)
+            .map(Key::Numpad)
+            .chain((1..=24).map(Key::Function))
+            .chain((0..=9).map(Key::Digit));
+
+        specific_keys()
+            .map(|key| key.key)
+            .chain(numbered)
+            .find(|key| matches!(key.keysym_name(), Some(keysym) if keysym.eq_ignore_ascii_case(name)))
+            .or_else(|| Key::from_name(name))
+    }
+
+    /// The keysym's name, as used by tools like xdotool.
+    pub fn keysym_name(self) -> Option<String> {
+        match self {
+            Key::Letter(letter) => Some(letter.to_ascii_lowercase().to_string()),
+            Key::Digit(digit) => Some(digit.to_string()),
+            Key::Numpad(digit) => Some(format!("KP_{}", digit)),
+            Key::Function(number) => Some(format!("F{}", number)),
+            _ => self.info().keysym.map(|(_, name)| name.to_string()),
+        }
+    }
+
+    fn info(self) -> &'static KeyInfo {
+        KEYS.iter()
+            .find(|key| key.key == self)
+            .expect("Numbered keys are handled before looking up the table")
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Key::Letter(letter) => write!(f, "{}", letter.to_ascii_uppercase()),
+            Key::Digit(digit) => write!(f, "{}", digit),
+            Key::Function(number) => write!(f, "F{}", number),
+            Key::Numpad(digit) => write!(f, "Number Pad {}", digit),
+            _ => f.write_str(self.info().name),
+        }
+    }
+}
+
+/// The table without the generic modifiers, their codes belong to the left keys.
+fn specific_keys() -> impl Iterator<Item = &'static KeyInfo> {
+    KEYS.iter()
+        .filter(|key| !matches!(key.key, Key::Shift | Key::Control | Key::Alt))
+}
+
+// Letter keys by evdev code of the first key in each keyboard row
+const LETTER_ROWS: [(u16, &str); 3] = [(16, "QWERTYUIOP"), (30, "ASDFGHJKL"), (44, "ZXCVBNM")];
+
+// Evdev codes of the number pad digits from 0 to 9
+const NUMPAD_EVDEV: [u16; 10] = [82, 79, 80, 81, 75, 76, 77, 71, 72, 73];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names() {
+        let names = [
+            ("Ctrl", Key::Control),
+            ("F5", Key::Function(5)),
+            ("Numpad3", Key::Numpad(3)),
+            ("Number Pad 3", Key::Numpad(3)),
+            ("Enter", Key::Enter),
+            ("Return", Key::Enter),
+            ("page_up", Key::PageUp),
+            ("Prior", Key::PageUp),
+            ("Left Arrow", Key::Left),
+            ("q", Key::Letter('Q')),
+            ("7", Key::Digit(7)),
+            ("Super_L", Key::LeftWindows),
+        ];
+
+        for (name, key) in names {
+            assert_eq!(Key::from_name(name), Some(key), "{}", name);
+        }
+        assert_eq!(Key::from_name("F25"), None);
+        assert_eq!(Key::from_name("Numpad10"), None);
+        assert_eq!(Key::from_name("nope"), None);
+    }
+
+    #[test]
+    fn round_trips_every_key() {
+        for key in Key::all() {
+            assert_eq!(Key::from_name(&key.to_string()), Some(key), "{}", key);
+            assert_eq!(Key::from_vk(key.to_vk()), Some(key), "{}", key);
+            if let Some(code) = key.to_evdev() {
+                assert_eq!(Key::from_evdev(code), Some(key), "{}", key);
+            }
+            if let Some(keysym) = key.to_keysym() {
+                assert_eq!(Key::from_keysym(keysym), Some(key), "{}", key);
+            }
+            if let Some(name) = key.keysym_name() {
+                assert_eq!(Key::from_keysym_name(&name), Some(key), "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn translates_between_platforms() {
+        assert_eq!(Key::from_evdev(30), Some(Key::Letter('A')));
+        assert_eq!(Key::from_evdev(81), Some(Key::Numpad(3)));
+        assert_eq!(Key::from_evdev(29).map(Key::to_vk), Some(0xA2));
+        assert_eq!(Key::from_evdev(96), Some(Key::Enter));
+        assert_eq!(Key::Function(13).to_evdev(), Some(183));
+        assert_eq!(Key::Control.to_evdev(), Some(29));
+        assert_eq!(Key::Letter('Z').to_keysym(), Some(0x7A));
+        assert_eq!(Key::from_keysym(0xFF1B), Some(Key::Escape));
+        assert_eq!(Key::from_vk(0x01), None);
+    }
+}
@@ -1,202 +1,175 @@
 pub mod actions;
+pub mod clipboard;
+pub mod export;
 pub mod gui;
 pub mod hotkeys;
+pub mod humanize;
 pub mod images;
+pub mod import;
+pub mod input;
 pub mod keycodes_to_string;
+pub mod keys;
+pub mod macro_file;
 pub mod modals;
+pub mod monitors;
 pub mod recorder;
 pub mod right_click_dialog;
+pub mod script;
 pub mod settings;
+pub mod transform;
+pub mod window_manager;
 use actions::*;
 use chrono::{DateTime, Utc};
 use egui::pos2;
+use hotkeys::hotkeys_pressed;
+use humanize::{jittered_delay, new_rng, MouseCurve};
 use images::{find_image, find_pixel};
+use input::{cursor_position, send_key, send_mouse_button, send_wheel, set_cursor_position};
+use macro_file::{
+    is_zip, read_container, read_macro, write_container, write_macro, MacroFile, MacroMetadata,
+};
+use monitors::MonitorLayout;
+use rand::rngs::StdRng;
+use regex::Regex;
+use script::{is_script, parse_script, print_script};
 use settings::Settings;
 use std::{
+    collections::HashMap,
     error::Error,
-    fs::{read_to_string, File},
-    io::Write,
-    mem::zeroed,
+    fs::{read, read_to_string, write, File},
+    io::{Read, Write},
     path::Path,
-    time::SystemTime,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, SystemTime},
 };
-
-use winapi::um::winuser::*;
+use window_manager::platform_window_manager;
 
 use crate::images::fast_find_image;
 
-fn execute_mouse_action(action: &MouseActionButton) {
-    let (dx, dy) = if let Some(point) = action.point {
-        match point {
-            MousePointKind::To(_) => (0, 0),
-            MousePointKind::By(point) => (point.x, point.y),
-        }
-    } else {
-        (0, 0)
+/// How long waits for something outside the macro sleep between checks, checking the clipboard
+/// can mean starting a program and finding a window lists all of them.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+fn execute_mouse_action(action: &MouseActionButton, state: &mut PlaybackState) {
+    if let Some(point) = &action.point {
+        set_cursor_to(point, state);
+    }
+
+    if let Err(error) = send_mouse_button(action.button, action.state) {
+        println!("Failed to play back mouse button: {}", error);
+    }
+}
+
+/// Moves the cursor to the point, offsets are from wherever the cursor is.
+fn set_cursor_to(point: &MousePointKind, state: &mut PlaybackState) {
+    // Only offsets need the cursor position, reading it isn't free on every platform
+    let relative_to = match point {
+        MousePointKind::By(_) => cursor(),
+        _ => Point { x: 0, y: 0 },
     };
-    if action.state == MouseActionButtonState::Pressed
-        || action.state == MouseActionButtonState::Released
-    {
-        let flag = match action.button {
-            VK_LBUTTON => {
-                if action.state == MouseActionButtonState::Pressed {
-                    MOUSEEVENTF_LEFTDOWN
-                } else {
-                    MOUSEEVENTF_LEFTUP
-                }
-            }
-            VK_RBUTTON => {
-                if action.state == MouseActionButtonState::Pressed {
-                    MOUSEEVENTF_RIGHTDOWN
-                } else {
-                    MOUSEEVENTF_RIGHTUP
-                }
-            }
-            VK_MBUTTON => {
-                if action.state == MouseActionButtonState::Pressed {
-                    MOUSEEVENTF_MIDDLEDOWN
-                } else {
-                    MOUSEEVENTF_MIDDLEUP
-                }
-            }
-            _ => panic!(
-            "Somehow got a mouse button other than left / middle / right in execute mouse action"
-        ),
-        };
 
-        let mouse_input = MOUSEINPUT {
-            dx,
-            dy,
-            mouseData: 0,
-            dwFlags: flag,
-            dwExtraInfo: 0,
-            time: 0,
-        };
+    if let Some(point) = screen_position(point, relative_to, state) {
+        move_cursor(point);
+    }
+}
 
-        let mut input = INPUT {
-            type_: INPUT_MOUSE,
-            u: unsafe { std::mem::transmute_copy(&mouse_input) },
-        };
+fn move_cursor(point: Point) {
+    if let Err(error) = set_cursor_position(point) {
+        println!("Failed to move the cursor: {}", error);
+    }
+}
 
-        if let Some(point) = action.point {
-            if let MousePointKind::To(point) = point {
-                unsafe { SetCursorPos(point.x, point.y) };
-            }
-        }
+/// The cursor position, or the top left corner of the screen if it can't be read.
+fn cursor() -> Point {
+    cursor_position().unwrap_or_else(|error| {
+        println!("Failed to get the cursor position: {}", error);
+        Point { x: 0, y: 0 }
+    })
+}
 
-        unsafe { SendInput(1, &mut input, std::mem::size_of::<INPUT>() as i32) };
-    } else {
-        let mut inputs = [
-            INPUT {
-                type_: INPUT_MOUSE,
-                u: unsafe {
-                    std::mem::transmute_copy(&MOUSEINPUT {
-                dx,
-                dy,
-                mouseData: 0,
-                dwFlags: match action.button {
-                    VK_LBUTTON => MOUSEEVENTF_LEFTDOWN,
-                    VK_RBUTTON => MOUSEEVENTF_RIGHTDOWN,
-                    VK_MBUTTON => MOUSEEVENTF_MIDDLEDOWN,
-                    _ => panic!("Somehow got a mouse button other than left / middle / right in execute mouse action"),
-            },
-                dwExtraInfo: 0,
-                time: 0,
-            })
-                },
-            },
-            INPUT {
-                type_: INPUT_MOUSE,
-                u: unsafe {
-                    std::mem::transmute_copy(&MOUSEINPUT {
-                dx,
-                dy,
-                mouseData: 0,
-                dwFlags: match action.button {
-                    VK_LBUTTON => MOUSEEVENTF_LEFTUP,
-                    VK_RBUTTON => MOUSEEVENTF_RIGHTUP,
-                    VK_MBUTTON => MOUSEEVENTF_MIDDLEUP,
-                    _ => panic!("Somehow got a mouse button other than left / middle / right in execute mouse action"),
-            },
-                dwExtraInfo: 0,
-                time: 0,
-            })
-                },
-            },
-        ];
+fn window_client_rect(window_info: &WindowInfo) -> Result<WindowRect, Box<dyn Error>> {
+    let window_manager = platform_window_manager()?;
+    let window = window_manager
+        .find_window(window_info)?
+        .ok_or("No matching window found")?;
+    window_manager.client_rect(window.id)
+}
 
-        if let Some(point) = action.point {
-            if let MousePointKind::To(point) = point {
-                unsafe { SetCursorPos(point.x, point.y) };
-            }
+fn window_point_to_screen(window_info: &WindowInfo, point: Point) -> Option<Point> {
+    match window_client_rect(window_info) {
+        Ok(client_rect) => Some(Point {
+            x: client_rect.x + point.x,
+            y: client_rect.y + point.y,
+        }),
+        Err(error) => {
+            println!("Failed to position mouse in window: {}", error);
+            None
         }
+    }
+}
 
-        unsafe { SendInput(2, inputs.as_mut_ptr(), std::mem::size_of::<INPUT>() as i32) };
+fn execute_keyboard_action(
+    key_code: i32,
+    state: KeyState,
+    scan_code: Option<ScanCode>,
+    by_scan_code: bool,
+) {
+    if let Err(error) = send_key(key_code, state, scan_code, by_scan_code) {
+        println!("Failed to play back key: {}", error);
     }
 }
 
-fn execute_keyboard_action(key_code: i32, state: KeyState) {
-    if state != KeyState::Pressed {
-        let mut keybd_input: INPUT_u = unsafe { std::mem::zeroed() };
-        unsafe {
-            *keybd_input.ki_mut() = KEYBDINPUT {
-                wVk: key_code as u16,
-                dwExtraInfo: 0,
-                wScan: 0,
-                time: 0,
-                dwFlags: if state == KeyState::Down {
-                    0
-                } else {
-                    KEYEVENTF_KEYUP
-                },
-            };
-        };
-        let mut input = INPUT {
-            type_: INPUT_KEYBOARD,
-            u: keybd_input,
-        };
+#[derive(Debug)]
+pub struct PlaybackState {
+    pub last_exit_code: Option<i32>,
+    pub last_stdout: Option<String>,
+    pub last_found_image: Option<Point>,
+    /// Values copied from the clipboard, by name.
+    pub values: HashMap<String, String>,
+    pub rng: StdRng,
+    /// Monitors connected when playback started, positions recorded on other setups are
+    /// remapped onto them.
+    pub monitors: MonitorLayout,
+}
 
-        unsafe {
-            SendInput(1, &mut input, std::mem::size_of::<INPUT>() as i32);
-        };
-    } else {
-        let mut inputs = [
-            INPUT {
-                type_: INPUT_KEYBOARD,
-                u: unsafe {
-                    let mut keybd_input: INPUT_u = std::mem::zeroed();
-                    *keybd_input.ki_mut() = KEYBDINPUT {
-                        wVk: key_code as u16,
-                        dwExtraInfo: 0,
-                        wScan: 0,
-                        time: 0,
-                        dwFlags: 0,
-                    };
-                    keybd_input
-                },
-            },
-            INPUT {
-                type_: INPUT_KEYBOARD,
-                u: unsafe {
-                    let mut keybd_input: INPUT_u = std::mem::zeroed();
-                    *keybd_input.ki_mut() = KEYBDINPUT {
-                        wVk: key_code as u16,
-                        dwExtraInfo: 0,
-                        wScan: 0,
-                        time: 0,
-                        dwFlags: KEYEVENTF_KEYUP,
-                    };
-                    keybd_input
-                },
-            },
+impl PlaybackState {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            last_exit_code: None,
+            last_stdout: None,
+            last_found_image: None,
+            values: HashMap::new(),
+            rng: new_rng(settings.random_seed),
+            monitors: MonitorLayout::current(),
+        }
+    }
+
+    /// The text with `{name}` replaced by copied values, and `{exit_code}` and `{output}` by the
+    /// result of the last program waited for. Placeholders without a value are left as they are.
+    fn substitute(&self, text: &str) -> String {
+        let launch_values = [
+            (
+                "exit_code",
+                self.last_exit_code.map(|code| code.to_string()),
+            ),
+            ("output", self.last_stdout.clone()),
         ];
 
-        unsafe { SendInput(2, inputs.as_mut_ptr(), std::mem::size_of::<INPUT>() as i32) };
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), Some(value.clone())))
+            .chain(launch_values)
+            .fold(text.to_string(), |text, (name, value)| match value {
+                Some(value) => text.replace(&format!("{{{}}}", name), &value),
+                None => text,
+            })
     }
 }
 
 pub fn play_back_actions(action_list: &[Action], settings: &Settings) {
-    play(action_list, settings, 0, settings.repeat_times);
+    let mut state = PlaybackState::new(settings);
+    play(action_list, settings, 0, settings.repeat_times, &mut state);
 }
 
 fn play(
@@ -204,6 +177,7 @@ fn play(
     settings: &Settings,
     skip: usize,
     repeat_times: usize,
+    state: &mut PlaybackState,
 ) -> Option<usize> {
     let mut if_stack: Vec<bool> = vec![];
     let mut counter = 0;
@@ -215,7 +189,7 @@ fn play(
         for action in action_list.iter().skip(skip) {
             index += 1;
 
-            if stop_key_pressed() {
+            if stop_key_pressed(settings) {
                 return None;
             }
 
@@ -241,7 +215,7 @@ fn play(
                     }
                 }
                 Action::Repeat(amount) => {
-                    repeat_end_skip_index = play(action_list, settings, index, *amount);
+                    repeat_end_skip_index = play(action_list, settings, index, *amount, state);
                 }
                 Action::EndRepeat => {
                     if counter + 1 == repeat_times {
@@ -257,46 +231,55 @@ fn play(
             }
 
             match action {
-                Action::Keyboard(key_code, state) => execute_keyboard_action(*key_code, *state),
-                Action::Delay(delay) => {
+                Action::Keyboard(key_code, state, scan_code) => execute_keyboard_action(
+                    *key_code,
+                    *state,
+                    *scan_code,
+                    settings.keys_by_scan_code,
+                ),
+                Action::Delay(delay, jitter) => {
                     if settings.ignore_delays {
                         continue;
                     }
 
-                    let delay = *delay as f64 / settings.playback_speed as f64;
+                    let delay = jittered_delay(*delay, *jitter, &mut state.rng);
+                    let delay = delay as f64 / settings.playback_speed as f64;
 
                     let time_started = DateTime::<Utc>::from(SystemTime::now());
                     while (DateTime::<Utc>::from(SystemTime::now()) - time_started)
                         .num_milliseconds()
                         < delay as i64
                     {
-                        if stop_key_pressed() {
+                        if stop_key_pressed(settings) {
                             return None;
                         }
                     }
                 }
 
                 Action::Mouse(action_kind) => match action_kind {
-                    MouseActionKind::Moved(point) => unsafe {
-                        match *point {
-                            MousePointKind::To(point) => {
-                                SetCursorPos(point.x, point.y);
-                            }
-                            MousePointKind::By(by_point) => {
-                                let mut point = zeroed();
-                                GetCursorPos(&mut point);
-                                SetCursorPos(point.x + by_point.x, point.y + by_point.y);
-                            }
+                    MouseActionKind::Moved(point) if settings.humanize_mouse => {
+                        if move_mouse_humanized(point, settings, state) {
+                            return None;
                         }
-                    },
-                    MouseActionKind::Button(action) => execute_mouse_action(action),
-                    MouseActionKind::Wheel(amount, point) => execute_scroll_wheel(*amount, *point),
+                    }
+                    MouseActionKind::Moved(point) => set_cursor_to(point, state),
+                    MouseActionKind::Button(action) => execute_mouse_action(action, state),
+                    MouseActionKind::Wheel(amount, point) => {
+                        execute_scroll_wheel(*amount, point.clone(), false, state)
+                    }
+                    MouseActionKind::HorizontalWheel(amount, point) => {
+                        execute_scroll_wheel(*amount, point.clone(), true, state)
+                    }
                 },
 
-                Action::WaitForImage(image_info) => execute_wait_for_image(image_info),
-                Action::IfImage(image_info) => if_stack.push(execute_if_image(image_info)),
-                Action::IfPixel(pixel_info) => if_stack.push(execute_if_pixel(pixel_info)),
-                Action::WaitForPixel(pixel_info) => execute_wait_for_pixel(pixel_info),
+                Action::WaitForImage(image_info) => {
+                    execute_wait_for_image(image_info, settings, state)
+                }
+                Action::IfImage(image_info) => if_stack.push(execute_if_image(image_info, state)),
+                Action::IfPixel(pixel_info) => if_stack.push(execute_if_pixel(pixel_info, state)),
+                Action::WaitForPixel(pixel_info) => {
+                    execute_wait_for_pixel(pixel_info, settings, state)
+                }
                 Action::Else | Action::EndIf | Action::EndRepeat | Action::Repeat(..) => {}
                 Action::Break => {
                     let mut current_index = index;
@@ -315,15 +298,37 @@ fn play(
                 }
                 Action::Play(path) => {
                     let action_list = load_from_file(path).expect("amogus");
-                    play_back_actions(
+                    play(
                         &action_list,
                         &Settings {
                             repeat_times: 1,
                             hotkeys: vec![],
-                            ..*settings
+                            ..settings.clone()
                         },
+                        0,
+                        1,
+                        state,
                     );
                 }
+                Action::Launch(launch_info) => {
+                    if execute_launch(launch_info, settings, state) {
+                        return None;
+                    }
+                }
+                Action::WaitForWindow(window_info) => {
+                    execute_wait_for_window(window_info, settings)
+                }
+                Action::ActivateWindow(window_info) => execute_activate_window(window_info),
+                Action::MoveWindow(window_info, rect) => execute_move_window(window_info, *rect),
+                Action::SetClipboard(text) => execute_set_clipboard(text, state),
+                Action::CopyClipboard(name) => execute_copy_clipboard(name, state),
+                Action::WaitForClipboardChange => execute_wait_for_clipboard_change(settings),
+                Action::IfClipboard(regex) => if_stack.push(execute_if_clipboard(regex)),
+                Action::Drag(drag_info) => {
+                    if execute_drag(drag_info, settings, state) {
+                        return None;
+                    }
+                }
             }
         }
 
@@ -335,13 +340,145 @@ fn play(
     None
 }
 
-fn execute_if_image(image: &ImageInfo) -> bool {
+/// Where playing back the point puts the cursor, None if its window isn't found. Offsets are from
+/// `relative_to`, the cursor or the start of a drag.
+fn screen_position(
+    point: &MousePointKind,
+    relative_to: Point,
+    state: &mut PlaybackState,
+) -> Option<Point> {
+    match point {
+        MousePointKind::To(point) => Some(*point),
+        MousePointKind::By(point) => Some(Point {
+            x: relative_to.x + point.x,
+            y: relative_to.y + point.y,
+        }),
+        MousePointKind::Window(window_info, point) => window_point_to_screen(window_info, *point),
+        MousePointKind::Monitor(monitor, point) => Some(state.monitors.to_screen(monitor, *point)),
+    }
+}
+
+/// Returns true if playback was stopped, the button is released either way.
+fn execute_drag(drag_info: &DragInfo, settings: &Settings, state: &mut PlaybackState) -> bool {
+    let Some(from) = screen_position(&drag_info.from, cursor(), state) else {
+        return false;
+    };
+    let Some(to) = screen_position(&drag_info.to, from, state) else {
+        return false;
+    };
+
+    let waypoints = drag_info
+        .path
+        .iter()
+        .map(|offset| Point {
+            x: from.x + offset.x,
+            y: from.y + offset.y,
+        })
+        .chain(std::iter::once(to))
+        .collect::<Vec<_>>();
+
+    let button = |state| MouseActionButton {
+        point: None,
+        button: drag_info.button,
+        state,
+    };
+
+    move_cursor(from);
+    execute_mouse_action(&button(MouseActionButtonState::Pressed), state);
+
+    let step_duration =
+        drag_info.duration as f64 / waypoints.len() as f64 / settings.playback_speed as f64;
+    let time_started = DateTime::<Utc>::from(SystemTime::now());
+    let mut stopped = false;
+
+    for (index, waypoint) in waypoints.iter().enumerate() {
+        let step_end = step_duration * (index + 1) as f64;
+        while ((DateTime::<Utc>::from(SystemTime::now()) - time_started).num_milliseconds() as f64)
+            < step_end
+        {
+            if stop_key_pressed(settings) {
+                stopped = true;
+                break;
+            }
+        }
+
+        if stopped {
+            break;
+        }
+
+        move_cursor(*waypoint);
+    }
+
+    execute_mouse_action(&button(MouseActionButtonState::Released), state);
+
+    stopped
+}
+
+/// Moves the mouse along a curve instead of jumping, returns true if playback was stopped.
+fn move_mouse_humanized(
+    point: &MousePointKind,
+    settings: &Settings,
+    state: &mut PlaybackState,
+) -> bool {
+    let from = cursor();
+
+    let Some(to) = screen_position(point, from, state) else {
+        return false;
+    };
+
+    let curve = MouseCurve::new(from, to, &mut state.rng);
+    let duration = curve.distance() * 1000.0
+        / settings.humanize_mouse_speed as f64
+        / settings.playback_speed as f64;
+
+    let time_started = DateTime::<Utc>::from(SystemTime::now());
+    loop {
+        if stop_key_pressed(settings) {
+            return true;
+        }
+
+        let elapsed = (DateTime::<Utc>::from(SystemTime::now()) - time_started).num_milliseconds();
+        let progress = if duration > 0.0 {
+            elapsed as f64 / duration
+        } else {
+            1.0
+        };
+
+        let position = curve.at(progress);
+        move_cursor(position);
+
+        if progress >= 1.0 {
+            return false;
+        }
+    }
+}
+
+/// Screen position that search locations are measured from, None if the anchor can't be found.
+fn search_anchor_origin(anchor: &SearchAnchor, state: &PlaybackState) -> Option<Point> {
+    match anchor {
+        SearchAnchor::Screen => Some(Point { x: 0, y: 0 }),
+        SearchAnchor::Window(window_info) => {
+            window_point_to_screen(window_info, Point { x: 0, y: 0 })
+        }
+        SearchAnchor::LastFoundImage => state.last_found_image,
+    }
+}
+
+fn execute_if_image(image: &ImageInfo, state: &mut PlaybackState) -> bool {
     let search_coordinates = match (
         image.search_location_left_top,
         image.search_location_width_height,
+        &image.search_anchor,
     ) {
-        (Some(left_top), Some(width_height)) => {
-            let corner1 = pos2(left_top.0 as f32, left_top.1 as f32);
+        (Some(left_top), Some(width_height), anchor) => {
+            let Some(origin) = search_anchor_origin(anchor, state) else {
+                return image.check_if_not_found;
+            };
+
+            let corner1 = pos2(
+                (origin.x + left_top.0) as f32,
+                (origin.y + left_top.1) as f32,
+            );
             let corner2 = pos2(
                 corner1.x + width_height.0 as f32,
                 corner1.y + width_height.1 as f32,
@@ -349,6 +486,16 @@ fn execute_if_image(image: &ImageInfo) -> bool {
 
             Some((corner1, corner2))
         }
+        (_, _, SearchAnchor::Window(window_info)) => match window_client_rect(window_info) {
+            Ok(rect) => Some((
+                pos2(rect.x as f32, rect.y as f32),
+                pos2((rect.x + rect.width) as f32, (rect.y + rect.height) as f32),
+            )),
+            Err(error) => {
+                println!("Failed to find window to search: {}", error);
+                return image.check_if_not_found;
+            }
+        },
         _ => None,
     };
 
@@ -358,7 +505,9 @@ fn execute_if_image(image: &ImageInfo) -> bool {
         find_image(image.screenshot_raw.as_ref().unwrap(), search_coordinates)
     };
 
-    println!("{}", similarity);
+    if similarity >= image.image_similarity {
+        state.last_found_image = Some(Point { x, y });
+    }
 
     if image.check_if_not_found {
         if similarity < image.image_similarity {
@@ -369,7 +518,7 @@ fn execute_if_image(image: &ImageInfo) -> bool {
     } else {
         if similarity >= image.image_similarity {
             if image.move_mouse_if_found {
-                unsafe { SetCursorPos(x, y) };
+                move_cursor(Point { x, y });
             }
             true
         } else {
@@ -378,10 +527,14 @@ fn execute_if_image(image: &ImageInfo) -> bool {
     }
 }
 
-fn execute_if_pixel(pixel_info: &PixelInfo) -> bool {
+fn execute_if_pixel(pixel_info: &PixelInfo, state: &PlaybackState) -> bool {
+    let Some(origin) = search_anchor_origin(&pixel_info.search_anchor, state) else {
+        return pixel_info.check_if_not_found;
+    };
+
     let corner1 = pos2(
-        pixel_info.search_location_left_top.0 as f32,
-        pixel_info.search_location_left_top.1 as f32,
+        (origin.x + pixel_info.search_location_left_top.0) as f32,
+        (origin.y + pixel_info.search_location_left_top.1) as f32,
     );
     let corner2 = pos2(
         corner1.x + pixel_info.search_location_width_height.0 as f32,
@@ -395,7 +548,10 @@ fn execute_if_pixel(pixel_info: &PixelInfo) -> bool {
     } else {
         if let Some(result) = result {
             if pixel_info.move_mouse_if_found {
-                unsafe { SetCursorPos(result.0, result.1) };
+                move_cursor(Point {
+                    x: result.0,
+                    y: result.1,
+                });
             }
             true
         } else {
@@ -404,75 +560,335 @@ fn execute_if_pixel(pixel_info: &PixelInfo) -> bool {
     }
 }
 
-fn execute_wait_for_pixel(pixel_info: &PixelInfo) {
+fn execute_wait_for_pixel(pixel_info: &PixelInfo, settings: &Settings, state: &PlaybackState) {
     loop {
-        if stop_key_pressed() || execute_if_pixel(pixel_info) {
+        if stop_key_pressed(settings) || execute_if_pixel(pixel_info, state) {
             break;
         }
     }
 }
 
-fn execute_wait_for_image(image: &ImageInfo) {
+fn execute_wait_for_image(image: &ImageInfo, settings: &Settings, state: &mut PlaybackState) {
     loop {
-        if stop_key_pressed() || execute_if_image(image) {
+        if stop_key_pressed(settings) || execute_if_image(image, state) {
             break;
         }
     }
 }
 
-fn execute_scroll_wheel(amount: i32, point: Option<MousePointKind>) {
-    let (dx, dy) = if let Some(point) = point {
-        match point {
-            MousePointKind::To(_) => (0, 0),
-            MousePointKind::By(point) => (point.x, point.y),
+/// Returns true if playback was stopped while waiting for the program to exit.
+fn execute_launch(
+    launch_info: &LaunchInfo,
+    settings: &Settings,
+    state: &mut PlaybackState,
+) -> bool {
+    let mut command = Command::new(&launch_info.program);
+    command.args(&launch_info.args);
+
+    if let Some(working_dir) = &launch_info.working_dir {
+        command.current_dir(working_dir);
+    }
+
+    state.last_exit_code = None;
+    state.last_stdout = None;
+
+    if !launch_info.wait_for_exit {
+        if let Err(error) = command.spawn() {
+            println!("Failed to launch {:?}: {}", launch_info.program, error);
+        }
+        return false;
+    }
+
+    let mut child = match command.stdout(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(error) => {
+            println!("Failed to launch {:?}: {}", launch_info.program, error);
+            return false;
         }
-    } else {
-        (0, 0)
     };
 
-    let mouse_input = MOUSEINPUT {
-        dx,
-        dy,
-        mouseData: amount as u32,
-        dwFlags: MOUSEEVENTF_WHEEL,
-        dwExtraInfo: 0,
-        time: 0,
+    // Read on another thread so a chatty program can't fill the pipe and block forever
+    let mut stdout = child.stdout.take().unwrap();
+    let stdout_reader = thread::spawn(move || {
+        let mut output = vec![];
+        stdout.read_to_end(&mut output).ok();
+        String::from_utf8_lossy(&output).into_owned()
+    });
+
+    let time_started = DateTime::<Utc>::from(SystemTime::now());
+    let mut stopped = false;
+
+    let status = loop {
+        if let Ok(Some(status)) = child.try_wait() {
+            break Some(status);
+        }
+
+        let timed_out = launch_info.timeout.is_some_and(|timeout| {
+            (DateTime::<Utc>::from(SystemTime::now()) - time_started).num_milliseconds()
+                >= timeout as i64
+        });
+
+        stopped = stop_key_pressed(settings);
+
+        if stopped || timed_out {
+            child.kill().ok();
+            child.wait().ok();
+            break None;
+        }
+    };
+
+    state.last_exit_code = status.and_then(|status| status.code());
+    state.last_stdout = stdout_reader.join().ok();
+
+    stopped
+}
+
+fn execute_wait_for_window(window_info: &WindowInfo, settings: &Settings) {
+    let window_manager = match platform_window_manager() {
+        Ok(window_manager) => window_manager,
+        Err(error) => {
+            println!("{}", error);
+            return;
+        }
     };
 
-    let mut input = INPUT {
-        type_: INPUT_MOUSE,
-        u: unsafe { std::mem::transmute_copy(&mouse_input) },
+    loop {
+        match window_manager.find_window(window_info) {
+            Ok(Some(_)) => break,
+            Ok(None) => {}
+            Err(error) => {
+                println!("Failed to wait for window: {}", error);
+                break;
+            }
+        }
+        if stop_key_pressed(settings) {
+            break;
+        }
+        thread::sleep(WAIT_POLL_INTERVAL);
+    }
+}
+
+fn execute_activate_window(window_info: &WindowInfo) {
+    let result = platform_window_manager().and_then(|window_manager| {
+        let window = window_manager
+            .find_window(window_info)?
+            .ok_or("No matching window found")?;
+        window_manager.activate(window.id)
+    });
+
+    if let Err(error) = result {
+        println!("Failed to activate window: {}", error);
+    }
+}
+
+fn execute_move_window(window_info: &WindowInfo, rect: WindowRect) {
+    let result = platform_window_manager().and_then(|window_manager| {
+        let window = window_manager
+            .find_window(window_info)?
+            .ok_or("No matching window found")?;
+        window_manager.set_rect(window.id, rect)
+    });
+
+    if let Err(error) = result {
+        println!("Failed to move window: {}", error);
+    }
+}
+
+fn execute_set_clipboard(text: &str, state: &PlaybackState) {
+    if let Err(error) = clipboard::set_text(&state.substitute(text)) {
+        println!("Failed to set clipboard: {}", error);
+    }
+}
+
+fn execute_copy_clipboard(name: &str, state: &mut PlaybackState) {
+    match clipboard::get_text() {
+        Ok(text) => {
+            state.values.insert(name.to_string(), text);
+        }
+        Err(error) => println!("Failed to copy clipboard: {}", error),
+    }
+}
+
+fn execute_wait_for_clipboard_change(settings: &Settings) {
+    let sequence_number = clipboard::sequence_number();
+
+    while clipboard::sequence_number() == sequence_number {
+        if stop_key_pressed(settings) {
+            break;
+        }
+        thread::sleep(WAIT_POLL_INTERVAL);
+    }
+}
+
+fn execute_if_clipboard(regex: &str) -> bool {
+    let regex = match Regex::new(regex) {
+        Ok(regex) => regex,
+        Err(error) => {
+            println!("Invalid clipboard regex: {}", error);
+            return false;
+        }
     };
 
-    if let Some(point) = point {
-        if let MousePointKind::To(point) = point {
-            unsafe { SetCursorPos(point.x, point.y) };
+    match clipboard::get_text() {
+        Ok(text) => regex.is_match(&text),
+        Err(error) => {
+            println!("Failed to read clipboard: {}", error);
+            false
         }
     }
+}
+
+fn execute_scroll_wheel(
+    amount: i32,
+    point: Option<MousePointKind>,
+    horizontal: bool,
+    state: &mut PlaybackState,
+) {
+    if let Some(point) = &point {
+        set_cursor_to(point, state);
+    }
+
+    if let Err(error) = send_wheel(amount, horizontal) {
+        println!("Failed to turn the mouse wheel: {}", error);
+    }
+}
 
-    unsafe { SendInput(1, &mut input, std::mem::size_of::<INPUT>() as i32) };
+pub fn stop_key_pressed(settings: &Settings) -> bool {
+    hotkeys_pressed(&settings.stop_hotkey)
 }
 
-pub fn stop_key_pressed() -> bool {
-    unsafe { GetAsyncKeyState(VK_CONTROL) < 0 && GetAsyncKeyState(0x51) < 0 }
+pub fn play_key_pressed(settings: &Settings) -> bool {
+    hotkeys_pressed(&settings.play_hotkey)
 }
 
-pub fn play_key_pressed() -> bool {
-    unsafe { GetAsyncKeyState(VK_CONTROL) < 0 && GetAsyncKeyState(0x50) < 0 }
+pub fn record_key_pressed(settings: &Settings) -> bool {
+    hotkeys_pressed(&settings.record_hotkey)
 }
 
 pub fn load_from_file(path: &Path) -> Result<Vec<Action>, Box<dyn Error>> {
-    let actions = read_to_string(path)?;
-    let actions = serde_json::from_str(&actions)?;
-    Ok(actions)
+    Ok(load_macro_file(path)?.actions)
 }
 
-pub fn save_macro(path: &Path, action_list: &[Action]) -> Result<(), Box<dyn Error>> {
-    let mut file = File::create(path)?;
+/// Loads the actions along with who made the macro and when. Macros saved before images were
+/// stored as PNG files are plain JSON. Scripts don't have metadata, their images are read from
+/// next to them.
+pub fn load_macro_file(path: &Path) -> Result<MacroFile, Box<dyn Error>> {
+    if is_script(path) {
+        let folder = path.parent().unwrap_or(Path::new(""));
+        let actions = parse_script(&read_to_string(path)?, |name| Ok(read(folder.join(name))?))?;
+
+        return Ok(MacroFile {
+            actions,
+            ..Default::default()
+        });
+    }
+
+    let bytes = read(path)?;
+
+    if is_zip(&bytes) {
+        read_container(&bytes)
+    } else {
+        read_macro(std::str::from_utf8(&bytes)?)
+    }
+}
+
+pub fn save_macro(
+    path: &Path,
+    action_list: &[Action],
+    metadata: &MacroMetadata,
+) -> Result<(), Box<dyn Error>> {
+    if is_script(path) {
+        let folder = path.parent().unwrap_or(Path::new(""));
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        let script = print_script(action_list, &format!("{}-image", name));
+
+        for (name, screenshot) in &script.images {
+            write(folder.join(name), screenshot.to_png()?)?;
+        }
+        write(path, script.text)?;
+
+        return Ok(());
+    }
+
+    // Only screenshots need the container, everything else stays readable as plain JSON
+    let has_screenshots = action_list.iter().any(|action| {
+        matches!(
+            action,
+            Action::WaitForImage(image_info) | Action::IfImage(image_info)
+                if image_info.screenshot_raw.is_some()
+        )
+    });
 
-    let action_list = serde_json::to_string(action_list).unwrap();
+    let mut file = File::create(path)?;
 
-    file.write_all(action_list.as_bytes())?;
+    if has_screenshots {
+        file.write_all(&write_container(metadata, action_list)?)?;
+    } else {
+        file.write_all(write_macro(metadata, action_list).as_bytes())?;
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        recorder::keymap::VK_LBUTTON,
+        transform::{convert_points, mouse_points, PointMode},
+    };
+
+    /// Where playback puts the cursor for each mouse point, moves, clicks, wheels and drags all go
+    /// through `screen_position`.
+    fn played_positions(actions: &[Action], start: Point) -> Vec<Point> {
+        let mut state = PlaybackState::new(&Settings::default());
+        let mut cursor = start;
+
+        actions
+            .iter()
+            .flat_map(mouse_points)
+            .map(|point| {
+                cursor = screen_position(point, cursor, &mut state).unwrap();
+                cursor
+            })
+            .collect()
+    }
+
+    #[test]
+    fn plays_offsets_of_clicks_and_wheels_from_the_cursor() {
+        let to = |x, y| MousePointKind::To(Point { x, y });
+        let absolute = vec![
+            Action::Mouse(MouseActionKind::Moved(to(10, 10))),
+            // Clicked somewhere else than the last move
+            Action::Mouse(MouseActionKind::Button(MouseActionButton {
+                point: Some(to(20, 30)),
+                button: VK_LBUTTON,
+                state: MouseActionButtonState::Clicked,
+            })),
+            Action::Mouse(MouseActionKind::Wheel(-120, Some(to(25, 35)))),
+            Action::Drag(DragInfo {
+                from: to(40, 40),
+                to: to(60, 70),
+                ..Default::default()
+            }),
+            Action::Mouse(MouseActionKind::HorizontalWheel(120, Some(to(0, 0)))),
+        ];
+        let start = Point { x: 0, y: 0 };
+        let mut relative = absolute.clone();
+
+        convert_points(&mut relative, PointMode::Relative, start, None, &[]);
+
+        assert!(relative
+            .iter()
+            .flat_map(mouse_points)
+            .all(|point| matches!(point, MousePointKind::By(_))));
+        assert_eq!(
+            played_positions(&relative, start),
+            played_positions(&absolute, start)
+        );
+        assert_eq!(
+            played_positions(&absolute, start),
+            [(10, 10), (20, 30), (25, 35), (40, 40), (60, 70), (0, 0)].map(|(x, y)| Point { x, y })
+        );
+    }
+}
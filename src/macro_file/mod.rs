@@ -0,0 +1,468 @@
+use std::{collections::HashMap, error::Error};
+
+use chrono::Utc;
+use serde::*;
+use serde_json::{json, Value};
+
+use self::zip::{read_zip, ZipWriter};
+use crate::{
+    actions::{Action, Point},
+    images::{GrayImageSerializable, RawScreenshot, RawScreenshotPair},
+};
+
+mod zip;
+
+pub use self::zip::is_zip;
+
+const MANIFEST_NAME: &str = "manifest.json";
+
+/// Bumped whenever saved actions change in a way older files can't be read as, with a migration
+/// added to `MIGRATIONS` that brings the previous version up to date.
+pub const FORMAT_VERSION: u32 = 3;
+
+/// Brings a file from one version to the next, changing the JSON in place.
+type Migration = fn(&mut Value) -> Result<(), Box<dyn Error>>;
+
+/// Turns a file of the version at its index into the next version.
+const MIGRATIONS: [Migration; FORMAT_VERSION as usize] = [
+    plain_delays_to_jittered,
+    keys_without_scan_codes,
+    metadata_without_start_position,
+];
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct MacroMetadata {
+    #[serde(default)]
+    pub author: String,
+    /// RFC 3339 timestamps.
+    #[serde(default)]
+    pub created: Option<String>,
+    #[serde(default)]
+    pub modified: Option<String>,
+    /// Version of the macro recorder that last saved the file.
+    #[serde(default)]
+    pub recorder_version: String,
+    /// Where the cursor was when recording started, mouse moves recorded as offsets begin here.
+    pub start_position: Point,
+}
+
+impl MacroMetadata {
+    /// Stamps the metadata for saving, the author is only filled in if there isn't one.
+    pub fn touch(&mut self, author: &str) {
+        let now = Utc::now().to_rfc3339();
+
+        if self.author.is_empty() {
+            self.author = author.to_string();
+        }
+        if self.created.is_none() {
+            self.created = Some(now.clone());
+        }
+        self.modified = Some(now);
+        self.recorder_version = env!("CARGO_PKG_VERSION").to_string();
+    }
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct MacroFile {
+    pub metadata: MacroMetadata,
+    pub actions: Vec<Action>,
+}
+
+/// A screenshot of an image action, saved as PNG files next to the manifest.
+#[derive(Serialize, Deserialize, Debug)]
+struct ImageAsset {
+    /// Index of the action in the list, so migrations can't add or remove actions.
+    action: usize,
+    color: String,
+    gray: String,
+    x: i32,
+    y: i32,
+}
+
+#[derive(Deserialize)]
+struct ManifestImages {
+    #[serde(default)]
+    images: Vec<ImageAsset>,
+}
+
+/// Reads a macro saved by any version of the recorder, migrating it to the current format.
+pub fn read_macro(text: &str) -> Result<MacroFile, Box<dyn Error>> {
+    let mut file: Value = serde_json::from_str(text)?;
+
+    // Files used to be nothing but the list of actions
+    if file.is_array() {
+        file = json!({ "version": 0, "actions": file });
+    }
+
+    let version = file
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or("Not a macro file, it has no format version")?;
+
+    if version > FORMAT_VERSION as u64 {
+        return Err(format!(
+            "The macro was saved by a newer version of the recorder (format version {}, this one reads up to {})",
+            version, FORMAT_VERSION
+        )
+        .into());
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(&mut file)?;
+    }
+
+    let metadata = match file.get_mut("metadata") {
+        Some(metadata) => serde_json::from_value(metadata.take())?,
+        None => MacroMetadata::default(),
+    };
+
+    // Point at the action that's wrong instead of somewhere in the whole file
+    let actions = actions_mut(&mut file)?
+        .drain(..)
+        .enumerate()
+        .map(|(index, action)| {
+            serde_json::from_value(action).map_err(|error| -> Box<dyn Error> {
+                format!("Action {} couldn't be read: {}", index + 1, error).into()
+            })
+        })
+        .collect::<Result<Vec<Action>, Box<dyn Error>>>()?;
+
+    Ok(MacroFile { metadata, actions })
+}
+
+#[derive(Serialize)]
+struct SavedMacro<'a> {
+    version: u32,
+    metadata: &'a MacroMetadata,
+    actions: &'a [Action],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    images: Option<&'a [ImageAsset]>,
+}
+
+/// Saves the macro as plain JSON, with the images in it. Used for macros without any.
+pub fn write_macro(metadata: &MacroMetadata, actions: &[Action]) -> String {
+    serde_json::to_string(&SavedMacro {
+        version: FORMAT_VERSION,
+        metadata,
+        actions,
+        images: None,
+    })
+    .unwrap()
+}
+
+/// Saves the macro as a zip file holding the JSON manifest and the screenshots of image actions
+/// as PNG files, named after their contents so each one is only stored once.
+pub fn write_container(
+    metadata: &MacroMetadata,
+    actions: &[Action],
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut actions = actions.to_vec();
+    let mut archive = ZipWriter::default();
+    let mut written = HashMap::new();
+    let mut images = vec![];
+
+    for (index, action) in actions.iter_mut().enumerate() {
+        let (Action::WaitForImage(image_info) | Action::IfImage(image_info)) = action else {
+            continue;
+        };
+        let Some(screenshot) = image_info.screenshot_raw.take() else {
+            continue;
+        };
+
+        let mut add_asset = |png: Vec<u8>| -> Result<String, Box<dyn Error>> {
+            let (name, is_new) = asset_name(&written, content_hash(&png), &png);
+            if is_new {
+                archive.add(&name, &png)?;
+                written.insert(name.clone(), png);
+            }
+            Ok(name)
+        };
+
+        images.push(ImageAsset {
+            action: index,
+            color: add_asset(screenshot.color.to_png()?)?,
+            gray: add_asset(screenshot.gray.to_png()?)?,
+            x: screenshot.color.x,
+            y: screenshot.color.y,
+        });
+    }
+
+    let manifest = serde_json::to_vec_pretty(&SavedMacro {
+        version: FORMAT_VERSION,
+        metadata,
+        actions: &actions,
+        images: Some(&images),
+    })?;
+    archive.add(MANIFEST_NAME, &manifest)?;
+
+    archive.finish()
+}
+
+pub fn read_container(bytes: &[u8]) -> Result<MacroFile, Box<dyn Error>> {
+    let files = read_zip(bytes)?;
+    let manifest = files
+        .get(MANIFEST_NAME)
+        .ok_or("The macro has no manifest.json")?;
+    let manifest = std::str::from_utf8(manifest)?;
+
+    let mut file = read_macro(manifest)?;
+    let images: ManifestImages = serde_json::from_str(manifest)?;
+
+    for image in images.images {
+        let asset = |name: &str| {
+            files
+                .get(name)
+                .copied()
+                .ok_or_else(|| format!("{} is missing from the macro", name))
+        };
+
+        let screenshot = RawScreenshotPair {
+            color: RawScreenshot::from_png(asset(&image.color)?, image.x, image.y)?,
+            gray: GrayImageSerializable::from_png(asset(&image.gray)?)?,
+        };
+
+        match file.actions.get_mut(image.action) {
+            Some(Action::WaitForImage(image_info) | Action::IfImage(image_info)) => {
+                image_info.screenshot_raw = Some(screenshot)
+            }
+            _ => {
+                return Err(format!(
+                    "{} belongs to action {}, which isn't an image action",
+                    image.color,
+                    image.action + 1
+                )
+                .into())
+            }
+        }
+    }
+
+    Ok(file)
+}
+
+/// Name of the PNG file for an image and whether it still has to be written. Images are named
+/// after their hash, a different image with the same hash gets a number after it.
+fn asset_name(written: &HashMap<String, Vec<u8>>, hash: u64, png: &[u8]) -> (String, bool) {
+    let mut name = format!("images/{:016x}.png", hash);
+    let mut number = 1;
+
+    while let Some(stored) = written.get(&name) {
+        if stored == png {
+            return (name, false);
+        }
+        number += 1;
+        name = format!("images/{:016x}-{}.png", hash, number);
+    }
+
+    (name, true)
+}
+
+/// FNV-1a, it only has to tell images in the same file apart and collisions are checked.
+fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn actions_mut(file: &mut Value) -> Result<&mut Vec<Value>, Box<dyn Error>> {
+    match file.get_mut("actions") {
+        Some(Value::Array(actions)) => Ok(actions),
+        _ => Err("Not a macro file, it has no list of actions".into()),
+    }
+}
+
+/// Version 0 saved delays without jitter as just the milliseconds.
+fn plain_delays_to_jittered(file: &mut Value) -> Result<(), Box<dyn Error>> {
+    for action in actions_mut(file)? {
+        if let Some(delay) = action.get_mut("Delay") {
+            if delay.is_u64() {
+                *delay = json!([delay.take(), "None"]);
+            }
+        }
+    }
+
+    file["version"] = json!(1);
+    Ok(())
+}
+
+/// Version 1 saved keys without a scan code.
+fn keys_without_scan_codes(file: &mut Value) -> Result<(), Box<dyn Error>> {
+    for action in actions_mut(file)? {
+        if let Some(Value::Array(key)) = action.get_mut("Keyboard") {
+            if key.len() == 2 {
+                key.push(Value::Null);
+            }
+        }
+    }
+
+    file["version"] = json!(2);
+    Ok(())
+}
+
+/// Version 2 didn't save where the recording started, it's taken to be the top left corner.
+fn metadata_without_start_position(file: &mut Value) -> Result<(), Box<dyn Error>> {
+    if !file["metadata"].is_object() {
+        file["metadata"] = json!({});
+    }
+    file["metadata"]["start_position"] = json!(Point { x: 0, y: 0 });
+
+    file["version"] = json!(3);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::{DelayJitter, KeyState, ScanCode};
+
+    #[test]
+    fn reads_bare_action_lists() {
+        let file = read_macro(r#"[{"Delay":25},{"Delay":[10,{"Uniform":3}]},"EndIf"]"#).unwrap();
+
+        assert_eq!(
+            format!("{:?}", file.actions),
+            format!(
+                "{:?}",
+                [
+                    Action::Delay(25, DelayJitter::None),
+                    Action::Delay(10, DelayJitter::Uniform(3)),
+                    Action::EndIf
+                ]
+            )
+        );
+        assert!(file.metadata.author.is_empty());
+        assert_eq!(file.metadata.start_position, Point { x: 0, y: 0 });
+    }
+
+    #[test]
+    fn adds_scan_codes_to_old_keys() {
+        let file = read_macro(
+            r#"{"version":1,"actions":[{"Keyboard":[65,"Down"]},{"Keyboard":[66,"Up",{"code":48,"extended":false}]}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            format!("{:?}", file.actions),
+            format!(
+                "{:?}",
+                [
+                    Action::Keyboard(65, KeyState::Down, None),
+                    Action::Keyboard(
+                        66,
+                        KeyState::Up,
+                        Some(ScanCode {
+                            code: 48,
+                            extended: false
+                        })
+                    )
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn reads_back_what_it_writes() {
+        let mut metadata = MacroMetadata {
+            start_position: Point { x: 640, y: 360 },
+            ..Default::default()
+        };
+        metadata.touch("Fluffy");
+
+        let text = write_macro(&metadata, &[Action::Delay(5, DelayJitter::Normal(2))]);
+        let file = read_macro(&text).unwrap();
+
+        assert_eq!(file.metadata.author, "Fluffy");
+        assert!(file.metadata.created.is_some());
+        assert_eq!(file.metadata.start_position, Point { x: 640, y: 360 });
+        assert_eq!(
+            format!("{:?}", file.actions),
+            format!("{:?}", [Action::Delay(5, DelayJitter::Normal(2))])
+        );
+    }
+
+    #[test]
+    fn stores_each_image_once() {
+        let color = RawScreenshot {
+            pixels: vec![10, 20, 30, 255, 40, 50, 60, 255],
+            width: 2,
+            height: 1,
+            x: 5,
+            y: 7,
+        };
+        let screenshot = RawScreenshotPair {
+            gray: GrayImageSerializable(image::GrayImage::from_raw(2, 1, vec![20, 50]).unwrap()),
+            color,
+        };
+        let image_action = Action::IfImage(crate::actions::ImageInfo {
+            screenshot_raw: Some(screenshot),
+            ..Default::default()
+        });
+        let actions = [image_action.clone(), Action::EndIf, image_action];
+
+        let bytes = write_container(&MacroMetadata::default(), &actions).unwrap();
+
+        assert!(is_zip(&bytes));
+        assert_eq!(read_zip(&bytes).unwrap().len(), 3);
+
+        let file = read_container(&bytes).unwrap();
+        let Action::IfImage(image_info) = &file.actions[2] else {
+            panic!("{:?}", file.actions[2]);
+        };
+        let screenshot = image_info.screenshot_raw.as_ref().unwrap();
+
+        assert_eq!(
+            screenshot.color.pixels,
+            vec![10, 20, 30, 255, 40, 50, 60, 255]
+        );
+        assert_eq!((screenshot.color.x, screenshot.color.y), (5, 7));
+        assert_eq!(screenshot.gray.as_raw(), &vec![20, 50]);
+    }
+
+    #[test]
+    fn starts_old_recordings_at_the_top_left() {
+        let file =
+            read_macro(r#"{"version":2,"metadata":{"author":"Fluffy"},"actions":[]}"#).unwrap();
+
+        assert_eq!(file.metadata.author, "Fluffy");
+        assert_eq!(file.metadata.start_position, Point { x: 0, y: 0 });
+    }
+
+    #[test]
+    fn numbers_different_images_with_the_same_hash() {
+        let mut written = HashMap::new();
+        written.insert("images/000000000000002a.png".to_string(), vec![1, 2, 3]);
+
+        assert_eq!(
+            asset_name(&written, 42, &[1, 2, 3]),
+            ("images/000000000000002a.png".to_string(), false)
+        );
+        assert_eq!(
+            asset_name(&written, 42, &[4, 5, 6]),
+            ("images/000000000000002a-2.png".to_string(), true)
+        );
+
+        written.insert("images/000000000000002a-2.png".to_string(), vec![4, 5, 6]);
+
+        assert_eq!(
+            asset_name(&written, 42, &[4, 5, 6]),
+            ("images/000000000000002a-2.png".to_string(), false)
+        );
+        assert_eq!(
+            asset_name(&written, 42, &[7]),
+            ("images/000000000000002a-3.png".to_string(), true)
+        );
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let text = format!(r#"{{"version":{},"actions":[]}}"#, FORMAT_VERSION + 1);
+
+        assert!(read_macro(&text).is_err());
+    }
+
+    #[test]
+    fn says_which_action_is_wrong() {
+        let error = read_macro(r#"[{"Delay":1},{"Teleport":[1,2]}]"#).unwrap_err();
+
+        assert!(error.to_string().starts_with("Action 2 "));
+    }
+}
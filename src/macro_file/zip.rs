@@ -0,0 +1,209 @@
+//! Just enough of the zip format to store files uncompressed, which is all macros need since the
+//! bulk of them is images that are already compressed PNGs.
+
+use std::{collections::HashMap, error::Error};
+
+const LOCAL_HEADER: u32 = 0x04034b50;
+const CENTRAL_HEADER: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+
+const LOCAL_HEADER_SIZE: usize = 30;
+const CENTRAL_HEADER_SIZE: usize = 46;
+const END_OF_CENTRAL_DIRECTORY_SIZE: usize = 22;
+
+/// Version 2.0, the first to have folders.
+const VERSION: u16 = 20;
+const UTF8_NAMES: u16 = 1 << 11;
+const STORED: u16 = 0;
+/// January 1st 1980, zip files can't have anything earlier.
+const DOS_DATE: u16 = 1 << 5 | 1;
+
+pub fn is_zip(bytes: &[u8]) -> bool {
+    bytes.starts_with(&LOCAL_HEADER.to_le_bytes())
+}
+
+#[derive(Default)]
+pub struct ZipWriter {
+    bytes: Vec<u8>,
+    central_directory: Vec<u8>,
+    entries: u16,
+}
+
+impl ZipWriter {
+    pub fn add(&mut self, name: &str, contents: &[u8]) -> Result<(), Box<dyn Error>> {
+        let too_large = |_| format!("{} is too large to save", name);
+        let size = u32::try_from(contents.len()).map_err(too_large)?;
+        let offset = u32::try_from(self.bytes.len()).map_err(too_large)?;
+        let name_length = u16::try_from(name.len()).map_err(too_large)?;
+        self.entries = self
+            .entries
+            .checked_add(1)
+            .ok_or("Too many files to save")?;
+
+        // Everything from the version needed up to the extra field length is in both headers
+        let mut fields = vec![];
+        put16(&mut fields, VERSION);
+        put16(&mut fields, UTF8_NAMES);
+        put16(&mut fields, STORED);
+        put16(&mut fields, 0);
+        put16(&mut fields, DOS_DATE);
+        put32(&mut fields, crc32fast::hash(contents));
+        put32(&mut fields, size);
+        put32(&mut fields, size);
+        put16(&mut fields, name_length);
+        put16(&mut fields, 0);
+
+        put32(&mut self.bytes, LOCAL_HEADER);
+        self.bytes.extend(&fields);
+        self.bytes.extend(name.as_bytes());
+        self.bytes.extend(contents);
+
+        let central_directory = &mut self.central_directory;
+        put32(central_directory, CENTRAL_HEADER);
+        put16(central_directory, VERSION);
+        central_directory.extend(&fields);
+        // Comment length, disk number and file attributes
+        put16(central_directory, 0);
+        put16(central_directory, 0);
+        put16(central_directory, 0);
+        put32(central_directory, 0);
+        put32(central_directory, offset);
+        central_directory.extend(name.as_bytes());
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let offset =
+            u32::try_from(self.bytes.len()).map_err(|_| "The macro is too large to save")?;
+        let size = self.central_directory.len() as u32;
+
+        self.bytes.append(&mut self.central_directory);
+
+        put32(&mut self.bytes, END_OF_CENTRAL_DIRECTORY);
+        put16(&mut self.bytes, 0);
+        put16(&mut self.bytes, 0);
+        put16(&mut self.bytes, self.entries);
+        put16(&mut self.bytes, self.entries);
+        put32(&mut self.bytes, size);
+        put32(&mut self.bytes, offset);
+        put16(&mut self.bytes, 0);
+
+        Ok(self.bytes)
+    }
+}
+
+/// The files in the archive by name.
+pub fn read_zip(bytes: &[u8]) -> Result<HashMap<String, &[u8]>, Box<dyn Error>> {
+    // The end record is last, unless the archive has a comment after it
+    let end = (0..=bytes.len().saturating_sub(END_OF_CENTRAL_DIRECTORY_SIZE))
+        .rev()
+        .find(|&at| matches!(read32(bytes, at), Ok(END_OF_CENTRAL_DIRECTORY)))
+        .ok_or("Not a zip file, the end of it is missing")?;
+
+    let entries = read16(bytes, end + 10)?;
+    let mut at = read32(bytes, end + 16)? as usize;
+    let mut files = HashMap::new();
+
+    for _ in 0..entries {
+        if read32(bytes, at)? != CENTRAL_HEADER {
+            return Err(damaged());
+        }
+
+        let method = read16(bytes, at + 10)?;
+        let crc = read32(bytes, at + 16)?;
+        let size = read32(bytes, at + 20)? as usize;
+        let name_length = read16(bytes, at + 28)? as usize;
+        let extra_length = read16(bytes, at + 30)? as usize;
+        let comment_length = read16(bytes, at + 32)? as usize;
+        let offset = read32(bytes, at + 42)? as usize;
+
+        let name_start = at + CENTRAL_HEADER_SIZE;
+        let name = bytes
+            .get(name_start..name_start + name_length)
+            .ok_or_else(damaged)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+
+        if method != STORED {
+            return Err(format!(
+                "{} is compressed, only uncompressed files can be read",
+                name
+            )
+            .into());
+        }
+
+        // The local header can have a different extra field than the central directory
+        if read32(bytes, offset)? != LOCAL_HEADER {
+            return Err(damaged());
+        }
+        let contents_start = offset
+            + LOCAL_HEADER_SIZE
+            + read16(bytes, offset + 26)? as usize
+            + read16(bytes, offset + 28)? as usize;
+        let contents = bytes
+            .get(contents_start..contents_start + size)
+            .ok_or_else(damaged)?;
+
+        if crc32fast::hash(contents) != crc {
+            return Err(format!("{} is damaged", name).into());
+        }
+
+        files.insert(name, contents);
+        at = name_start + name_length + extra_length + comment_length;
+    }
+
+    Ok(files)
+}
+
+fn damaged() -> Box<dyn Error> {
+    "The zip file is damaged".into()
+}
+
+fn put16(bytes: &mut Vec<u8>, value: u16) {
+    bytes.extend(value.to_le_bytes());
+}
+
+fn put32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend(value.to_le_bytes());
+}
+
+fn read16(bytes: &[u8], at: usize) -> Result<u16, Box<dyn Error>> {
+    let field = bytes.get(at..at + 2).ok_or_else(damaged)?;
+    Ok(u16::from_le_bytes([field[0], field[1]]))
+}
+
+fn read32(bytes: &[u8], at: usize) -> Result<u32, Box<dyn Error>> {
+    let field = bytes.get(at..at + 4).ok_or_else(damaged)?;
+    Ok(u32::from_le_bytes([field[0], field[1], field[2], field[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_back_what_it_writes() {
+        let mut writer = ZipWriter::default();
+        writer.add("manifest.json", b"{}").unwrap();
+        writer.add("images/ab.png", &[1, 2, 3]).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        assert!(is_zip(&bytes));
+
+        let files = read_zip(&bytes).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files["manifest.json"], b"{}");
+        assert_eq!(files["images/ab.png"], [1, 2, 3]);
+    }
+
+    #[test]
+    fn notices_damage() {
+        let mut writer = ZipWriter::default();
+        writer.add("manifest.json", b"{\"version\":1}").unwrap();
+        let mut bytes = writer.finish().unwrap();
+
+        bytes[LOCAL_HEADER_SIZE + "manifest.json".len() + 2] ^= 0xff;
+
+        assert!(read_zip(&bytes).is_err());
+    }
+}
@@ -1,9 +1,8 @@
 use egui::{vec2, Align, Align2, Context, Key, Layout, Rect, Ui, Window};
 use std::cell::RefCell;
 use strum_macros::EnumIter;
-use winapi::um::winuser::GetAsyncKeyState;
 
-use crate::{actions::Action, gui::Recorder, modals::ModalWindow};
+use crate::{actions::Action, gui::Recorder, input::key_pressed, modals::ModalWindow};
 
 #[derive(Clone, Copy, Debug)]
 pub enum ActionListCategory {
@@ -11,6 +10,7 @@ pub enum ActionListCategory {
     If,
     Repeat,
     Other,
+    System,
 }
 
 #[derive(Clone, Copy, Debug, EnumIter)]
@@ -26,16 +26,38 @@ enum SubCategory {
     EndRepeat,
     Break,
     Play,
+    Launch,
+    WaitForWindow,
+    ActivateWindow,
+    MoveWindow,
+    WaitForClipboardChange,
+    IfClipboard,
+    SetClipboard,
+    CopyClipboard,
+    Drag,
 }
 
 impl ActionListCategory {
     fn get_categories(&self) -> &[SubCategory] {
         use SubCategory::*;
         match *self {
-            ActionListCategory::Wait => &[Delay, WaitForImage, WaitForPixel],
-            ActionListCategory::If => &[IfImage, IfPixel, Else, EndIf],
+            ActionListCategory::Wait => &[
+                Delay,
+                WaitForImage,
+                WaitForPixel,
+                WaitForWindow,
+                WaitForClipboardChange,
+            ],
+            ActionListCategory::If => &[IfImage, IfPixel, IfClipboard, Else, EndIf],
             ActionListCategory::Repeat => &[Repeat, EndRepeat, Break],
-            ActionListCategory::Other => &[Play],
+            ActionListCategory::Other => &[Play, Drag],
+            ActionListCategory::System => &[
+                Launch,
+                ActivateWindow,
+                MoveWindow,
+                SetClipboard,
+                CopyClipboard,
+            ],
         }
     }
 }
@@ -44,7 +66,7 @@ impl SubCategory {
     fn get_default_action(&self) -> Action {
         use SubCategory::*;
         match *self {
-            Delay => Action::Delay(0),
+            Delay => Action::Delay(0, Default::default()),
             WaitForImage => Action::WaitForImage(Default::default()),
             WaitForPixel => Action::WaitForPixel(Default::default()),
             IfImage => Action::IfImage(Default::default()),
@@ -55,6 +77,15 @@ impl SubCategory {
             EndRepeat => Action::EndRepeat,
             Break => Action::Break,
             Play => Action::Play(Default::default()),
+            Launch => Action::Launch(Default::default()),
+            WaitForWindow => Action::WaitForWindow(Default::default()),
+            ActivateWindow => Action::ActivateWindow(Default::default()),
+            MoveWindow => Action::MoveWindow(Default::default(), Default::default()),
+            WaitForClipboardChange => Action::WaitForClipboardChange,
+            IfClipboard => Action::IfClipboard(Default::default()),
+            SetClipboard => Action::SetClipboard(Default::default()),
+            CopyClipboard => Action::CopyClipboard(Default::default()),
+            Drag => Action::Drag(Default::default()),
         }
     }
 }
@@ -74,6 +105,15 @@ impl ToString for SubCategory {
             EndRepeat => "End Repeat".into(),
             Break => "Break".into(),
             Play => "Play".into(),
+            Launch => "Launch Program".into(),
+            WaitForWindow => "Wait For Window".into(),
+            ActivateWindow => "Activate Window".into(),
+            MoveWindow => "Move / Resize Window".into(),
+            WaitForClipboardChange => "Wait For Clipboard Change".into(),
+            IfClipboard => "If Clipboard Matches".into(),
+            SetClipboard => "Set Clipboard".into(),
+            CopyClipboard => "Copy Clipboard".into(),
+            Drag => "Mouse Drag".into(),
         }
     }
 }
@@ -174,7 +214,7 @@ impl ModalWindow for ActionListWindow {
 fn get_pressed_numbers() -> Vec<i32> {
     (0x31..=0x39)
         .filter_map(|code| {
-            if unsafe { GetAsyncKeyState(code) < 0 } {
+            if key_pressed(code) {
                 Some(code - 0x30)
             } else {
                 None
@@ -0,0 +1,177 @@
+use std::{cell::RefCell, fmt::Display, rc::Rc};
+
+use egui::*;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use crate::{gui::Recorder, transform::*};
+
+use super::ModalWindow;
+
+#[derive(PartialEq, Eq, EnumIter, Clone, Copy)]
+enum Cleanup {
+    MergeDelays,
+    ClampDelays,
+    ScaleDelays,
+    DropShortDelays,
+    RemoveRedundantMoves,
+}
+
+impl Display for Cleanup {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::MergeDelays => "Merge Adjacent Delays",
+                Self::ClampDelays => "Clamp Delays",
+                Self::ScaleDelays => "Scale Delays",
+                Self::DropShortDelays => "Drop Short Delays",
+                Self::RemoveRedundantMoves => "Remove Redundant Moves",
+            }
+        )
+    }
+}
+
+pub struct CleanupWindow {
+    data: RefCell<CleanupWindowData>,
+}
+
+struct CleanupWindowData {
+    cleanup: Cleanup,
+    min_textedit_text: String,
+    max_textedit_text: String,
+    factor_textedit_text: String,
+}
+
+impl CleanupWindow {
+    pub fn new() -> Rc<dyn ModalWindow> {
+        Rc::new(Self {
+            data: RefCell::new(CleanupWindowData {
+                cleanup: Cleanup::MergeDelays,
+                min_textedit_text: "10".into(),
+                max_textedit_text: "1000".into(),
+                factor_textedit_text: "1.0".into(),
+            }),
+        })
+    }
+
+    fn setup(&self, _recorder: &mut Recorder, drag_bounds: Rect) -> Window {
+        Window::new("Clean Up")
+            .collapsible(false)
+            .resizable(false)
+            .drag_bounds(drag_bounds)
+            .anchor(Align2::CENTER_CENTER, vec2(0.0, 0.0))
+    }
+
+    /// Returns false if the amounts for the cleanup don't parse.
+    fn apply(&self, data: &CleanupWindowData, recorder: &mut Recorder) -> bool {
+        let min = data.min_textedit_text.parse::<u32>();
+        let max = data.max_textedit_text.parse::<u32>();
+        let factor = data.factor_textedit_text.parse::<f32>();
+
+        match (data.cleanup, min, max, factor) {
+            (Cleanup::MergeDelays, ..) => recorder.transform_selection(merge_adjacent_delays),
+            (Cleanup::ClampDelays, Ok(min), Ok(max), _) => {
+                recorder.transform_selection(|actions| clamp_delays(actions, min, max))
+            }
+            (Cleanup::ScaleDelays, _, _, Ok(factor)) => {
+                recorder.transform_selection(|actions| scale_delays(actions, factor))
+            }
+            (Cleanup::DropShortDelays, Ok(min), ..) => {
+                recorder.transform_selection(|actions| drop_short_delays(actions, min))
+            }
+            (Cleanup::RemoveRedundantMoves, ..) => {
+                recorder.transform_selection(remove_redundant_moves)
+            }
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+impl ModalWindow for CleanupWindow {
+    fn update(
+        &self,
+        recorder: &mut Recorder,
+        ctx: &Context,
+        _ui: &mut Ui,
+        drag_bounds: Rect,
+        _frame: &mut eframe::Frame,
+    ) {
+        let window = self.setup(recorder, drag_bounds);
+
+        window.show(ctx, |ui| {
+            let mut data = self.data.borrow_mut();
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
+            ui.label(
+                if recorder
+                    .selected_rows()
+                    .is_some_and(|rows| rows.start() != rows.end())
+                {
+                    "Cleans up the selected rows."
+                } else {
+                    "Cleans up the whole macro. Shift click rows to clean up only some of them."
+                },
+            );
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                let fields = &mut *data;
+                let selected = &mut fields.cleanup;
+
+                ComboBox::new("Cleanup Combo Box", "")
+                    .selected_text(format!("{}", selected))
+                    .width(200.0)
+                    .show_ui(ui, |ui| {
+                        for cleanup in Cleanup::iter() {
+                            let text = format!("{}", cleanup);
+
+                            ui.selectable_value(selected, cleanup, text);
+
+                            ui.allocate_space(vec2(0.0, 3.5));
+                        }
+                    });
+
+                let amounts: Vec<(&mut String, &str)> = match fields.cleanup {
+                    Cleanup::ClampDelays => vec![
+                        (&mut fields.min_textedit_text, "ms at least,"),
+                        (&mut fields.max_textedit_text, "ms at most"),
+                    ],
+                    Cleanup::ScaleDelays => vec![(&mut fields.factor_textedit_text, "times")],
+                    Cleanup::DropShortDelays => {
+                        vec![(
+                            &mut fields.min_textedit_text,
+                            "ms, shorter delays are removed",
+                        )]
+                    }
+                    Cleanup::MergeDelays | Cleanup::RemoveRedundantMoves => vec![],
+                };
+
+                for (text, label) in amounts {
+                    ui.add_space(15.0);
+                    TextEdit::singleline(text).desired_width(50.0).ui(ui);
+                    ui.add_space(5.0);
+                    ui.label(label);
+                }
+            });
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(35.0);
+                if ui.button("Cancel").clicked() {
+                    recorder.modal = None;
+                }
+                ui.add_space(35.0);
+                if ui.button("Apply").clicked() && self.apply(&data, recorder) {
+                    recorder.modal = None;
+                }
+            });
+        });
+    }
+}
@@ -0,0 +1,137 @@
+use std::{cell::RefCell, rc::Rc};
+
+use egui::*;
+use strum::IntoEnumIterator;
+
+use crate::{
+    actions::Point,
+    gui::Recorder,
+    monitors::platform_monitors,
+    transform::{convert_points, cursor_after, PointMode},
+    window_manager::platform_window_manager,
+};
+
+use super::ModalWindow;
+
+pub struct ConvertPointsWindow {
+    data: RefCell<ConvertPointsWindowData>,
+}
+
+struct ConvertPointsWindowData {
+    mode: PointMode,
+    x_textedit_text: String,
+    y_textedit_text: String,
+}
+
+impl ConvertPointsWindow {
+    /// `start` is where the cursor was when the macro was recorded, if that's known.
+    pub fn new(start: Point) -> Rc<dyn ModalWindow> {
+        Rc::new(Self {
+            data: RefCell::new(ConvertPointsWindowData {
+                mode: PointMode::Absolute,
+                x_textedit_text: start.x.to_string(),
+                y_textedit_text: start.y.to_string(),
+            }),
+        })
+    }
+
+    fn setup(&self, _recorder: &mut Recorder, drag_bounds: Rect) -> Window {
+        Window::new("Convert Positions")
+            .collapsible(false)
+            .resizable(false)
+            .drag_bounds(drag_bounds)
+            .anchor(Align2::CENTER_CENTER, vec2(0.0, 0.0))
+    }
+}
+
+impl ModalWindow for ConvertPointsWindow {
+    fn update(
+        &self,
+        recorder: &mut Recorder,
+        ctx: &Context,
+        _ui: &mut Ui,
+        drag_bounds: Rect,
+        _frame: &mut eframe::Frame,
+    ) {
+        let window = self.setup(recorder, drag_bounds);
+
+        window.show(ctx, |ui| {
+            let mut data = self.data.borrow_mut();
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
+            ui.label(
+                "Rewrites mouse positions as screen positions, offsets from the last position,\nor positions inside the window or on the monitor that's there now.",
+            );
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                let selected = &mut data.mode;
+
+                ComboBox::new("Point Mode Combo Box", "")
+                    .selected_text(format!("{}", selected))
+                    .width(150.0)
+                    .show_ui(ui, |ui| {
+                        for mode in PointMode::iter() {
+                            let text = format!("{}", mode);
+
+                            ui.selectable_value(selected, mode, text);
+
+                            ui.allocate_space(vec2(0.0, 3.5));
+                        }
+                    });
+            });
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.label("Cursor position when the macro starts: ");
+                TextEdit::singleline(&mut data.x_textedit_text)
+                    .desired_width(50.0)
+                    .ui(ui);
+                ui.add_space(10.0);
+                TextEdit::singleline(&mut data.y_textedit_text)
+                    .desired_width(50.0)
+                    .ui(ui);
+            });
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(35.0);
+                if ui.button("Cancel").clicked() {
+                    recorder.modal = None;
+                }
+                ui.add_space(35.0);
+                if ui.button("Convert").clicked() {
+                    if let (Ok(x), Ok(y)) = (data.x_textedit_text.parse(), data.y_textedit_text.parse())
+                    {
+                        let window_manager = platform_window_manager().ok();
+                        let window_manager = window_manager.as_deref();
+                        let monitors = platform_monitors().unwrap_or_default();
+
+                        // A selection starts wherever the rows before it leave the cursor
+                        let start = Point { x, y };
+                        let start = match recorder.selected_rows() {
+                            Some(rows) if rows.start() != rows.end() => cursor_after(
+                                &recorder.action_list()[..*rows.start()],
+                                start,
+                                window_manager,
+                                &monitors,
+                            )
+                            .unwrap_or(start),
+                            _ => start,
+                        };
+
+                        let mode = data.mode;
+                        recorder.transform_selection(|actions| {
+                            convert_points(actions, mode, start, window_manager, &monitors)
+                        });
+                        recorder.modal = None;
+                    }
+                }
+            });
+        });
+    }
+}
@@ -2,11 +2,12 @@ use std::{borrow::Cow, cell::RefCell, path::PathBuf, time::SystemTime};
 
 use chrono::{DateTime, Utc};
 use egui::{vec2, Align, Align2, Layout, ScrollArea, Window};
-use winapi::um::winuser::{GetAsyncKeyState, VK_CONTROL, VK_MENU, VK_SHIFT};
 
 use crate::{
     hotkeys::{start_hotkey_detector, HotkeyMacro},
+    input::key_pressed,
     keycodes_to_string::key_code_to_string,
+    keys::Key,
 };
 
 use super::*;
@@ -67,18 +68,18 @@ impl HotkeysWindow {
         }
         recorder.settings.hotkeys = hotkey_macros;
         recorder.modal = recorder.settings.save_with_error_window();
-        recorder.hotkey_detector_sender =
-            Some(start_hotkey_detector(&mut recorder.settings.hotkeys));
+        recorder.hotkey_detector_sender = Some(start_hotkey_detector(&mut recorder.settings));
     }
 }
 
-fn get_pressed_buttons() -> Vec<i32> {
+pub fn get_pressed_buttons() -> Vec<i32> {
     (0x01..=0xFE)
         .filter(|key_code| {
-            *key_code != VK_SHIFT
-                && *key_code != VK_CONTROL
-                && *key_code != VK_MENU
-                && unsafe { GetAsyncKeyState(*key_code) < 0 }
+            // The generic modifiers are down along with their left or right key
+            !matches!(
+                Key::from_vk(*key_code),
+                Some(Key::Shift | Key::Control | Key::Alt)
+            ) && key_pressed(*key_code)
         })
         .collect()
 }
@@ -103,7 +104,7 @@ impl ModalWindow for HotkeysWindow {
                 "Once you click the button to set a hotkey, \
             you will have 1 second to press and hold all keys until \
             they are registered. \
-            Macros launched by a hotkey only play once but can be stopped with Ctrl + Q.",
+            Macros launched by a hotkey only play once but can be stopped with the stop hotkey.",
             );
 
             ui.add_space(10.0);
@@ -260,7 +261,7 @@ impl ModalWindow for HotkeysWindow {
                 if ui.button("Cancel").clicked() {
                     recorder.modal = None;
                     recorder.hotkey_detector_sender =
-                        Some(start_hotkey_detector(&mut recorder.settings.hotkeys));
+                        Some(start_hotkey_detector(&mut recorder.settings));
                 }
 
                 ui.add_space(35.0);
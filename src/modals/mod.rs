@@ -3,6 +3,8 @@ use egui::{Context, Rect, Ui};
 use crate::gui::Recorder;
 
 pub mod action_list_category;
+pub mod cleanup_window;
+pub mod convert_points_window;
 pub mod hotkeys_window;
 pub mod modify_command_window;
 pub mod settings_window;
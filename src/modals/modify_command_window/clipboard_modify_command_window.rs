@@ -0,0 +1,181 @@
+use std::cell::RefCell;
+
+use crate::{actions::Action, gui::Recorder, modals::ModalWindow};
+use eframe::egui::*;
+use regex::Regex;
+
+#[derive(Clone, Copy)]
+pub enum ClipboardWindowType {
+    Set,
+    Copy,
+    If,
+}
+
+pub struct ClipboardModifyCommandWindow {
+    data: RefCell<ClipboardModifyCommandWindowData>,
+}
+
+struct ClipboardModifyCommandWindowData {
+    window_type: ClipboardWindowType,
+    creating_command: bool,
+    position: Option<Pos2>,
+    text_edit_text: String,
+    error: Option<String>,
+    enter_lock: bool,
+}
+
+impl ClipboardModifyCommandWindow {
+    pub fn new(
+        creating_command: bool,
+        position: Pos2,
+        text: &str,
+        window_type: ClipboardWindowType,
+    ) -> Self {
+        Self {
+            data: RefCell::new(ClipboardModifyCommandWindowData {
+                window_type,
+                creating_command,
+                position: Some(position),
+                text_edit_text: text.to_string(),
+                error: None,
+                enter_lock: true,
+            }),
+        }
+    }
+
+    fn setup(&self, drag_bounds: Rect) -> Window {
+        let mut data = self.data.borrow_mut();
+
+        let mut window = Window::new(match data.window_type {
+            ClipboardWindowType::Set => "Set Clipboard",
+            ClipboardWindowType::Copy => "Copy Clipboard",
+            ClipboardWindowType::If => "If Clipboard Matches",
+        })
+        .collapsible(false)
+        .resizable(false)
+        .drag_bounds(drag_bounds);
+
+        if let Some(position) = &data.position {
+            window = window.current_pos(Pos2::new(position.x, position.y));
+            data.position = None;
+        }
+
+        window
+    }
+
+    fn save(&self, data: &mut ClipboardModifyCommandWindowData, recorder: &mut Recorder) {
+        let text = data.text_edit_text.clone();
+
+        let action = match data.window_type {
+            ClipboardWindowType::Set => Action::SetClipboard(text),
+            ClipboardWindowType::Copy => {
+                if text.is_empty() {
+                    data.error = Some("The value needs a name".into());
+                    return;
+                }
+                Action::CopyClipboard(text)
+            }
+            ClipboardWindowType::If => {
+                if let Err(error) = Regex::new(&text) {
+                    data.error = Some(error.to_string());
+                    return;
+                }
+                Action::IfClipboard(text)
+            }
+        };
+
+        recorder.modal = None;
+        let selected_row = recorder.selected_row.unwrap();
+        recorder.action_list()[selected_row] = action;
+    }
+
+    fn cancel(&self, data: &ClipboardModifyCommandWindowData, recorder: &mut Recorder) {
+        let selected_row = recorder.selected_row.unwrap();
+        recorder.modal = None;
+        if data.creating_command {
+            recorder.action_list().remove(selected_row);
+            recorder.selected_row = None;
+        }
+    }
+}
+
+impl ModalWindow for ClipboardModifyCommandWindow {
+    fn update(
+        &self,
+        recorder: &mut Recorder,
+        ctx: &Context,
+        _ui: &mut Ui,
+        drag_bounds: Rect,
+        _frame: &mut eframe::Frame,
+    ) {
+        let window = self.setup(drag_bounds);
+
+        window.show(ctx, |ui| {
+            let data = &mut self.data.borrow_mut();
+
+            if ui.input().key_down(Key::Enter) {
+                if !data.enter_lock {
+                    self.save(data, recorder);
+                }
+            } else {
+                data.enter_lock = false;
+            }
+            if ui.input().key_pressed(Key::Escape) {
+                self.cancel(data, recorder);
+            }
+
+            ui.allocate_space(vec2(0.0, 15.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(15.0);
+                ui.label(match data.window_type {
+                    ClipboardWindowType::Set => "Text: ",
+                    ClipboardWindowType::Copy => "Value name: ",
+                    ClipboardWindowType::If => "Regex: ",
+                });
+                let id = TextEdit::singleline(&mut data.text_edit_text)
+                    .desired_width(250.0)
+                    .ui(ui)
+                    .id;
+                ui.memory().request_focus(id);
+                ui.add_space(15.0);
+            });
+
+            ui.add_space(10.0);
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(15.0);
+                ui.label(match data.window_type {
+                    ClipboardWindowType::Set => {
+                        "{name} is replaced with a copied value, {output} and {exit_code} with \
+                         the last launched program's"
+                    }
+                    ClipboardWindowType::Copy => "Use {name} in Set Clipboard to paste it back",
+                    ClipboardWindowType::If => "Checks if the clipboard text matches",
+                });
+            });
+
+            if let Some(error) = &data.error {
+                ui.add_space(10.0);
+
+                ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                    ui.add_space(15.0);
+                    ui.label(RichText::new(error).color(Color32::RED));
+                });
+            }
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(35.0);
+                if ui.button("Cancel").clicked() {
+                    self.cancel(data, recorder);
+                }
+                ui.add_space(35.0);
+                if ui.button("Save").clicked() {
+                    self.save(data, recorder);
+                }
+            });
+        });
+    }
+}
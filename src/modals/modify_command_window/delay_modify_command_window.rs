@@ -1,7 +1,34 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, fmt::Display};
 
-use crate::{actions::Action, gui::Recorder, modals::ModalWindow};
+use crate::{
+    actions::{Action, DelayJitter},
+    gui::Recorder,
+    modals::ModalWindow,
+};
 use eframe::egui::*;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+#[derive(PartialEq, Eq, EnumIter, Clone, Copy)]
+enum JitterType {
+    None,
+    Uniform,
+    Normal,
+}
+
+impl Display for JitterType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::None => "No Jitter",
+                Self::Uniform => "Uniform",
+                Self::Normal => "Normal",
+            }
+        )
+    }
+}
 
 pub struct DelayModifyCommandWindow {
     data: RefCell<DelayModifyCommandWindowData>,
@@ -11,16 +38,26 @@ struct DelayModifyCommandWindowData {
     creating_command: bool,
     position: Option<Pos2>,
     text_edit_text: String,
+    jitter_type: JitterType,
+    jitter_text_edit_text: String,
     enter_lock: bool,
 }
 
 impl DelayModifyCommandWindow {
-    pub fn new(creating_command: bool, position: Pos2, delay: u32) -> Self {
+    pub fn new(creating_command: bool, position: Pos2, delay: u32, jitter: DelayJitter) -> Self {
+        let (jitter_type, jitter_amount) = match jitter {
+            DelayJitter::None => (JitterType::None, 0),
+            DelayJitter::Uniform(range) => (JitterType::Uniform, range),
+            DelayJitter::Normal(deviation) => (JitterType::Normal, deviation),
+        };
+
         Self {
             data: RefCell::new(DelayModifyCommandWindowData {
                 creating_command,
                 position: Some(position),
                 text_edit_text: delay.to_string(),
+                jitter_type,
+                jitter_text_edit_text: jitter_amount.to_string(),
                 enter_lock: true,
             }),
         }
@@ -44,9 +81,16 @@ impl DelayModifyCommandWindow {
 
     fn save(&self, data: &DelayModifyCommandWindowData, recorder: &mut Recorder) {
         let selected_row = recorder.selected_row.unwrap();
+        let jitter = match (data.jitter_type, data.jitter_text_edit_text.parse()) {
+            (JitterType::None, _) => DelayJitter::None,
+            (JitterType::Uniform, Ok(range)) => DelayJitter::Uniform(range),
+            (JitterType::Normal, Ok(deviation)) => DelayJitter::Normal(deviation),
+            _ => return,
+        };
+
         if let Ok(delay) = data.text_edit_text.parse() {
             recorder.modal = None;
-            recorder.action_list()[selected_row] = Action::Delay(delay);
+            recorder.action_list()[selected_row] = Action::Delay(delay, jitter);
         }
     }
 
@@ -99,6 +143,39 @@ impl ModalWindow for DelayModifyCommandWindow {
                 ui.add_space(35.0);
             });
 
+            ui.allocate_space(Vec2::new(0.0, 15.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                let selected = &mut data.jitter_type;
+
+                ui.add_space(35.0);
+                ComboBox::new("Jitter Combo Box", "")
+                    .selected_text(format!("{}", selected))
+                    .width(100.0)
+                    .show_ui(ui, |ui| {
+                        for jitter_type in JitterType::iter() {
+                            let text = format!("{}", jitter_type);
+
+                            ui.selectable_value(selected, jitter_type, text);
+
+                            ui.allocate_space(vec2(0.0, 3.5));
+                        }
+                    });
+
+                if data.jitter_type != JitterType::None {
+                    ui.add_space(15.0);
+                    TextEdit::singleline(&mut data.jitter_text_edit_text)
+                        .desired_width(50.0)
+                        .ui(ui);
+                    ui.add_space(5.0);
+                    ui.label(match data.jitter_type {
+                        JitterType::Normal => "ms deviation",
+                        _ => "ms either way",
+                    });
+                }
+                ui.add_space(35.0);
+            });
+
             ui.allocate_space(Vec2::new(0.0, 25.0));
 
             ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
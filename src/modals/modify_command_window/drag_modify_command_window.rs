@@ -0,0 +1,331 @@
+use std::{cell::RefCell, error::Error};
+
+use crate::{
+    actions::{Action, DragInfo, MousePointKind, Point},
+    gui::Recorder,
+    input::{cursor_position, key_pressed},
+    keycodes_to_string::key_code_to_string,
+    modals::ModalWindow,
+    monitors::platform_monitors,
+    recorder::keymap::{VK_F2, VK_F3, VK_LBUTTON, VK_MBUTTON, VK_RBUTTON},
+    window_manager::platform_window_manager,
+};
+use eframe::egui::*;
+
+const BUTTONS: [i32; 3] = [VK_LBUTTON, VK_RBUTTON, VK_MBUTTON];
+
+pub struct DragModifyCommandWindow {
+    data: RefCell<DragModifyCommandWindowData>,
+}
+
+struct DragModifyCommandWindowData {
+    creating_command: bool,
+    position: Option<Pos2>,
+    button: i32,
+    from: MousePointKind,
+    to: MousePointKind,
+    from_text_edit_text: (String, String),
+    to_text_edit_text: (String, String),
+    duration_text_edit_text: String,
+    path: Vec<Point>,
+    capture_error: Option<String>,
+    f2_previously_pressed: bool,
+    f3_previously_pressed: bool,
+    enter_lock: bool,
+}
+
+impl DragModifyCommandWindow {
+    pub fn new(creating_command: bool, position: Pos2, drag_info: &DragInfo) -> Self {
+        Self {
+            data: RefCell::new(DragModifyCommandWindowData {
+                creating_command,
+                position: Some(position),
+                button: drag_info.button,
+                from: drag_info.from.clone(),
+                to: drag_info.to.clone(),
+                from_text_edit_text: (
+                    drag_info.from.x().to_string(),
+                    drag_info.from.y().to_string(),
+                ),
+                to_text_edit_text: (drag_info.to.x().to_string(), drag_info.to.y().to_string()),
+                duration_text_edit_text: drag_info.duration.to_string(),
+                path: drag_info.path.clone(),
+                capture_error: None,
+                f2_previously_pressed: capture_start_key_pressed(),
+                f3_previously_pressed: capture_end_key_pressed(),
+                enter_lock: true,
+            }),
+        }
+    }
+
+    fn setup(&self, drag_bounds: Rect) -> Window {
+        let mut window = Window::new("Drag")
+            .collapsible(false)
+            .resizable(false)
+            .drag_bounds(drag_bounds);
+
+        let mut data = self.data.borrow_mut();
+
+        if let Some(position) = &data.position {
+            window = window.current_pos(Pos2::new(position.x, position.y));
+            data.position = None;
+        }
+
+        window
+    }
+
+    /// The mouse position in the coordinates of the point kind.
+    fn captured_point(kind: &MousePointKind) -> Result<Point, Box<dyn Error>> {
+        let point = cursor_position()?;
+
+        match kind {
+            MousePointKind::To(_) => Ok(Point {
+                x: point.x,
+                y: point.y,
+            }),
+            MousePointKind::Window(window_info, _) => {
+                let window_manager = platform_window_manager()?;
+                let window = window_manager
+                    .find_window(window_info)?
+                    .ok_or("No matching window found")?;
+                let client_rect = window_manager.client_rect(window.id)?;
+
+                Ok(Point {
+                    x: point.x - client_rect.x,
+                    y: point.y - client_rect.y,
+                })
+            }
+            MousePointKind::Monitor(monitor, _) => {
+                let monitors = platform_monitors()?;
+                let monitor = monitors
+                    .iter()
+                    .find(|connected| connected.name == monitor.name)
+                    .ok_or("The monitor isn't connected")?;
+
+                Ok(Point {
+                    x: point.x - monitor.rect.x,
+                    y: point.y - monitor.rect.y,
+                })
+            }
+            MousePointKind::By(_) => Err("Offsets can't be captured, edit them instead".into()),
+        }
+    }
+
+    fn capture(data: &mut DragModifyCommandWindowData, start: bool) {
+        let kind = if start { &data.from } else { &data.to };
+
+        match Self::captured_point(kind) {
+            Ok(point) => {
+                let texts = (point.x.to_string(), point.y.to_string());
+                if start {
+                    data.from_text_edit_text = texts;
+                } else {
+                    data.to_text_edit_text = texts;
+                }
+
+                // The recorded path doesn't lead to the new position anymore
+                data.path.clear();
+                data.capture_error = None;
+            }
+            Err(error) => data.capture_error = Some(error.to_string()),
+        }
+    }
+
+    fn save(&self, data: &DragModifyCommandWindowData, recorder: &mut Recorder) {
+        let (Ok(from_x), Ok(from_y), Ok(to_x), Ok(to_y), Ok(duration)) = (
+            data.from_text_edit_text.0.parse(),
+            data.from_text_edit_text.1.parse(),
+            data.to_text_edit_text.0.parse(),
+            data.to_text_edit_text.1.parse(),
+            data.duration_text_edit_text.parse(),
+        ) else {
+            return;
+        };
+
+        recorder.modal = None;
+        let selected_row = recorder.selected_row.unwrap();
+        recorder.action_list()[selected_row] = Action::Drag(DragInfo {
+            button: data.button,
+            from: data.from.with_point(Point {
+                x: from_x,
+                y: from_y,
+            }),
+            to: data.to.with_point(Point { x: to_x, y: to_y }),
+            duration,
+            path: data.path.clone(),
+        });
+    }
+
+    fn cancel(&self, data: &DragModifyCommandWindowData, recorder: &mut Recorder) {
+        let selected_row = recorder.selected_row.unwrap();
+        recorder.modal = None;
+        if data.creating_command {
+            recorder.action_list().remove(selected_row);
+            recorder.selected_row = None;
+        }
+    }
+}
+
+impl ModalWindow for DragModifyCommandWindow {
+    fn update(
+        &self,
+        recorder: &mut Recorder,
+        ctx: &Context,
+        _ui: &mut Ui,
+        drag_bounds: Rect,
+        _frame: &mut eframe::Frame,
+    ) {
+        let window = self.setup(drag_bounds);
+
+        window.show(ctx, |ui| {
+            let data = &mut self.data.borrow_mut();
+
+            if ui.input().key_down(Key::Enter) {
+                if !data.enter_lock {
+                    self.save(data, recorder);
+                }
+            } else {
+                data.enter_lock = false;
+            }
+            if ui.input().key_pressed(Key::Escape) {
+                self.cancel(data, recorder);
+            }
+
+            if capture_start_key_pressed() {
+                if !data.f2_previously_pressed {
+                    Self::capture(data, true);
+                    data.f2_previously_pressed = true;
+                }
+            } else {
+                data.f2_previously_pressed = false;
+            }
+
+            if capture_end_key_pressed() {
+                if !data.f3_previously_pressed {
+                    Self::capture(data, false);
+                    data.f3_previously_pressed = true;
+                }
+            } else {
+                data.f3_previously_pressed = false;
+            }
+
+            ui.allocate_space(vec2(0.0, 10.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                let selected = &mut data.button;
+
+                ui.add_space(15.0);
+                ui.label("Button: ");
+                ui.add_space(10.0);
+                ComboBox::new("Drag Button Combo Box", "")
+                    .selected_text(key_code_to_string(*selected).to_string())
+                    .width(100.0)
+                    .show_ui(ui, |ui| {
+                        for button in BUTTONS {
+                            let text = key_code_to_string(button).to_string();
+
+                            ui.selectable_value(selected, button, text);
+
+                            ui.allocate_space(vec2(0.0, 3.5));
+                        }
+                    });
+            });
+
+            let fields = &mut **data;
+            for (label, kind, texts) in [
+                ("From", &fields.from, &mut fields.from_text_edit_text),
+                ("To", &fields.to, &mut fields.to_text_edit_text),
+            ] {
+                ui.add_space(15.0);
+
+                ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                    ui.add_space(15.0);
+                    ui.label(format!("{label} X: "));
+                    TextEdit::singleline(&mut texts.0)
+                        .desired_width(50.0)
+                        .ui(ui);
+                    ui.add_space(15.0);
+                    ui.label("Y: ");
+                    TextEdit::singleline(&mut texts.1)
+                        .desired_width(50.0)
+                        .ui(ui);
+
+                    match kind {
+                        MousePointKind::By(_) => {
+                            ui.add_space(10.0);
+                            ui.label("(offset)");
+                        }
+                        MousePointKind::Window(..) => {
+                            ui.add_space(10.0);
+                            ui.label("(in window)");
+                        }
+                        MousePointKind::Monitor(..) => {
+                            ui.add_space(10.0);
+                            ui.label("(on monitor)");
+                        }
+                        MousePointKind::To(_) => {}
+                    }
+                });
+            }
+
+            ui.add_space(15.0);
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(15.0);
+                ui.label("Duration: ");
+                TextEdit::singleline(&mut data.duration_text_edit_text)
+                    .desired_width(75.0)
+                    .ui(ui);
+                ui.add_space(10.0);
+                ui.label("milliseconds");
+            });
+
+            if !data.path.is_empty() {
+                ui.add_space(15.0);
+
+                ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                    ui.add_space(15.0);
+                    ui.label(format!(
+                        "Follows a recorded path of {} points",
+                        data.path.len()
+                    ));
+                    ui.add_space(10.0);
+                    if ui.button("Straight Line").clicked() {
+                        data.path.clear();
+                    }
+                });
+            }
+
+            ui.add_space(25.0);
+            ui.label("Press F2 to capture the start position.");
+            ui.add_space(15.0);
+            ui.label("Press F3 to capture the end position.");
+
+            if let Some(error) = &data.capture_error {
+                ui.add_space(10.0);
+                ui.label(RichText::new(error).color(Color32::RED));
+            }
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(35.0);
+                if ui.button("Cancel").clicked() {
+                    self.cancel(data, recorder);
+                }
+                ui.add_space(35.0);
+                if ui.button("Save").clicked() {
+                    self.save(data, recorder);
+                }
+            });
+        });
+    }
+}
+
+fn capture_start_key_pressed() -> bool {
+    key_pressed(VK_F2)
+}
+
+fn capture_end_key_pressed() -> bool {
+    key_pressed(VK_F3)
+}
@@ -1,18 +1,18 @@
 use crate::gui::PIXELS_PER_POINT;
 use crate::images::RawScreenshot;
+use crate::modals::modify_command_window::search_anchor_editor::SearchAnchorEditor;
 use crate::modals::ModalWindow;
 use crate::{
     actions::{Action, ImageInfo},
     gui::Recorder,
     images::{
-        find_image, screenshot, screenshot_to_color_image, GrayImageSerializable,
+        find_image, screen_size, screenshot, screenshot_to_color_image, GrayImageSerializable,
         RawScreenshotPair, IMAGE_PANEL_IMAGE_SIZE,
     },
 };
 use eframe::egui::*;
 use image::{DynamicImage, EncodableLayout, ImageBuffer};
 use std::cell::RefCell;
-use winapi::um::winuser::{GetSystemMetrics, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN};
 
 const CAPTURE_COLOR: Color32 = Color32::from_rgba_premultiplied(50, 50, 50, 0);
 const INVALID_CAPTURE_COLOR: Color32 = Color32::from_rgba_premultiplied(50, 00, 00, 0);
@@ -51,6 +51,7 @@ struct ImageModifyCommandWindowData {
     full_screen_texture: Option<TextureHandle>,
     full_screen_image: Option<ImageBuffer<image::Rgba<u8>, Vec<u8>>>,
     capture_state: CapturingState,
+    search_anchor: SearchAnchorEditor,
 }
 
 impl ImageModifyCommandWindow {
@@ -96,6 +97,7 @@ impl ImageModifyCommandWindow {
                 full_screen_texture: None,
                 capture_state: CapturingState::NotCapturing,
                 full_screen_image: None,
+                search_anchor: SearchAnchorEditor::new(&image_info.search_anchor),
             }),
         }
     }
@@ -152,6 +154,7 @@ impl ImageModifyCommandWindow {
                 search_location_left_top,
                 search_location_width_height,
                 image_similarity,
+                search_anchor: data.search_anchor.anchor(),
             };
 
             let selected_row = recorder.selected_row.unwrap();
@@ -353,11 +356,13 @@ impl ImageModifyCommandWindow {
                                     TextureFilter::Linear,
                                 ));
                             }
+                            let (left, top) = data.search_anchor.relative_to_anchor(
+                                lesser(capture_start.x, capture_end.x),
+                                lesser(capture_start.y, capture_end.y),
+                            );
+
                             data.search_location_text_edit_texts = Some((
-                                (
-                                    lesser(capture_start.x, capture_end.x).to_string(),
-                                    lesser(capture_start.y, capture_end.y).to_string(),
-                                ),
+                                (left.to_string(), top.to_string()),
                                 (
                                     (capture_start.x - capture_end.x).abs().to_string(),
                                     (capture_start.y - capture_end.y).abs().to_string(),
@@ -379,13 +384,8 @@ impl ImageModifyCommandWindow {
 impl ImageModifyCommandWindow {
     fn screenshot_this_frame(&self, ctx: &Context, frame: &mut eframe::Frame) {
         let mut data = self.data.borrow_mut();
-        let (corner1, corner2) = unsafe {
-            (
-                GetSystemMetrics(SM_CXVIRTUALSCREEN),
-                GetSystemMetrics(SM_CYVIRTUALSCREEN),
-            )
-        };
-        let (corner1, corner2) = (pos2(0.0, 0.0), pos2(corner1 as f32, corner2 as f32));
+        let (width, height) = screen_size();
+        let (corner1, corner2) = (pos2(0.0, 0.0), pos2(width as f32, height as f32));
 
         let screenshot = screenshot(corner1, corner2);
 
@@ -562,12 +562,20 @@ impl ImageModifyCommandWindow {
                     ui.allocate_space(vec2(0.0, 15.0));
                 }
 
+                data.search_anchor.ui(ui);
+
+                ui.allocate_space(vec2(0.0, 15.0));
+
                 if ui.button("Check if image is found").clicked() {
                     if let Some(text) = &data.search_location_text_edit_texts {
-                        let start = match (text.0 .0.parse(), text.0 .1.parse()) {
-                            (Ok(x), Ok(y)) => Some(pos2(x, y)),
-                            _ => None,
-                        };
+                        let origin = data.search_anchor.origin();
+                        let start =
+                            match (text.0 .0.parse::<f32>(), text.0 .1.parse::<f32>(), origin) {
+                                (Ok(x), Ok(y), Some(origin)) => {
+                                    Some(pos2(x + origin.0, y + origin.1))
+                                }
+                                _ => None,
+                            };
                         let width_height = match (text.1 .0.parse(), text.1 .1.parse()) {
                             (Ok(x), Ok(y)) => Some(pos2(x, y)),
                             _ => None,
@@ -1,9 +1,10 @@
 use std::cell::RefCell;
 
 use crate::{
-    actions::{Action, KeyState},
+    actions::{Action, KeyState, ScanCode},
     gui::Recorder,
-    keycodes_to_string::{key_code_to_string, ALLOWED_KEYBOARD_KEYS},
+    keycodes_to_string::key_code_to_string,
+    keys::Key as KeyName,
     modals::ModalWindow,
 };
 use eframe::egui::*;
@@ -18,11 +19,19 @@ struct KeyboardModifyCommandWindowData {
     key_state: KeyState,
     key_code: Option<i32>,
     key_code_text_edit_text: String,
+    /// The recorded key and its scan code, which only stays if the key does.
+    recorded: (i32, Option<ScanCode>),
     enter_lock: bool,
 }
 
 impl KeyboardModifyCommandWindow {
-    pub fn new(creating_command: bool, position: Pos2, key_code: i32, key_state: KeyState) -> Self {
+    pub fn new(
+        creating_command: bool,
+        position: Pos2,
+        key_code: i32,
+        key_state: KeyState,
+        scan_code: Option<ScanCode>,
+    ) -> Self {
         Self {
             data: RefCell::new(KeyboardModifyCommandWindowData {
                 creating_command,
@@ -30,6 +39,7 @@ impl KeyboardModifyCommandWindow {
                 key_state,
                 key_code: Some(key_code),
                 key_code_text_edit_text: key_code.to_string(),
+                recorded: (key_code, scan_code),
                 enter_lock: true,
             }),
         }
@@ -119,12 +129,13 @@ impl ModalWindow for KeyboardModifyCommandWindow {
                     })
                     .width(140.0)
                     .show_ui(ui, |ui| {
-                        for key_code in ALLOWED_KEYBOARD_KEYS.iter() {
+                        for key in KeyName::all() {
+                            let key_code = key.to_vk();
                             if ui
                                 .selectable_value(
                                     &mut data.key_code,
-                                    Some(*key_code),
-                                    key_code_to_string(*key_code),
+                                    Some(key_code),
+                                    key.to_string(),
                                 )
                                 .clicked()
                             {
@@ -145,7 +156,13 @@ impl ModalWindow for KeyboardModifyCommandWindow {
                     .desired_width(50.0)
                     .ui(ui);
 
-                data.key_code = data.key_code_text_edit_text.parse().ok();
+                // Takes key names as well as codes, like "Ctrl" or "F5"
+                let text = &data.key_code_text_edit_text;
+                let key_code = text
+                    .parse()
+                    .ok()
+                    .or_else(|| KeyName::from_name(text).map(KeyName::to_vk));
+                data.key_code = key_code;
             });
 
             ui.allocate_space(Vec2::new(0.0, 25.0));
@@ -174,7 +191,12 @@ impl KeyboardModifyCommandWindow {
         let selected_row = recorder.selected_row.unwrap();
         if let Some(key_code) = data.key_code {
             recorder.modal = None;
-            recorder.action_list()[selected_row] = Action::Keyboard(key_code, data.key_state);
+            let (recorded_key_code, scan_code) = data.recorded;
+            recorder.action_list()[selected_row] = Action::Keyboard(
+                key_code,
+                data.key_state,
+                scan_code.filter(|_| key_code == recorded_key_code),
+            );
         }
     }
 }
@@ -0,0 +1,294 @@
+use std::{cell::RefCell, path::PathBuf};
+
+use crate::{
+    actions::{Action, LaunchInfo},
+    gui::Recorder,
+    modals::ModalWindow,
+};
+use eframe::egui::*;
+
+pub struct LaunchModifyCommandWindow {
+    data: RefCell<LaunchModifyCommandWindowData>,
+}
+
+struct LaunchModifyCommandWindowData {
+    creating_command: bool,
+    position: Option<Pos2>,
+    program_text_edit_text: String,
+    args_text_edit_text: String,
+    working_dir_text_edit_text: String,
+    wait_for_exit: bool,
+    timeout_text_edit_text: String,
+    enter_lock: bool,
+}
+
+impl LaunchModifyCommandWindow {
+    pub fn new(creating_command: bool, position: Pos2, launch_info: &LaunchInfo) -> Self {
+        Self {
+            data: RefCell::new(LaunchModifyCommandWindowData {
+                creating_command,
+                position: Some(position),
+                program_text_edit_text: launch_info.program.to_string_lossy().into(),
+                args_text_edit_text: join_args(&launch_info.args),
+                working_dir_text_edit_text: launch_info
+                    .working_dir
+                    .as_ref()
+                    .map_or(String::new(), |path| path.to_string_lossy().into()),
+                wait_for_exit: launch_info.wait_for_exit,
+                timeout_text_edit_text: launch_info
+                    .timeout
+                    .map_or(String::new(), |timeout| timeout.to_string()),
+                enter_lock: true,
+            }),
+        }
+    }
+
+    fn setup(&self, drag_bounds: Rect) -> Window {
+        let mut window = Window::new("Launch")
+            .collapsible(false)
+            .resizable(false)
+            .drag_bounds(drag_bounds);
+
+        let mut data = self.data.borrow_mut();
+
+        if let Some(position) = data.position {
+            window = window.current_pos(Pos2::new(position.x, position.y));
+            data.position = None;
+        }
+
+        window
+    }
+
+    fn save(&self, data: &LaunchModifyCommandWindowData, recorder: &mut Recorder) {
+        if data.program_text_edit_text.trim().is_empty() {
+            return;
+        }
+
+        let timeout = if data.timeout_text_edit_text.trim().is_empty() {
+            None
+        } else if let Ok(timeout) = data.timeout_text_edit_text.trim().parse() {
+            Some(timeout)
+        } else {
+            return;
+        };
+
+        let working_dir = if data.working_dir_text_edit_text.trim().is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(data.working_dir_text_edit_text.trim()))
+        };
+
+        recorder.modal = None;
+        let selected_row = recorder.selected_row.unwrap();
+        recorder.action_list()[selected_row] = Action::Launch(LaunchInfo {
+            program: PathBuf::from(data.program_text_edit_text.trim()),
+            args: split_args(&data.args_text_edit_text),
+            working_dir,
+            wait_for_exit: data.wait_for_exit,
+            timeout,
+        });
+    }
+
+    fn cancel(&self, data: &LaunchModifyCommandWindowData, recorder: &mut Recorder) {
+        let selected_row = recorder.selected_row.unwrap();
+        recorder.modal = None;
+        if data.creating_command {
+            recorder.action_list().remove(selected_row);
+            recorder.selected_row = None;
+        }
+    }
+}
+
+impl ModalWindow for LaunchModifyCommandWindow {
+    fn update(
+        &self,
+        recorder: &mut Recorder,
+        ctx: &Context,
+        _ui: &mut Ui,
+        drag_bounds: Rect,
+        _frame: &mut eframe::Frame,
+    ) {
+        let window = self.setup(drag_bounds);
+
+        window.show(ctx, |ui| {
+            let data = &mut self.data.borrow_mut();
+
+            if ui.input().key_down(Key::Enter) {
+                if !data.enter_lock {
+                    self.save(data, recorder);
+                }
+            } else {
+                data.enter_lock = false;
+            }
+            if ui.input().key_pressed(Key::Escape) {
+                self.cancel(data, recorder);
+            }
+
+            ui.allocate_space(Vec2::new(0.0, 15.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(15.0);
+                ui.label("Program: ");
+                TextEdit::singleline(&mut data.program_text_edit_text)
+                    .desired_width(250.0)
+                    .ui(ui);
+                ui.add_space(10.0);
+                if ui.button("Browse").clicked() {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Programs", &["exe", "bat", "cmd"])
+                        .add_filter("All files", &["*"])
+                        .pick_file()
+                    {
+                        data.program_text_edit_text = path.to_string_lossy().into();
+                    }
+                }
+                ui.add_space(15.0);
+            });
+
+            ui.allocate_space(Vec2::new(0.0, 15.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(15.0);
+                ui.label("Arguments: ");
+                TextEdit::singleline(&mut data.args_text_edit_text)
+                    .desired_width(250.0)
+                    .ui(ui);
+            })
+            .response
+            .on_hover_text("Separated by spaces. Use quotes for arguments containing spaces.");
+
+            ui.allocate_space(Vec2::new(0.0, 15.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(15.0);
+                ui.label("Working directory: ");
+                TextEdit::singleline(&mut data.working_dir_text_edit_text)
+                    .desired_width(200.0)
+                    .ui(ui);
+                ui.add_space(10.0);
+                if ui.button("Browse").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                        data.working_dir_text_edit_text = path.to_string_lossy().into();
+                    }
+                }
+                ui.add_space(15.0);
+            });
+
+            ui.allocate_space(Vec2::new(0.0, 15.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(45.0);
+                ui.checkbox(&mut data.wait_for_exit, "");
+                ui.label("Wait for the program to exit").on_hover_text(
+                    "Set Clipboard can paste the program's output and exit code with {output} and \
+                     {exit_code}.",
+                );
+            });
+
+            if data.wait_for_exit {
+                ui.allocate_space(Vec2::new(0.0, 15.0));
+
+                ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                    ui.add_space(45.0);
+                    ui.label("Timeout: ");
+                    TextEdit::singleline(&mut data.timeout_text_edit_text)
+                        .desired_width(75.0)
+                        .ui(ui);
+                    ui.add_space(10.0);
+                    ui.label("milliseconds (empty for none)");
+                });
+            }
+
+            ui.allocate_space(Vec2::new(0.0, 25.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(35.0);
+                if ui.button("Cancel").clicked() {
+                    self.cancel(data, recorder);
+                }
+                ui.add_space(35.0);
+                if ui.button("Save").clicked() {
+                    self.save(data, recorder);
+                }
+            });
+        });
+    }
+}
+
+fn split_args(text: &str) -> Vec<String> {
+    let mut args = vec![];
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_arg = false;
+
+    for character in text.chars() {
+        match character {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_arg = true;
+            }
+            ' ' | '\t' if !in_quotes => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            _ => {
+                current.push(character);
+                has_arg = true;
+            }
+        }
+    }
+
+    if has_arg {
+        args.push(current);
+    }
+
+    args
+}
+
+fn join_args(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            if arg.is_empty() || arg.contains(' ') || arg.contains('\t') {
+                format!("\"{}\"", arg)
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_quoted_arguments() {
+        assert_eq!(
+            split_args(r#"--name "Jane Doe" -v"#),
+            ["--name", "Jane Doe", "-v"]
+        );
+        assert_eq!(
+            split_args(r#"--out="C:\My Files\log.txt""#),
+            [r"--out=C:\My Files\log.txt"]
+        );
+        assert_eq!(split_args(r#"a "" b"#), ["a", "", "b"]);
+        assert_eq!(split_args("  one \t two  "), ["one", "two"]);
+        assert!(split_args("").is_empty());
+    }
+
+    #[test]
+    fn joined_arguments_split_back() {
+        let args = [
+            "plain".to_string(),
+            "with space".to_string(),
+            "with\ttab".to_string(),
+            String::new(),
+        ];
+
+        assert_eq!(join_args(&args), "plain \"with space\" \"with\ttab\" \"\"");
+        assert_eq!(split_args(&join_args(&args)), args);
+    }
+}
@@ -1,10 +1,15 @@
+pub mod clipboard_modify_command_window;
 pub mod delay_modify_command_window;
+pub mod drag_modify_command_window;
 pub mod image_modify_command_window;
 pub mod keyboard_command_window;
+pub mod launch_modify_command_window;
 pub mod mouse_modify_command_window;
 pub mod pixel_modify_command_window;
 pub mod play_modify_command_window;
 pub mod repeat_modify_command_window;
+pub mod search_anchor_editor;
+pub mod window_modify_command_window;
 
 use std::rc::Rc;
 
@@ -12,13 +17,17 @@ use crate::actions::Action;
 use crate::modals::ModalWindow;
 use eframe::egui::*;
 
+use self::clipboard_modify_command_window::{ClipboardModifyCommandWindow, ClipboardWindowType};
 use self::delay_modify_command_window::DelayModifyCommandWindow;
+use self::drag_modify_command_window::DragModifyCommandWindow;
 use self::image_modify_command_window::{ImageModifyCommandWindow, ImageWindowType};
 use self::keyboard_command_window::KeyboardModifyCommandWindow;
+use self::launch_modify_command_window::LaunchModifyCommandWindow;
 use self::mouse_modify_command_window::MouseModifyCommandWindow;
 use self::pixel_modify_command_window::{PixelModifyCommandWindow, PixelWindowType};
 use self::play_modify_command_window::PlayModifyCommandWindow;
 use self::repeat_modify_command_window::RepeatModifyCommandWindow;
+use self::window_modify_command_window::{WindowModifyCommandWindow, WindowWindowType};
 
 impl Action {
     pub fn get_modify_command_window(
@@ -33,17 +42,21 @@ impl Action {
                 position,
                 mouse_action_kind,
             ))),
-            Self::Delay(delay) => Some(Rc::new(DelayModifyCommandWindow::new(
+            Self::Delay(delay, jitter) => Some(Rc::new(DelayModifyCommandWindow::new(
                 creating_command,
                 position,
                 *delay,
+                *jitter,
             ))),
-            Self::Keyboard(key, key_state) => Some(Rc::new(KeyboardModifyCommandWindow::new(
-                creating_command,
-                position,
-                *key,
-                *key_state,
-            ))),
+            Self::Keyboard(key, key_state, scan_code) => {
+                Some(Rc::new(KeyboardModifyCommandWindow::new(
+                    creating_command,
+                    position,
+                    *key,
+                    *key_state,
+                    *scan_code,
+                )))
+            }
             Self::WaitForImage(image_info) => Some(Rc::new(ImageModifyCommandWindow::new(
                 image_info,
                 creating_command,
@@ -80,8 +93,58 @@ impl Action {
                 position,
                 path,
             ))),
+            Self::Launch(launch_info) => Some(Rc::new(LaunchModifyCommandWindow::new(
+                creating_command,
+                position,
+                launch_info,
+            ))),
+            Self::WaitForWindow(window_info) => Some(Rc::new(WindowModifyCommandWindow::new(
+                creating_command,
+                position,
+                window_info,
+                WindowWindowType::Wait,
+            ))),
+            Self::ActivateWindow(window_info) => Some(Rc::new(WindowModifyCommandWindow::new(
+                creating_command,
+                position,
+                window_info,
+                WindowWindowType::Activate,
+            ))),
+            Self::MoveWindow(window_info, rect) => Some(Rc::new(WindowModifyCommandWindow::new(
+                creating_command,
+                position,
+                window_info,
+                WindowWindowType::Move(*rect),
+            ))),
+            Self::SetClipboard(text) => Some(Rc::new(ClipboardModifyCommandWindow::new(
+                creating_command,
+                position,
+                text,
+                ClipboardWindowType::Set,
+            ))),
+            Self::CopyClipboard(name) => Some(Rc::new(ClipboardModifyCommandWindow::new(
+                creating_command,
+                position,
+                name,
+                ClipboardWindowType::Copy,
+            ))),
+            Self::IfClipboard(regex) => Some(Rc::new(ClipboardModifyCommandWindow::new(
+                creating_command,
+                position,
+                regex,
+                ClipboardWindowType::If,
+            ))),
+            Self::Drag(drag_info) => Some(Rc::new(DragModifyCommandWindow::new(
+                creating_command,
+                position,
+                drag_info,
+            ))),
 
-            Self::Else | Self::EndIf | Self::EndRepeat | Self::Break => None,
+            Self::Else
+            | Self::EndIf
+            | Self::EndRepeat
+            | Self::Break
+            | Self::WaitForClipboardChange => None,
         }
     }
 }
@@ -2,17 +2,20 @@ use std::cell::RefCell;
 
 use crate::{
     actions::{
-        self, Action, MouseActionButton, MouseActionButtonState, MouseActionKind, MousePointKind,
-        Point,
+        self, Action, MonitorInfo, MouseActionButton, MouseActionButtonState, MouseActionKind,
+        MousePointKind, Point, WindowInfo,
     },
     gui::Recorder,
+    input::{cursor_position, key_pressed},
     modals::ModalWindow,
+    monitors::{monitor_at, platform_monitors},
+    recorder::keymap::*,
+    window_manager::{exact_window_info, platform_window_manager},
 };
 use eframe::egui::*;
 use std::fmt::*;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
-use winapi::um::winuser::*;
 
 pub struct MouseModifyCommandWindow {
     data: RefCell<MouseModifyCommandWindowData>,
@@ -29,7 +32,11 @@ struct MouseModifyCommandWindowData {
     f3_previously_pressed: bool,
     window_visible: bool,
     enter_lock: bool,
-    offset_mouse: bool,
+    position_type: MousePositionType,
+    window_title_text_edit_text: String,
+    window_class_text_edit_text: String,
+    monitor: Option<MonitorInfo>,
+    capture_error: Option<String>,
 }
 
 impl MouseModifyCommandWindow {
@@ -39,29 +46,44 @@ impl MouseModifyCommandWindow {
         mouse_action_kind: &MouseActionKind,
     ) -> Self {
         let mouse_position = match mouse_action_kind {
-            MouseActionKind::Button(button) => button.point,
-            MouseActionKind::Moved(point) => Some(*point),
-            MouseActionKind::Wheel(_, point) => *point,
+            MouseActionKind::Button(button) => button.point.clone(),
+            MouseActionKind::Moved(point) => Some(point.clone()),
+            MouseActionKind::Wheel(_, point) | MouseActionKind::HorizontalWheel(_, point) => {
+                point.clone()
+            }
+        };
+
+        let position_type = match mouse_position {
+            Some(MousePointKind::By(..)) => MousePositionType::Offset,
+            Some(MousePointKind::Window(..)) => MousePositionType::Window,
+            Some(MousePointKind::Monitor(..)) => MousePositionType::Monitor,
+            _ => MousePositionType::Absolute,
+        };
+
+        let window_info = match &mouse_position {
+            Some(MousePointKind::Window(window_info, _)) => window_info.clone(),
+            _ => Default::default(),
         };
 
-        let offset_mouse = match mouse_action_kind {
-            MouseActionKind::Button(button) => matches!(button.point, Some(MousePointKind::By(..))),
-            MouseActionKind::Moved(point) => matches!(*point, MousePointKind::By(..)),
-            MouseActionKind::Wheel(_, point) => matches!(*point, Some(MousePointKind::By(..))),
+        // New monitor positions start out on the primary monitor
+        let monitor = match &mouse_position {
+            Some(MousePointKind::Monitor(monitor, _)) => Some(monitor.clone()),
+            _ => platform_monitors()
+                .ok()
+                .and_then(|monitors| monitors.into_iter().find(|monitor| monitor.primary)),
         };
 
-        let mouse_position_text_edit_text = if let Some(position) = mouse_position {
+        let mouse_position_text_edit_text = if let Some(position) = &mouse_position {
             (position.x().to_string(), position.y().to_string())
         } else {
             (String::new(), String::new())
         };
 
-        let scroll_text_edit_text =
-            if let actions::MouseActionKind::Wheel(amount, ..) = mouse_action_kind {
-                (amount / 120).to_string()
-            } else {
-                String::new()
-            };
+        let scroll_text_edit_text = match mouse_action_kind {
+            actions::MouseActionKind::Wheel(amount, ..)
+            | actions::MouseActionKind::HorizontalWheel(amount, ..) => (amount / 120).to_string(),
+            _ => String::new(),
+        };
 
         Self {
             data: RefCell::new(MouseModifyCommandWindowData {
@@ -75,7 +97,11 @@ impl MouseModifyCommandWindow {
                 f3_previously_pressed: minimize_window_key_pressed(),
                 window_visible: true,
                 enter_lock: true,
-                offset_mouse,
+                position_type,
+                window_title_text_edit_text: window_info.title,
+                window_class_text_edit_text: window_info.class,
+                monitor,
+                capture_error: None,
             }),
         }
     }
@@ -109,7 +135,37 @@ enum MouseComboBoxType {
     RightUp,
     MiddleDown,
     MiddleUp,
+    X1Click,
+    X1Down,
+    X1Up,
+    X2Click,
+    X2Down,
+    X2Up,
     Wheel,
+    HorizontalWheel,
+}
+
+#[derive(Debug, PartialEq, Eq, EnumIter, Clone, Copy)]
+enum MousePositionType {
+    Absolute,
+    Offset,
+    Window,
+    Monitor,
+}
+
+impl Display for MousePositionType {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Absolute => "Screen Position",
+                Self::Offset => "Offset",
+                Self::Window => "Window Position",
+                Self::Monitor => "Monitor Position",
+            }
+        )
+    }
 }
 
 impl From<&MouseActionKind> for MouseComboBoxType {
@@ -117,6 +173,7 @@ impl From<&MouseActionKind> for MouseComboBoxType {
         match item {
             MouseActionKind::Moved(_) => Self::Move,
             MouseActionKind::Wheel(_, _) => Self::Wheel,
+            MouseActionKind::HorizontalWheel(_, _) => Self::HorizontalWheel,
             MouseActionKind::Button(button) => match button.button {
                 VK_LBUTTON => match button.state {
                     MouseActionButtonState::Pressed => Self::LeftDown,
@@ -136,7 +193,18 @@ impl From<&MouseActionKind> for MouseComboBoxType {
                     MouseActionButtonState::Clicked => Self::MiddleClick,
                     _ => panic!(),
                 },
-                _ => panic!(),
+                VK_XBUTTON1 => match button.state {
+                    MouseActionButtonState::Pressed => Self::X1Down,
+                    MouseActionButtonState::Released => Self::X1Up,
+                    MouseActionButtonState::Clicked => Self::X1Click,
+                },
+                VK_XBUTTON2 => match button.state {
+                    MouseActionButtonState::Pressed => Self::X2Down,
+                    MouseActionButtonState::Released => Self::X2Up,
+                    MouseActionButtonState::Clicked => Self::X2Click,
+                },
+                // Older recordings stored unknown window messages as the button
+                _ => Self::LeftClick,
             },
         }
     }
@@ -158,7 +226,14 @@ impl Display for MouseComboBoxType {
                 Self::RightUp => "Right Button Up",
                 Self::MiddleDown => "Middle Button Down",
                 Self::MiddleUp => "Middle Button Up",
+                Self::X1Click => "Back (X1) Click",
+                Self::X1Down => "Back (X1) Button Down",
+                Self::X1Up => "Back (X1) Button Up",
+                Self::X2Click => "Forward (X2) Click",
+                Self::X2Down => "Forward (X2) Button Down",
+                Self::X2Up => "Forward (X2) Button Up",
                 Self::Wheel => "Wheel",
+                Self::HorizontalWheel => "Horizontal Wheel",
             }
         )
     }
@@ -191,12 +266,16 @@ impl ModalWindow for MouseModifyCommandWindow {
 
             if capture_mouse_position_key_pressed() {
                 if !data.f2_previously_pressed {
-                    let mut point = unsafe { std::mem::zeroed() };
-                    unsafe {
-                        GetCursorPos(&mut point);
-                    };
+                    let point = cursor_position().unwrap_or(Point { x: 0, y: 0 });
 
-                    data.mouse_position_text_edit_text = (point.x.to_string(), point.y.to_string());
+                    if data.position_type == MousePositionType::Window {
+                        Self::capture_window_position(data, point.x, point.y);
+                    } else if data.position_type == MousePositionType::Monitor {
+                        Self::capture_monitor_position(data, point.x, point.y);
+                    } else {
+                        data.mouse_position_text_edit_text =
+                            (point.x.to_string(), point.y.to_string());
+                    }
                     data.window_visible = true;
                     frame.set_visible(true);
                     data.f2_previously_pressed = true;
@@ -260,10 +339,69 @@ impl ModalWindow for MouseModifyCommandWindow {
                 ui.allocate_space(vec2(0.0, 25.0));
 
                 ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
-                    ui.label("Offset mouse position instead ");
-                    ui.checkbox(&mut data.offset_mouse, "");
+                    let selected = &mut data.position_type;
+
+                    ui.add_space(15.0);
+                    ui.label("Position Type: ");
+                    ui.add_space(10.0);
+                    ComboBox::new("Mouse Position Type Combo Box", "")
+                        .selected_text(format!("{}", selected))
+                        .width(150.0)
+                        .show_ui(ui, |ui| {
+                            for position_type in MousePositionType::iter() {
+                                let text = format!("{}", position_type);
+
+                                ui.selectable_value(selected, position_type, text);
+
+                                ui.allocate_space(vec2(0.0, 3.5));
+                            }
+                        });
                 });
 
+                if data.position_type == MousePositionType::Window {
+                    ui.add_space(10.0);
+
+                    ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                        ui.add_space(15.0);
+                        ui.label("Window Title: ");
+                        TextEdit::singleline(&mut data.window_title_text_edit_text)
+                            .desired_width(180.0)
+                            .ui(ui);
+                    });
+
+                    ui.add_space(10.0);
+
+                    ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                        ui.add_space(15.0);
+                        ui.label("Window Class: ");
+                        TextEdit::singleline(&mut data.window_class_text_edit_text)
+                            .desired_width(180.0)
+                            .ui(ui);
+                    });
+
+                    if let Some(error) = &data.capture_error {
+                        ui.add_space(10.0);
+                        ui.label(RichText::new(error).color(Color32::RED));
+                    }
+                }
+
+                if data.position_type == MousePositionType::Monitor {
+                    ui.add_space(10.0);
+
+                    ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                        ui.add_space(15.0);
+                        ui.label(match &data.monitor {
+                            Some(monitor) => format!("Monitor: {}", monitor.describe()),
+                            None => "Capture a position to pick the monitor".into(),
+                        });
+                    });
+
+                    if let Some(error) = &data.capture_error {
+                        ui.add_space(10.0);
+                        ui.label(RichText::new(error).color(Color32::RED));
+                    }
+                }
+
                 ui.add_space(10.0);
 
                 ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
@@ -287,10 +425,7 @@ impl ModalWindow for MouseModifyCommandWindow {
                 ui.allocate_space(vec2(0.0, 25.0));
 
                 ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
-                    let mut point = unsafe { std::mem::zeroed() };
-                    unsafe {
-                        GetCursorPos(&mut point);
-                    };
+                    let point = cursor_position().unwrap_or(Point { x: 0, y: 0 });
 
                     ui.add_space(15.0);
 
@@ -306,13 +441,19 @@ impl ModalWindow for MouseModifyCommandWindow {
                 ui.label("Press F3 to hide the window.");
             }
 
-            if data.combo_box_type == MouseComboBoxType::Wheel {
+            if data.combo_box_type == MouseComboBoxType::Wheel
+                || data.combo_box_type == MouseComboBoxType::HorizontalWheel
+            {
                 ui.allocate_space(vec2(0.0, 25.0));
 
                 ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
                     ui.add_space(55.0);
 
-                    ui.label("Scroll wheel: ");
+                    ui.label(if data.combo_box_type == MouseComboBoxType::Wheel {
+                        "Scroll wheel: "
+                    } else {
+                        "Scroll wheel (positive is right): "
+                    });
 
                     TextEdit::singleline(&mut data.scroll_text_edit_text)
                         .desired_width(25.0)
@@ -340,45 +481,33 @@ impl MouseModifyCommandWindow {
     fn save(&self, data: &MouseModifyCommandWindowData, recorder: &mut Recorder) {
         let selected_row = recorder.selected_row.unwrap();
         match data.combo_box_type {
-            MouseComboBoxType::Wheel => {
+            MouseComboBoxType::Wheel | MouseComboBoxType::HorizontalWheel => {
+                let wheel = if data.combo_box_type == MouseComboBoxType::Wheel {
+                    MouseActionKind::Wheel
+                } else {
+                    MouseActionKind::HorizontalWheel
+                };
+
                 if let Ok(mut scroll) = data.scroll_text_edit_text.parse() {
                     scroll *= 120;
 
                     if data.mouse_position_checkbox_state {
-                        if let (Ok(x), Ok(y)) = (
-                            data.mouse_position_text_edit_text.0.parse(),
-                            data.mouse_position_text_edit_text.1.parse(),
-                        ) {
+                        if let Some(point) = Self::point_kind(data) {
                             recorder.modal = None;
                             recorder.action_list()[selected_row] =
-                                Action::Mouse(MouseActionKind::Wheel(
-                                    scroll,
-                                    Some(if data.offset_mouse {
-                                        MousePointKind::By(Point { x, y })
-                                    } else {
-                                        MousePointKind::To(Point { x, y })
-                                    }),
-                                ));
+                                Action::Mouse(wheel(scroll, Some(point)));
                         }
                     } else {
                         recorder.modal = None;
-                        recorder.action_list()[selected_row] =
-                            Action::Mouse(MouseActionKind::Wheel(scroll, None));
+                        recorder.action_list()[selected_row] = Action::Mouse(wheel(scroll, None));
                     }
                 }
             }
             MouseComboBoxType::Move => {
-                if let (Ok(x), Ok(y)) = (
-                    data.mouse_position_text_edit_text.0.parse(),
-                    data.mouse_position_text_edit_text.1.parse(),
-                ) {
+                if let Some(point) = Self::point_kind(data) {
                     recorder.modal = None;
                     recorder.action_list()[selected_row] =
-                        Action::Mouse(MouseActionKind::Moved(if data.offset_mouse {
-                            MousePointKind::By(Point { x, y })
-                        } else {
-                            MousePointKind::To(Point { x, y })
-                        }));
+                        Action::Mouse(MouseActionKind::Moved(point));
                 }
             }
             _ => {
@@ -392,35 +521,40 @@ impl MouseModifyCommandWindow {
                     MouseComboBoxType::MiddleClick
                     | MouseComboBoxType::MiddleDown
                     | MouseComboBoxType::MiddleUp => VK_MBUTTON,
+                    MouseComboBoxType::X1Click
+                    | MouseComboBoxType::X1Down
+                    | MouseComboBoxType::X1Up => VK_XBUTTON1,
+                    MouseComboBoxType::X2Click
+                    | MouseComboBoxType::X2Down
+                    | MouseComboBoxType::X2Up => VK_XBUTTON2,
                     _ => unreachable!(),
                 };
 
                 let state = match data.combo_box_type {
                     MouseComboBoxType::LeftClick
                     | MouseComboBoxType::RightClick
-                    | MouseComboBoxType::MiddleClick => MouseActionButtonState::Clicked,
+                    | MouseComboBoxType::MiddleClick
+                    | MouseComboBoxType::X1Click
+                    | MouseComboBoxType::X2Click => MouseActionButtonState::Clicked,
                     MouseComboBoxType::LeftDown
                     | MouseComboBoxType::RightDown
-                    | MouseComboBoxType::MiddleDown => MouseActionButtonState::Pressed,
+                    | MouseComboBoxType::MiddleDown
+                    | MouseComboBoxType::X1Down
+                    | MouseComboBoxType::X2Down => MouseActionButtonState::Pressed,
                     MouseComboBoxType::LeftUp
                     | MouseComboBoxType::RightUp
-                    | MouseComboBoxType::MiddleUp => MouseActionButtonState::Released,
+                    | MouseComboBoxType::MiddleUp
+                    | MouseComboBoxType::X1Up
+                    | MouseComboBoxType::X2Up => MouseActionButtonState::Released,
                     _ => unreachable!(),
                 };
 
                 if data.mouse_position_checkbox_state {
-                    if let (Ok(x), Ok(y)) = (
-                        data.mouse_position_text_edit_text.0.parse(),
-                        data.mouse_position_text_edit_text.1.parse(),
-                    ) {
+                    if let Some(point) = Self::point_kind(data) {
                         recorder.modal = None;
                         recorder.action_list()[selected_row] =
                             Action::Mouse(MouseActionKind::Button(MouseActionButton {
-                                point: Some(if data.offset_mouse {
-                                    MousePointKind::By(Point { x, y })
-                                } else {
-                                    MousePointKind::To(Point { x, y })
-                                }),
+                                point: Some(point),
                                 button,
                                 state,
                             }));
@@ -438,6 +572,74 @@ impl MouseModifyCommandWindow {
         }
     }
 
+    /// The entered position, None if it doesn't parse or there's no monitor for it.
+    fn point_kind(data: &MouseModifyCommandWindowData) -> Option<MousePointKind> {
+        let (Ok(x), Ok(y)) = (
+            data.mouse_position_text_edit_text.0.parse(),
+            data.mouse_position_text_edit_text.1.parse(),
+        ) else {
+            return None;
+        };
+        let point = Point { x, y };
+
+        Some(match data.position_type {
+            MousePositionType::Absolute => MousePointKind::To(point),
+            MousePositionType::Offset => MousePointKind::By(point),
+            MousePositionType::Window => MousePointKind::Window(
+                WindowInfo {
+                    title: data.window_title_text_edit_text.clone(),
+                    class: data.window_class_text_edit_text.clone(),
+                },
+                point,
+            ),
+            MousePositionType::Monitor => MousePointKind::Monitor(data.monitor.clone()?, point),
+        })
+    }
+
+    fn capture_monitor_position(data: &mut MouseModifyCommandWindowData, x: i32, y: i32) {
+        let result = platform_monitors().and_then(|monitors| {
+            monitor_at(&monitors, Point { x, y })
+                .cloned()
+                .ok_or_else(|| "No monitor under the mouse".into())
+        });
+
+        match result {
+            Ok(monitor) => {
+                data.mouse_position_text_edit_text = (
+                    (x - monitor.rect.x).to_string(),
+                    (y - monitor.rect.y).to_string(),
+                );
+                data.monitor = Some(monitor);
+                data.capture_error = None;
+            }
+            Err(error) => data.capture_error = Some(error.to_string()),
+        }
+    }
+
+    fn capture_window_position(data: &mut MouseModifyCommandWindowData, x: i32, y: i32) {
+        let result = platform_window_manager().and_then(|window_manager| {
+            let window = window_manager
+                .window_at(x, y)
+                .ok_or("No window under the mouse")?;
+            let client_rect = window_manager.client_rect(window.id)?;
+            Ok((window, client_rect))
+        });
+
+        match result {
+            Ok((window, client_rect)) => {
+                let window_info = exact_window_info(&window);
+                data.window_title_text_edit_text = window_info.title;
+                data.window_class_text_edit_text = window_info.class;
+                data.mouse_position_text_edit_text = (
+                    (x - client_rect.x).to_string(),
+                    (y - client_rect.y).to_string(),
+                );
+                data.capture_error = None;
+            }
+            Err(error) => data.capture_error = Some(error.to_string()),
+        }
+    }
+
     fn cancel(&self, data: &MouseModifyCommandWindowData, recorder: &mut Recorder) {
         let selected_row = recorder.selected_row.unwrap();
         recorder.modal = None;
@@ -449,9 +651,9 @@ impl MouseModifyCommandWindow {
 }
 
 fn capture_mouse_position_key_pressed() -> bool {
-    unsafe { GetAsyncKeyState(VK_F2) < 0 }
+    key_pressed(VK_F2)
 }
 
 fn minimize_window_key_pressed() -> bool {
-    unsafe { GetAsyncKeyState(VK_F3) < 0 }
+    key_pressed(VK_F3)
 }
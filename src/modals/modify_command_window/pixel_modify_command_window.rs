@@ -3,13 +3,12 @@ use std::cell::RefCell;
 use crate::{
     actions::{Action, PixelInfo},
     gui::{Recorder, PIXELS_PER_POINT},
-    images::{get_color_under_mouse, screenshot, screenshot_to_color_image},
-    modals::ModalWindow,
+    images::{get_color_under_mouse, screen_size, screenshot, screenshot_to_color_image},
+    input::key_pressed,
+    modals::{modify_command_window::search_anchor_editor::SearchAnchorEditor, ModalWindow},
+    recorder::keymap::VK_F2,
 };
 use eframe::egui::*;
-use winapi::um::winuser::{
-    GetAsyncKeyState, GetSystemMetrics, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN, VK_F2,
-};
 
 #[derive(PartialEq, Eq, Clone, Copy)]
 enum CapturingState {
@@ -41,6 +40,7 @@ struct PixelModifyCommandWindowData {
     screenshot_next_frame: bool,
     capture_state: CapturingState,
     full_screen_texture: Option<TextureHandle>,
+    search_anchor: SearchAnchorEditor,
 }
 
 #[derive(Clone, Copy)]
@@ -97,6 +97,7 @@ impl PixelModifyCommandWindow {
                 screenshot_next_frame: false,
                 capture_state: CapturingState::NotCapturing,
                 full_screen_texture: None,
+                search_anchor: SearchAnchorEditor::new(&info.search_anchor),
             }),
         }
     }
@@ -295,6 +296,10 @@ impl PixelModifyCommandWindow {
 
             ui.add_space(15.0);
 
+            data.search_anchor.ui(ui);
+
+            ui.add_space(15.0);
+
             ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
                 ui.add_space(35.0);
                 if ui.button("Cancel").clicked() {
@@ -313,13 +318,8 @@ impl PixelModifyCommandWindow {
 
     fn screenshot_this_frame(&self, ctx: &Context, frame: &mut eframe::Frame) {
         let mut data = self.data.borrow_mut();
-        let (corner1, corner2) = unsafe {
-            (
-                GetSystemMetrics(SM_CXVIRTUALSCREEN),
-                GetSystemMetrics(SM_CYVIRTUALSCREEN),
-            )
-        };
-        let (corner1, corner2) = (pos2(0.0, 0.0), pos2(corner1 as f32, corner2 as f32));
+        let (width, height) = screen_size();
+        let (corner1, corner2) = (pos2(0.0, 0.0), pos2(width as f32, height as f32));
 
         let screenshot = screenshot(corner1, corner2);
 
@@ -451,11 +451,13 @@ impl PixelModifyCommandWindow {
                         if (capture_start.x - capture_end.x).abs() as i32 != 0
                             && (capture_start.y - capture_end.y).abs() as i32 != 0
                         {
+                            let (left, top) = data.search_anchor.relative_to_anchor(
+                                lesser(capture_start.x, capture_end.x),
+                                lesser(capture_start.y, capture_end.y),
+                            );
+
                             data.search_location_text_edit_texts = Some((
-                                (
-                                    lesser(capture_start.x, capture_end.x).to_string(),
-                                    lesser(capture_start.y, capture_end.y).to_string(),
-                                ),
+                                (left.to_string(), top.to_string()),
                                 (
                                     (capture_start.x - capture_end.x).abs().to_string(),
                                     (capture_start.y - capture_end.y).abs().to_string(),
@@ -493,6 +495,7 @@ impl PixelModifyCommandWindow {
                     search_location_width_height: (width, height),
                     check_if_not_found: data.check_if_not_found,
                     move_mouse_if_found: data.move_mouse_if_found,
+                    search_anchor: data.search_anchor.anchor(),
                 };
 
                 let selected_row = recorder.selected_row.unwrap();
@@ -534,7 +537,7 @@ impl ModalWindow for PixelModifyCommandWindow {
 }
 
 fn f2_pressed() -> bool {
-    unsafe { GetAsyncKeyState(VK_F2) < 0 }
+    key_pressed(VK_F2)
 }
 
 fn greater(one: f32, two: f32) -> f32 {
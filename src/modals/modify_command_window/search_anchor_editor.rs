@@ -0,0 +1,186 @@
+use std::fmt::*;
+
+use crate::{
+    actions::{SearchAnchor, WindowInfo},
+    window_manager::{exact_window_info, platform_window_manager},
+};
+use eframe::egui::*;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+#[derive(Debug, PartialEq, Eq, EnumIter, Clone, Copy)]
+enum SearchAnchorType {
+    Screen,
+    Window,
+    LastFoundImage,
+}
+
+impl Display for SearchAnchorType {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Screen => "Screen",
+                Self::Window => "Window",
+                Self::LastFoundImage => "Last Found Image",
+            }
+        )
+    }
+}
+
+/// Shared part of the image and pixel windows for choosing what the search area is relative to.
+pub struct SearchAnchorEditor {
+    anchor_type: SearchAnchorType,
+    title_text_edit_text: String,
+    class_text_edit_text: String,
+    error: Option<String>,
+}
+
+impl SearchAnchorEditor {
+    pub fn new(anchor: &SearchAnchor) -> Self {
+        let (anchor_type, window_info) = match anchor {
+            SearchAnchor::Screen => (SearchAnchorType::Screen, Default::default()),
+            SearchAnchor::Window(window_info) => (SearchAnchorType::Window, window_info.clone()),
+            SearchAnchor::LastFoundImage => (SearchAnchorType::LastFoundImage, Default::default()),
+        };
+
+        Self {
+            anchor_type,
+            title_text_edit_text: window_info.title,
+            class_text_edit_text: window_info.class,
+            error: None,
+        }
+    }
+
+    pub fn anchor(&self) -> SearchAnchor {
+        match self.anchor_type {
+            SearchAnchorType::Screen => SearchAnchor::Screen,
+            SearchAnchorType::Window => SearchAnchor::Window(self.window_info()),
+            SearchAnchorType::LastFoundImage => SearchAnchor::LastFoundImage,
+        }
+    }
+
+    fn window_info(&self) -> WindowInfo {
+        WindowInfo {
+            title: self.title_text_edit_text.clone(),
+            class: self.class_text_edit_text.clone(),
+        }
+    }
+
+    /// Where the anchor currently is on screen. The last found image is only known during
+    /// playback, so it's treated like the screen.
+    pub fn origin(&self) -> Option<(f32, f32)> {
+        if self.anchor_type != SearchAnchorType::Window {
+            return Some((0.0, 0.0));
+        }
+
+        let window_manager = platform_window_manager().ok()?;
+        let window = window_manager
+            .find_window(&self.window_info())
+            .ok()
+            .flatten()?;
+        let client_rect = window_manager.client_rect(window.id).ok()?;
+
+        Some((client_rect.x as f32, client_rect.y as f32))
+    }
+
+    /// Converts a selected screen position into one relative to the anchor. With no window
+    /// chosen yet, the window at the position becomes the anchor.
+    pub fn relative_to_anchor(&mut self, x: f32, y: f32) -> (f32, f32) {
+        if self.anchor_type != SearchAnchorType::Window {
+            return (x, y);
+        }
+
+        let result = platform_window_manager().and_then(|window_manager| {
+            let window =
+                if self.title_text_edit_text.is_empty() && self.class_text_edit_text.is_empty() {
+                    window_manager.window_at(x as i32, y as i32)
+                } else {
+                    window_manager.find_window(&self.window_info())?
+                };
+            let window = window.ok_or("No matching window found")?;
+
+            Ok((window_manager.client_rect(window.id)?, window))
+        });
+
+        match result {
+            Ok((client_rect, window)) => {
+                let window_info = exact_window_info(&window);
+                self.title_text_edit_text = window_info.title;
+                self.class_text_edit_text = window_info.class;
+                self.error = None;
+
+                (x - client_rect.x as f32, y - client_rect.y as f32)
+            }
+            Err(error) => {
+                self.error = Some(format!("{}, kept screen position", error));
+                (x, y)
+            }
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+            let selected = &mut self.anchor_type;
+
+            ui.add_space(25.0);
+            ui.label("Search area relative to: ");
+            ui.add_space(10.0);
+            ComboBox::new("Search Anchor Combo Box", "")
+                .selected_text(format!("{}", selected))
+                .width(150.0)
+                .show_ui(ui, |ui| {
+                    for anchor_type in SearchAnchorType::iter() {
+                        let text = format!("{}", anchor_type);
+
+                        ui.selectable_value(selected, anchor_type, text);
+
+                        ui.allocate_space(vec2(0.0, 3.5));
+                    }
+                });
+        });
+
+        match self.anchor_type {
+            SearchAnchorType::Window => {
+                ui.add_space(10.0);
+
+                ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                    ui.add_space(25.0);
+                    ui.label("Window Title: ");
+                    TextEdit::singleline(&mut self.title_text_edit_text)
+                        .desired_width(180.0)
+                        .ui(ui);
+                });
+
+                ui.add_space(10.0);
+
+                ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                    ui.add_space(25.0);
+                    ui.label("Window Class: ");
+                    TextEdit::singleline(&mut self.class_text_edit_text)
+                        .desired_width(180.0)
+                        .ui(ui);
+                });
+            }
+            SearchAnchorType::LastFoundImage => {
+                ui.add_space(10.0);
+
+                ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                    ui.add_space(25.0);
+                    ui.label("Left and Top are offsets from the center of the last found image.");
+                });
+            }
+            SearchAnchorType::Screen => {}
+        }
+
+        if let Some(error) = &self.error {
+            ui.add_space(10.0);
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(25.0);
+                ui.label(RichText::new(error).color(Color32::RED));
+            });
+        }
+    }
+}
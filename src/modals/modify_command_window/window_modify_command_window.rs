@@ -0,0 +1,296 @@
+use std::cell::RefCell;
+
+use crate::{
+    actions::{Action, Point, WindowInfo, WindowRect},
+    gui::Recorder,
+    input::{cursor_position, key_pressed},
+    modals::ModalWindow,
+    recorder::keymap::VK_F2,
+    window_manager::{exact_window_info, platform_window_manager},
+};
+use eframe::egui::*;
+use regex::Regex;
+
+#[derive(Clone, Copy)]
+pub enum WindowWindowType {
+    Wait,
+    Activate,
+    Move(WindowRect),
+}
+
+pub struct WindowModifyCommandWindow {
+    data: RefCell<WindowModifyCommandWindowData>,
+}
+
+struct WindowModifyCommandWindowData {
+    window_type: WindowWindowType,
+    creating_command: bool,
+    position: Option<Pos2>,
+    title_text_edit_text: String,
+    class_text_edit_text: String,
+    rect_text_edit_texts: ((String, String), (String, String)),
+    error: Option<String>,
+    f2_previously_pressed: bool,
+    enter_lock: bool,
+}
+
+impl WindowModifyCommandWindow {
+    pub fn new(
+        creating_command: bool,
+        position: Pos2,
+        window_info: &WindowInfo,
+        window_type: WindowWindowType,
+    ) -> Self {
+        let rect = match window_type {
+            WindowWindowType::Move(rect) => rect,
+            _ => Default::default(),
+        };
+
+        Self {
+            data: RefCell::new(WindowModifyCommandWindowData {
+                window_type,
+                creating_command,
+                position: Some(position),
+                title_text_edit_text: window_info.title.clone(),
+                class_text_edit_text: window_info.class.clone(),
+                rect_text_edit_texts: (
+                    (rect.x.to_string(), rect.y.to_string()),
+                    (rect.width.to_string(), rect.height.to_string()),
+                ),
+                error: None,
+                f2_previously_pressed: f2_pressed(),
+                enter_lock: true,
+            }),
+        }
+    }
+
+    fn setup(&self, drag_bounds: Rect) -> Window {
+        let mut data = self.data.borrow_mut();
+
+        let mut window = Window::new(match data.window_type {
+            WindowWindowType::Wait => "Wait For Window",
+            WindowWindowType::Activate => "Activate Window",
+            WindowWindowType::Move(..) => "Move Window",
+        })
+        .collapsible(false)
+        .resizable(false)
+        .drag_bounds(drag_bounds);
+
+        if let Some(position) = &data.position {
+            window = window.current_pos(Pos2::new(position.x, position.y));
+            data.position = None;
+        }
+
+        window
+    }
+
+    fn capture_window_under_mouse(data: &mut WindowModifyCommandWindowData) {
+        let point = cursor_position().unwrap_or(Point { x: 0, y: 0 });
+
+        let result = platform_window_manager().and_then(|window_manager| {
+            let window = window_manager
+                .window_at(point.x, point.y)
+                .ok_or("No window under the mouse")?;
+            let rect = window_manager.rect(window.id)?;
+            Ok((window, rect))
+        });
+
+        match result {
+            Ok((window, rect)) => {
+                let window_info = exact_window_info(&window);
+                data.title_text_edit_text = window_info.title;
+                data.class_text_edit_text = window_info.class;
+                data.rect_text_edit_texts = (
+                    (rect.x.to_string(), rect.y.to_string()),
+                    (rect.width.to_string(), rect.height.to_string()),
+                );
+                data.error = None;
+            }
+            Err(error) => data.error = Some(error.to_string()),
+        }
+    }
+
+    fn save(&self, data: &mut WindowModifyCommandWindowData, recorder: &mut Recorder) {
+        let window_info = WindowInfo {
+            title: data.title_text_edit_text.clone(),
+            class: data.class_text_edit_text.clone(),
+        };
+
+        for (name, regex) in [("Title", &window_info.title), ("Class", &window_info.class)] {
+            if let Err(error) = Regex::new(regex) {
+                data.error = Some(format!("{}: {}", name, error));
+                return;
+            }
+        }
+
+        let action = match data.window_type {
+            WindowWindowType::Wait => Action::WaitForWindow(window_info),
+            WindowWindowType::Activate => Action::ActivateWindow(window_info),
+            WindowWindowType::Move(..) => {
+                let texts = &data.rect_text_edit_texts;
+                let (Ok(x), Ok(y), Ok(width), Ok(height)) = (
+                    texts.0 .0.parse(),
+                    texts.0 .1.parse(),
+                    texts.1 .0.parse(),
+                    texts.1 .1.parse(),
+                ) else {
+                    return;
+                };
+
+                Action::MoveWindow(
+                    window_info,
+                    WindowRect {
+                        x,
+                        y,
+                        width,
+                        height,
+                    },
+                )
+            }
+        };
+
+        recorder.modal = None;
+        let selected_row = recorder.selected_row.unwrap();
+        recorder.action_list()[selected_row] = action;
+    }
+
+    fn cancel(&self, data: &WindowModifyCommandWindowData, recorder: &mut Recorder) {
+        let selected_row = recorder.selected_row.unwrap();
+        recorder.modal = None;
+        if data.creating_command {
+            recorder.action_list().remove(selected_row);
+            recorder.selected_row = None;
+        }
+    }
+}
+
+impl ModalWindow for WindowModifyCommandWindow {
+    fn update(
+        &self,
+        recorder: &mut Recorder,
+        ctx: &Context,
+        _ui: &mut Ui,
+        drag_bounds: Rect,
+        _frame: &mut eframe::Frame,
+    ) {
+        let window = self.setup(drag_bounds);
+
+        window.show(ctx, |ui| {
+            let data = &mut self.data.borrow_mut();
+
+            if ui.input().key_down(Key::Enter) {
+                if !data.enter_lock {
+                    self.save(data, recorder);
+                }
+            } else {
+                data.enter_lock = false;
+            }
+            if ui.input().key_pressed(Key::Escape) {
+                self.cancel(data, recorder);
+            }
+
+            if f2_pressed() {
+                if !data.f2_previously_pressed {
+                    Self::capture_window_under_mouse(data);
+                    data.f2_previously_pressed = true;
+                }
+            } else {
+                data.f2_previously_pressed = false;
+            }
+
+            ui.allocate_space(vec2(0.0, 15.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(15.0);
+                ui.label("Title: ");
+                TextEdit::singleline(&mut data.title_text_edit_text)
+                    .desired_width(250.0)
+                    .ui(ui);
+                ui.add_space(15.0);
+            });
+
+            ui.allocate_space(vec2(0.0, 15.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(15.0);
+                ui.label("Class: ");
+                TextEdit::singleline(&mut data.class_text_edit_text)
+                    .desired_width(250.0)
+                    .ui(ui);
+                ui.add_space(15.0);
+            });
+
+            ui.add_space(10.0);
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(15.0);
+                ui.label("Regular expressions. Leave empty to match any window.");
+            });
+
+            if let WindowWindowType::Move(..) = data.window_type {
+                ui.allocate_space(vec2(0.0, 15.0));
+
+                ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                    let rect = &mut data.rect_text_edit_texts;
+                    ui.allocate_space(vec2(15.0, 0.0));
+
+                    ui.label("X: ");
+                    TextEdit::singleline(&mut rect.0 .0)
+                        .desired_width(50.0)
+                        .ui(ui);
+                    ui.allocate_space(vec2(5.0, 0.0));
+
+                    ui.label("Y: ");
+                    TextEdit::singleline(&mut rect.0 .1)
+                        .desired_width(50.0)
+                        .ui(ui);
+                    ui.allocate_space(vec2(5.0, 0.0));
+
+                    ui.label("Width: ");
+                    TextEdit::singleline(&mut rect.1 .0)
+                        .desired_width(50.0)
+                        .ui(ui);
+                    ui.allocate_space(vec2(5.0, 0.0));
+
+                    ui.label("Height: ");
+                    TextEdit::singleline(&mut rect.1 .1)
+                        .desired_width(50.0)
+                        .ui(ui);
+                });
+            }
+
+            ui.add_space(25.0);
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(15.0);
+                ui.label("Press F2 to capture the window under the mouse");
+            });
+
+            if let Some(error) = &data.error {
+                ui.add_space(10.0);
+
+                ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                    ui.add_space(15.0);
+                    ui.label(RichText::new(error).color(Color32::RED));
+                });
+            }
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                ui.add_space(35.0);
+                if ui.button("Cancel").clicked() {
+                    self.cancel(data, recorder);
+                }
+                ui.add_space(35.0);
+                if ui.button("Save").clicked() {
+                    self.save(data, recorder);
+                }
+            });
+        });
+    }
+}
+
+fn f2_pressed() -> bool {
+    key_pressed(VK_F2)
+}
@@ -1,10 +1,15 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, time::SystemTime};
 
+use chrono::{DateTime, Utc};
 use egui::*;
+use strum::IntoEnumIterator;
 
-use crate::{gui::Recorder, settings, Settings};
+use crate::{
+    gui::Recorder, hotkeys::start_hotkey_detector, keycodes_to_string::key_code_to_string,
+    recorder::simplify::MouseSimplification, settings, Settings,
+};
 
-use super::{warning_window::DefaultErrorWindow, ModalWindow};
+use super::{hotkeys_window::get_pressed_buttons, warning_window::DefaultErrorWindow, ModalWindow};
 
 pub struct SettingsWindow {
     data: RefCell<SettingsWindowData>,
@@ -13,11 +18,23 @@ pub struct SettingsWindow {
 impl SettingsWindow {
     pub fn new(settings: Settings) -> Self {
         let replay_textedit_text = settings.repeat_times.to_string();
+        let humanize_speed_textedit_text = settings.humanize_mouse_speed.to_string();
+        let simplify_interval_textedit_text = settings.simplify_interval.to_string();
+        let simplify_tolerance_textedit_text = settings.simplify_tolerance.to_string();
+        let seed_textedit_text = settings
+            .random_seed
+            .map_or(String::new(), |seed| seed.to_string());
 
         Self {
             data: RefCell::new(SettingsWindowData {
                 temp_settings: settings,
                 replay_textedit_text,
+                humanize_speed_textedit_text,
+                simplify_interval_textedit_text,
+                simplify_tolerance_textedit_text,
+                seed_textedit_text,
+                hotkey_setting_index: None,
+                key_start_time: None,
             }),
         }
     }
@@ -34,6 +51,20 @@ impl SettingsWindow {
 struct SettingsWindowData {
     temp_settings: Settings,
     replay_textedit_text: String,
+    humanize_speed_textedit_text: String,
+    simplify_interval_textedit_text: String,
+    simplify_tolerance_textedit_text: String,
+    seed_textedit_text: String,
+    /// Which of the stop, play, record and pause hotkeys is being set.
+    hotkey_setting_index: Option<usize>,
+    key_start_time: Option<DateTime<Utc>>,
+}
+
+fn hotkey_text(keys: &[i32]) -> String {
+    keys.iter()
+        .map(|key| key_code_to_string(*key).to_string())
+        .collect::<Vec<_>>()
+        .join(" + ")
 }
 
 impl ModalWindow for SettingsWindow {
@@ -57,6 +88,45 @@ impl ModalWindow for SettingsWindow {
                 "Record mouse movement",
             );
 
+            if data.temp_settings.record_mouse_movement {
+                ui.allocate_space(vec2(0.0, 10.0));
+
+                ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                    let fields = &mut *data;
+                    let selected = &mut fields.temp_settings.mouse_simplification;
+
+                    ComboBox::new("Mouse Simplification Combo Box", "")
+                        .selected_text(format!("{}", selected))
+                        .width(150.0)
+                        .show_ui(ui, |ui| {
+                            for simplification in MouseSimplification::iter() {
+                                let text = format!("{}", simplification);
+
+                                ui.selectable_value(selected, simplification, text);
+
+                                ui.allocate_space(vec2(0.0, 3.5));
+                            }
+                        });
+
+                    let (text, label) = match fields.temp_settings.mouse_simplification {
+                        MouseSimplification::None => return,
+                        MouseSimplification::Sampling => (
+                            &mut fields.simplify_interval_textedit_text,
+                            "ms between movements",
+                        ),
+                        _ => (
+                            &mut fields.simplify_tolerance_textedit_text,
+                            "pixel tolerance",
+                        ),
+                    };
+
+                    ui.add_space(15.0);
+                    TextEdit::singleline(text).desired_width(50.0).ui(ui);
+                    ui.add_space(5.0);
+                    ui.label(label);
+                });
+            }
+
             ui.allocate_space(vec2(0.0, 25.0));
 
             ui.checkbox(
@@ -66,6 +136,30 @@ impl ModalWindow for SettingsWindow {
 
             ui.allocate_space(vec2(0.0, 25.0));
 
+            ui.checkbox(
+                &mut data.temp_settings.record_window_relative,
+                "Record mouse positions relative to the window under the mouse",
+            );
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
+            ui.checkbox(
+                &mut data.temp_settings.record_monitor_relative,
+                "Record mouse positions relative to the monitor under the mouse",
+            )
+            .on_hover_text(
+                "Positions are moved to the same place if the monitors are rearranged,\nor change resolution or scaling.",
+            );
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
+            ui.checkbox(
+                &mut data.temp_settings.record_drags,
+                "Record dragging the mouse as a single drag action",
+            );
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
             ui.label(format!(
                 "Playback speed: {}x",
                 data.temp_settings.playback_speed
@@ -82,6 +176,13 @@ impl ModalWindow for SettingsWindow {
 
             ui.allocate_space(vec2(0.0, 25.0));
 
+            ui.checkbox(
+                &mut data.temp_settings.keys_by_scan_code,
+                "Send keys by scan code, for games and other keyboard layouts",
+            );
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
             ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
                 let repeat_area =
                     TextEdit::singleline(&mut data.replay_textedit_text).desired_width(75.0);
@@ -93,6 +194,97 @@ impl ModalWindow for SettingsWindow {
 
             ui.allocate_space(vec2(0.0, 25.0));
 
+            ui.checkbox(
+                &mut data.temp_settings.humanize_mouse,
+                "Move the mouse along smooth curves instead of jumping",
+            );
+
+            if data.temp_settings.humanize_mouse {
+                ui.allocate_space(vec2(0.0, 10.0));
+
+                ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                    TextEdit::singleline(&mut data.humanize_speed_textedit_text)
+                        .desired_width(75.0)
+                        .ui(ui);
+                    ui.add_space(25.0);
+                    ui.label("Mouse speed in pixels per second");
+                });
+            }
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                TextEdit::singleline(&mut data.seed_textedit_text)
+                    .desired_width(75.0)
+                    .ui(ui);
+                ui.add_space(25.0);
+                ui.label(
+                    "Random seed for jitter and mouse curves. Leave empty for a new one every run.",
+                );
+            });
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
+            ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                TextEdit::singleline(&mut data.temp_settings.author)
+                    .desired_width(150.0)
+                    .ui(ui);
+                ui.add_space(25.0);
+                ui.label("Author name saved in new macros");
+            });
+
+            ui.allocate_space(vec2(0.0, 25.0));
+
+            let fields = &mut *data;
+            let hotkeys = [
+                ("Stop hotkey: ", &mut fields.temp_settings.stop_hotkey),
+                ("Play hotkey: ", &mut fields.temp_settings.play_hotkey),
+                ("Record hotkey: ", &mut fields.temp_settings.record_hotkey),
+                ("Pause hotkey: ", &mut fields.temp_settings.pause_hotkey),
+            ];
+            for (index, (label, keys)) in hotkeys.into_iter().enumerate() {
+                ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
+                    ui.label(label);
+                    ui.add_space(5.0);
+
+                    let button_text = if fields.hotkey_setting_index == Some(index) {
+                        let pressed_buttons = get_pressed_buttons();
+                        if pressed_buttons.is_empty() {
+                            "Press desired button(s)".into()
+                        } else {
+                            hotkey_text(&pressed_buttons)
+                        }
+                    } else {
+                        hotkey_text(keys)
+                    };
+
+                    if ui.button(button_text).clicked() {
+                        fields.hotkey_setting_index = Some(index);
+                        fields.key_start_time = None;
+                    }
+                });
+
+                if fields.hotkey_setting_index == Some(index) {
+                    if let Some(time) = fields.key_start_time {
+                        if (DateTime::from(SystemTime::now()) - time).num_seconds() > 0 {
+                            let pressed_buttons = get_pressed_buttons();
+                            // Keep the old hotkey, an empty one could never stop playback
+                            if !pressed_buttons.is_empty() {
+                                *keys = pressed_buttons;
+                            }
+                            fields.hotkey_setting_index = None;
+                            fields.key_start_time = None;
+                        }
+                    } else {
+                        fields.key_start_time = Some(DateTime::from(SystemTime::now()));
+                    }
+                }
+
+                ui.allocate_space(vec2(0.0, 10.0));
+            }
+
+            ui.allocate_space(vec2(0.0, 15.0));
+
             ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
                 ui.add_space(35.0);
                 if ui.button("Cancel").clicked() {
@@ -100,10 +292,33 @@ impl ModalWindow for SettingsWindow {
                 }
                 ui.add_space(35.0);
                 if ui.button("Save").clicked() {
-                    if let Ok(repeats) = data.replay_textedit_text.parse() {
+                    let seed = if data.seed_textedit_text.is_empty() {
+                        Ok(None)
+                    } else {
+                        data.seed_textedit_text.parse().map(Some)
+                    };
+
+                    if let (Ok(repeats), Ok(speed), Ok(interval), Ok(tolerance), Ok(seed)) = (
+                        data.replay_textedit_text.parse(),
+                        data.humanize_speed_textedit_text.parse::<f32>(),
+                        data.simplify_interval_textedit_text.parse(),
+                        data.simplify_tolerance_textedit_text.parse::<f32>(),
+                        seed,
+                    ) {
                         data.temp_settings.repeat_times = repeats;
+                        data.temp_settings.humanize_mouse_speed = speed.max(1.0);
+                        data.temp_settings.simplify_interval = interval;
+                        data.temp_settings.simplify_tolerance = tolerance.max(0.0);
+                        data.temp_settings.random_seed = seed;
                         recorder.settings = data.temp_settings.clone();
                         recorder.modal = data.temp_settings.save_with_error_window();
+
+                        // Hotkey macros are stopped with the stop hotkey they were started with
+                        if let Some(sender) = recorder.hotkey_detector_sender.take() {
+                            sender.send(()).unwrap();
+                        }
+                        recorder.hotkey_detector_sender =
+                            Some(start_hotkey_detector(&mut recorder.settings));
                     }
                 }
             });
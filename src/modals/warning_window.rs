@@ -2,7 +2,7 @@ use std::rc::Rc;
 
 use egui::*;
 
-use crate::gui::{RecordPlayAction, Recorder};
+use crate::gui::{RecordMode, RecordPlayAction, Recorder};
 
 use super::ModalWindow;
 
@@ -69,11 +69,13 @@ impl ModalWindow for RecordConfirmationWindow {
             ui.allocate_space(vec2(0.0, 25.0));
 
             ui.label(
-                "Recording will replace the existing macro.\nMake sure to save if you haven't!",
+                "Recording can replace the macro or be added to it.\nMake sure to save if you haven't!",
             );
 
             ui.allocate_space(vec2(0.0, 25.0));
 
+            let mut mode = None;
+
             ui.with_layout(Layout::left_to_right(Align::LEFT), |ui| {
                 if ui.button("Cancel").clicked() {
                     recorder.modal = None;
@@ -81,13 +83,31 @@ impl ModalWindow for RecordConfirmationWindow {
 
                 ui.add_space(25.0);
 
-                if ui.button("Record").clicked() {
-                    recorder.modal = None;
-                    recorder.next_play_record_action = Some(RecordPlayAction::Record);
-                    frame.set_visible(false);
-                    frame.set_fullscreen(false);
+                if ui.button("Replace").clicked() {
+                    mode = Some(RecordMode::Replace);
+                }
+
+                ui.add_space(25.0);
+
+                if ui.button("Append").clicked() {
+                    mode = Some(RecordMode::Append);
+                }
+
+                if let Some(row) = recorder.selected_row {
+                    ui.add_space(25.0);
+
+                    if ui.button("Insert After Selected").clicked() {
+                        mode = Some(RecordMode::InsertAfter(row));
+                    }
                 }
             });
+
+            if let Some(mode) = mode {
+                recorder.modal = None;
+                recorder.next_play_record_action = Some(RecordPlayAction::Record(mode));
+                frame.set_visible(false);
+                frame.set_fullscreen(false);
+            }
         });
     }
 }
@@ -0,0 +1,202 @@
+use std::{collections::HashSet, error::Error};
+
+use crate::actions::{MonitorInfo, Point};
+
+#[cfg(windows)]
+mod win32;
+#[cfg(target_os = "linux")]
+mod x11;
+
+/// Connected monitors, in screen pixels. Positions are only in physical pixels while the process
+/// is DPI aware, which the window library takes care of when it starts.
+#[cfg(windows)]
+pub fn platform_monitors() -> Result<Vec<MonitorInfo>, Box<dyn Error>> {
+    win32::monitors()
+}
+
+#[cfg(target_os = "linux")]
+pub fn platform_monitors() -> Result<Vec<MonitorInfo>, Box<dyn Error>> {
+    x11::monitors()
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn platform_monitors() -> Result<Vec<MonitorInfo>, Box<dyn Error>> {
+    Err("Listing monitors isn't supported on this platform".into())
+}
+
+/// The monitor showing the screen position.
+pub fn monitor_at(monitors: &[MonitorInfo], point: Point) -> Option<&MonitorInfo> {
+    monitors
+        .iter()
+        .find(|monitor| monitor.rect.contains(point.x, point.y))
+}
+
+/// Where a position recorded on a monitor ends up with the monitors connected now, and a warning
+/// if it can't be placed exactly. The monitor with the same name is used, or the primary one if
+/// it's gone. Positions are stretched to the monitor's resolution, which is only exact if the
+/// resolution and scaling changed together.
+pub fn remap_point(
+    recorded: &MonitorInfo,
+    point: Point,
+    monitors: &[MonitorInfo],
+) -> (Point, Option<String>) {
+    let same_monitor = monitors
+        .iter()
+        .find(|monitor| monitor.name == recorded.name);
+    let Some(monitor) = same_monitor
+        .or_else(|| monitors.iter().find(|monitor| monitor.primary))
+        .or_else(|| monitors.first())
+    else {
+        return (
+            Point {
+                x: recorded.rect.x + point.x,
+                y: recorded.rect.y + point.y,
+            },
+            Some(format!(
+                "No monitors found, playing positions recorded on {} where they were recorded",
+                recorded.describe()
+            )),
+        );
+    };
+
+    let stretch = |value: i32, from: i32, to: i32| {
+        if from == to || from <= 0 {
+            value
+        } else {
+            (value as f64 * to as f64 / from as f64).round() as i32
+        }
+    };
+
+    let position = Point {
+        x: monitor.rect.x + stretch(point.x, recorded.rect.width, monitor.rect.width),
+        y: monitor.rect.y + stretch(point.y, recorded.rect.height, monitor.rect.height),
+    };
+
+    // What's on the screen is laid out in scaled pixels, if there's more or less room for it
+    // things have moved around
+    let logical_size = |monitor: &MonitorInfo| {
+        (
+            (monitor.rect.width as f32 / monitor.scale).round(),
+            (monitor.rect.height as f32 / monitor.scale).round(),
+        )
+    };
+
+    let warning = if same_monitor.is_none() {
+        Some(format!(
+            "Monitor {} isn't connected, playing its positions on {}",
+            recorded.describe(),
+            monitor.describe()
+        ))
+    } else if logical_size(recorded) != logical_size(monitor) {
+        Some(format!(
+            "Monitor {} is now {}, its positions are stretched to fit and may be off",
+            recorded.describe(),
+            monitor.describe()
+        ))
+    } else {
+        None
+    };
+
+    (position, warning)
+}
+
+/// The monitors at the start of playback. Warnings about positions that can't be placed exactly
+/// are only printed once for each recorded monitor.
+#[derive(Debug, Default)]
+pub struct MonitorLayout {
+    monitors: Vec<MonitorInfo>,
+    warned: HashSet<String>,
+}
+
+impl MonitorLayout {
+    pub fn new(monitors: Vec<MonitorInfo>) -> Self {
+        Self {
+            monitors,
+            warned: HashSet::new(),
+        }
+    }
+
+    pub fn current() -> Self {
+        match platform_monitors() {
+            Ok(monitors) => Self::new(monitors),
+            Err(error) => {
+                println!("Failed to list monitors: {}", error);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn to_screen(&mut self, recorded: &MonitorInfo, point: Point) -> Point {
+        let (position, warning) = remap_point(recorded, point, &self.monitors);
+
+        if let Some(warning) = warning {
+            if self.warned.insert(recorded.name.clone()) {
+                println!("{}", warning);
+            }
+        }
+
+        position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::WindowRect;
+
+    fn monitor(name: &str, x: i32, y: i32, width: i32, height: i32, scale: f32) -> MonitorInfo {
+        MonitorInfo {
+            name: name.into(),
+            rect: WindowRect {
+                x,
+                y,
+                width,
+                height,
+            },
+            scale,
+            primary: x == 0 && y == 0,
+        }
+    }
+
+    #[test]
+    fn follows_a_monitor_that_moved() {
+        let recorded = monitor("DISPLAY2", -1920, 0, 1920, 1080, 1.0);
+        let monitors = [
+            monitor("DISPLAY1", 0, 0, 2560, 1440, 1.0),
+            monitor("DISPLAY2", 2560, -200, 1920, 1080, 1.0),
+        ];
+
+        assert_eq!(
+            remap_point(&recorded, Point { x: 100, y: 50 }, &monitors),
+            (Point { x: 2660, y: -150 }, None)
+        );
+    }
+
+    #[test]
+    fn stretches_positions_when_scaling_changed_with_the_resolution() {
+        let recorded = monitor("DISPLAY1", 0, 0, 3840, 2160, 1.5);
+        let monitors = [monitor("DISPLAY1", 0, 0, 2560, 1440, 1.0)];
+
+        assert_eq!(
+            remap_point(&recorded, Point { x: 300, y: 600 }, &monitors),
+            (Point { x: 200, y: 400 }, None)
+        );
+    }
+
+    #[test]
+    fn warns_when_positions_cant_be_placed_exactly() {
+        let recorded = monitor("DISPLAY1", 0, 0, 1920, 1080, 1.0);
+        let rescaled = [monitor("DISPLAY1", 0, 0, 1920, 1080, 1.25)];
+        let (position, warning) = remap_point(&recorded, Point { x: 10, y: 10 }, &rescaled);
+
+        assert_eq!(position, Point { x: 10, y: 10 });
+        assert!(warning.is_some());
+
+        let recorded = monitor("DISPLAY3", 1920, 0, 1280, 1024, 1.0);
+        let monitors = [monitor("DISPLAY1", 0, 0, 2560, 2048, 1.0)];
+        let (position, warning) = remap_point(&recorded, Point { x: 640, y: 512 }, &monitors);
+
+        assert_eq!(position, Point { x: 1280, y: 1024 });
+        assert!(warning.is_some());
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    error::Error,
+    mem::{size_of, zeroed},
+    ptr::{null, null_mut},
+};
+
+use winapi::{
+    shared::{
+        minwindef::{BOOL, LPARAM, TRUE},
+        windef::{HDC, HMONITOR, LPRECT},
+    },
+    um::{
+        shellscalingapi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI},
+        winuser::*,
+    },
+};
+
+use crate::actions::{MonitorInfo, WindowRect};
+
+unsafe extern "system" fn collect_monitor(
+    monitor: HMONITOR,
+    _hdc: HDC,
+    _rect: LPRECT,
+    l_param: LPARAM,
+) -> BOOL {
+    let monitors = &mut *(l_param as *mut Vec<HMONITOR>);
+    monitors.push(monitor);
+
+    TRUE
+}
+
+pub fn monitors() -> Result<Vec<MonitorInfo>, Box<dyn Error>> {
+    let mut handles: Vec<HMONITOR> = vec![];
+
+    if unsafe {
+        EnumDisplayMonitors(
+            null_mut(),
+            null(),
+            Some(collect_monitor),
+            &mut handles as *mut Vec<HMONITOR> as LPARAM,
+        )
+    } == 0
+    {
+        return Err("Failed to list monitors".into());
+    }
+
+    handles.into_iter().map(monitor_info).collect()
+}
+
+fn monitor_info(monitor: HMONITOR) -> Result<MonitorInfo, Box<dyn Error>> {
+    let mut info: MONITORINFOEXW = unsafe { zeroed() };
+    info.cbSize = size_of::<MONITORINFOEXW>() as u32;
+
+    if unsafe {
+        GetMonitorInfoW(
+            monitor,
+            &mut info as *mut MONITORINFOEXW as *mut MONITORINFO,
+        )
+    } == 0
+    {
+        return Err("A monitor was disconnected while listing them".into());
+    }
+
+    // Stays at 96 on Windows versions without per monitor scaling
+    let (mut dpi_x, mut dpi_y) = (96, 96);
+    unsafe { GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) };
+
+    let name_length = info
+        .szDevice
+        .iter()
+        .position(|character| *character == 0)
+        .unwrap_or(info.szDevice.len());
+    let rect = info.rcMonitor;
+
+    Ok(MonitorInfo {
+        name: String::from_utf16_lossy(&info.szDevice[..name_length]),
+        rect: WindowRect {
+            x: rect.left,
+            y: rect.top,
+            width: rect.right - rect.left,
+            height: rect.bottom - rect.top,
+        },
+        scale: dpi_x as f32 / 96.0,
+        primary: info.dwFlags & MONITORINFOF_PRIMARY != 0,
+    })
+}
@@ -0,0 +1,78 @@
+use std::error::Error;
+
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        randr::ConnectionExt as _,
+        xproto::{AtomEnum, ConnectionExt as _, Window},
+    },
+    rust_connection::RustConnection,
+};
+
+use crate::actions::{MonitorInfo, WindowRect};
+
+pub fn monitors() -> Result<Vec<MonitorInfo>, Box<dyn Error>> {
+    let (connection, screen_num) = x11rb::connect(None)?;
+    let root = connection.setup().roots[screen_num].root;
+    let scale = scale(&connection, root)?;
+
+    let reply = connection.randr_get_monitors(root, true)?.reply()?;
+
+    reply
+        .monitors
+        .iter()
+        .map(|monitor| {
+            let name = connection.get_atom_name(monitor.name)?.reply()?.name;
+
+            Ok(MonitorInfo {
+                name: String::from_utf8_lossy(&name).into_owned(),
+                rect: WindowRect {
+                    x: monitor.x as i32,
+                    y: monitor.y as i32,
+                    width: monitor.width as i32,
+                    height: monitor.height as i32,
+                },
+                scale,
+                primary: monitor.primary,
+            })
+        })
+        .collect()
+}
+
+/// X11 has one scale for every monitor, desktops set it through `Xft.dpi`.
+fn scale(connection: &RustConnection, root: Window) -> Result<f32, Box<dyn Error>> {
+    let reply = connection
+        .get_property(
+            false,
+            root,
+            AtomEnum::RESOURCE_MANAGER,
+            AtomEnum::STRING,
+            0,
+            u32::MAX,
+        )?
+        .reply()?;
+
+    Ok(resources_scale(&String::from_utf8_lossy(&reply.value)))
+}
+
+/// The scale set in X resources, 96 DPI is unscaled.
+fn resources_scale(resources: &str) -> f32 {
+    resources
+        .lines()
+        .find_map(|line| line.strip_prefix("Xft.dpi:")?.trim().parse::<f32>().ok())
+        .map_or(1.0, |dpi| dpi / 96.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_scale_from_xft_dpi() {
+        let resources = "Xcursor.size:\t24\nXft.antialias:\t1\nXft.dpi:\t144\n";
+        assert_eq!(resources_scale(resources), 1.5);
+        assert_eq!(resources_scale("Xft.dpi: 96"), 1.0);
+        assert_eq!(resources_scale("Xcursor.size:\t24\n"), 1.0);
+        assert_eq!(resources_scale(""), 1.0);
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    error::Error,
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Read},
+    mem::size_of,
+    os::unix::fs::OpenOptionsExt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{keymap::*, HookEvent, InputEvent, InputHook};
+use crate::actions::{KeyState, MouseActionButtonState, Point};
+
+// Event types and codes from linux/input-event-codes.h
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_REL: u16 = 0x02;
+const SYN_REPORT: u16 = 0x00;
+const REL_X: u16 = 0x00;
+const REL_Y: u16 = 0x01;
+const REL_HWHEEL: u16 = 0x06;
+const REL_WHEEL: u16 = 0x08;
+
+// struct input_event is a timeval of two longs followed by the type, code and value
+const TIME_SIZE: usize = size_of::<usize>();
+const EVENT_SIZE: usize = TIME_SIZE * 2 + 8;
+
+/// How long the readers wait for events before checking if they were stopped. Events carry the
+/// kernel's timestamps, so this doesn't change the recorded delays.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Reads the kernel's input devices directly, which works without a display server but usually
+/// needs the user to be in the input group. The cursor position isn't known, so positions start
+/// at the origin and follow the relative movement of the mice, and only the offsets between them
+/// are recorded.
+#[derive(Default)]
+pub struct EvdevInputHook {
+    running: Option<(Arc<AtomicBool>, Vec<JoinHandle<()>>)>,
+}
+
+impl InputHook for EvdevInputHook {
+    fn start(&mut self, sender: Sender<HookEvent>) -> Result<(Option<Point>, u32), Box<dyn Error>> {
+        self.stop();
+
+        let devices: Vec<File> = fs::read_dir("/dev/input")?
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("event"))
+            .filter_map(|entry| {
+                OpenOptions::new()
+                    .read(true)
+                    .custom_flags(libc::O_NONBLOCK)
+                    .open(entry.path())
+                    .ok()
+            })
+            .collect();
+
+        if devices.is_empty() {
+            return Err(
+                "No readable input devices in /dev/input, the user may need to be in the input group"
+                    .into(),
+            );
+        }
+
+        let running = Arc::new(AtomicBool::new(true));
+        let position = Arc::new(Mutex::new(Point { x: 0, y: 0 }));
+
+        let handles = devices
+            .into_iter()
+            .map(|device| {
+                let sender = sender.clone();
+                let running = running.clone();
+                let position = position.clone();
+                thread::spawn(move || read_device(device, sender, &running, &position))
+            })
+            .collect();
+        self.running = Some((running, handles));
+
+        let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u32;
+
+        Ok((None, time))
+    }
+
+    fn stop(&mut self) {
+        if let Some((running, handles)) = self.running.take() {
+            running.store(false, Ordering::Relaxed);
+
+            for handle in handles {
+                handle.join().ok();
+            }
+        }
+    }
+}
+
+impl Drop for EvdevInputHook {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn read_device(
+    mut device: File,
+    sender: Sender<HookEvent>,
+    running: &AtomicBool,
+    position: &Mutex<Point>,
+) {
+    let mut buffer = [0; EVENT_SIZE];
+    // Movement is sent once the device reports a complete batch of changes
+    let mut moved = Point { x: 0, y: 0 };
+
+    while running.load(Ordering::Relaxed) {
+        // The kernel hands out whole events, never parts of one
+        match device.read_exact(&mut buffer) {
+            Ok(()) => {}
+            Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            Err(_) => break,
+        }
+
+        let seconds = usize::from_ne_bytes(buffer[..TIME_SIZE].try_into().unwrap());
+        let microseconds =
+            usize::from_ne_bytes(buffer[TIME_SIZE..TIME_SIZE * 2].try_into().unwrap());
+        let rest = &buffer[TIME_SIZE * 2..];
+        let kind = u16::from_ne_bytes([rest[0], rest[1]]);
+        let code = u16::from_ne_bytes([rest[2], rest[3]]);
+        let value = i32::from_ne_bytes([rest[4], rest[5], rest[6], rest[7]]);
+
+        let time = (seconds as u64 * 1000 + microseconds as u64 / 1000) as u32;
+
+        let event = match (kind, code) {
+            (EV_KEY, _) => {
+                if let Some(button) = evdev_to_mouse_button(code) {
+                    let state = if value == 0 {
+                        MouseActionButtonState::Released
+                    } else {
+                        MouseActionButtonState::Pressed
+                    };
+                    Some(InputEvent::MouseButton(button, state))
+                } else {
+                    // A value of 2 is the key repeating while held
+                    let state = if value == 0 {
+                        KeyState::Up
+                    } else {
+                        KeyState::Down
+                    };
+                    evdev_to_virtual_key(code)
+                        .map(|key_code| InputEvent::Key(key_code, state, evdev_to_scan_code(code)))
+                }
+            }
+            (EV_REL, REL_X) => {
+                moved.x += value;
+                None
+            }
+            (EV_REL, REL_Y) => {
+                moved.y += value;
+                None
+            }
+            (EV_REL, REL_WHEEL) => Some(InputEvent::Wheel {
+                amount: value * 120,
+                horizontal: false,
+            }),
+            (EV_REL, REL_HWHEEL) => Some(InputEvent::Wheel {
+                amount: value * 120,
+                horizontal: true,
+            }),
+            (EV_SYN, SYN_REPORT) if moved.x != 0 || moved.y != 0 => {
+                let mut position = position.lock().unwrap();
+                position.x += moved.x;
+                position.y += moved.y;
+                moved = Point { x: 0, y: 0 };
+                Some(InputEvent::MouseMove)
+            }
+            _ => None,
+        };
+
+        if let Some(event) = event {
+            let hook_event = HookEvent {
+                event,
+                position: *position.lock().unwrap(),
+                time,
+                window: None,
+            };
+
+            if sender.send(hook_event).is_err() {
+                break;
+            }
+        }
+    }
+}
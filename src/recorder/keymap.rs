@@ -0,0 +1,140 @@
+//! Recordings store keys and mouse buttons as Win32 virtual-key codes on every platform.
+
+use crate::{actions::ScanCode, keys::Key};
+
+pub const VK_LBUTTON: i32 = 0x01;
+pub const VK_RBUTTON: i32 = 0x02;
+pub const VK_MBUTTON: i32 = 0x04;
+pub const VK_XBUTTON1: i32 = 0x05;
+pub const VK_XBUTTON2: i32 = 0x06;
+pub const VK_BACK: i32 = 0x08;
+pub const VK_TAB: i32 = 0x09;
+pub const VK_RETURN: i32 = 0x0D;
+pub const VK_SHIFT: i32 = 0x10;
+pub const VK_CONTROL: i32 = 0x11;
+pub const VK_MENU: i32 = 0x12;
+pub const VK_PAUSE: i32 = 0x13;
+pub const VK_CAPITAL: i32 = 0x14;
+pub const VK_ESCAPE: i32 = 0x1B;
+pub const VK_SPACE: i32 = 0x20;
+pub const VK_PRIOR: i32 = 0x21;
+pub const VK_NEXT: i32 = 0x22;
+pub const VK_END: i32 = 0x23;
+pub const VK_HOME: i32 = 0x24;
+pub const VK_LEFT: i32 = 0x25;
+pub const VK_UP: i32 = 0x26;
+pub const VK_RIGHT: i32 = 0x27;
+pub const VK_DOWN: i32 = 0x28;
+pub const VK_SNAPSHOT: i32 = 0x2C;
+pub const VK_INSERT: i32 = 0x2D;
+pub const VK_DELETE: i32 = 0x2E;
+pub const VK_LWIN: i32 = 0x5B;
+pub const VK_RWIN: i32 = 0x5C;
+pub const VK_APPS: i32 = 0x5D;
+pub const VK_SLEEP: i32 = 0x5F;
+pub const VK_NUMPAD0: i32 = 0x60;
+pub const VK_NUMPAD1: i32 = 0x61;
+pub const VK_NUMPAD2: i32 = 0x62;
+pub const VK_NUMPAD3: i32 = 0x63;
+pub const VK_NUMPAD4: i32 = 0x64;
+pub const VK_NUMPAD5: i32 = 0x65;
+pub const VK_NUMPAD6: i32 = 0x66;
+pub const VK_NUMPAD7: i32 = 0x67;
+pub const VK_NUMPAD8: i32 = 0x68;
+pub const VK_NUMPAD9: i32 = 0x69;
+pub const VK_MULTIPLY: i32 = 0x6A;
+pub const VK_ADD: i32 = 0x6B;
+pub const VK_SEPARATOR: i32 = 0x6C;
+pub const VK_SUBTRACT: i32 = 0x6D;
+pub const VK_DECIMAL: i32 = 0x6E;
+pub const VK_DIVIDE: i32 = 0x6F;
+pub const VK_F1: i32 = 0x70;
+pub const VK_F2: i32 = 0x71;
+pub const VK_F3: i32 = 0x72;
+pub const VK_F5: i32 = 0x74;
+pub const VK_F24: i32 = 0x87;
+pub const VK_NUMLOCK: i32 = 0x90;
+pub const VK_SCROLL: i32 = 0x91;
+pub const VK_LSHIFT: i32 = 0xA0;
+pub const VK_RSHIFT: i32 = 0xA1;
+pub const VK_LCONTROL: i32 = 0xA2;
+pub const VK_RCONTROL: i32 = 0xA3;
+pub const VK_LMENU: i32 = 0xA4;
+pub const VK_RMENU: i32 = 0xA5;
+
+/// Translates a Linux evdev key code, X11 key codes are the evdev code plus 8.
+pub fn evdev_to_virtual_key(code: u16) -> Option<i32> {
+    Key::from_evdev(code).map(Key::to_vk)
+}
+
+/// Evdev codes of keys sent with an 0xE0 prefix, with their scan code.
+const EXTENDED_SCAN_CODES: [(u16, u16); 25] = [
+    (96, 0x1C),  // Number pad enter
+    (97, 0x1D),  // Right control
+    (98, 0x35),  // Number pad divide
+    (99, 0x37),  // Print screen
+    (100, 0x38), // Right alt
+    (102, 0x47), // Home
+    (103, 0x48), // Up
+    (104, 0x49), // Page up
+    (105, 0x4B), // Left
+    (106, 0x4D), // Right
+    (107, 0x4F), // End
+    (108, 0x50), // Down
+    (109, 0x51), // Page down
+    (110, 0x52), // Insert
+    (111, 0x53), // Delete
+    (113, 0x20), // Mute
+    (114, 0x2E), // Volume down
+    (115, 0x30), // Volume up
+    (125, 0x5B), // Left Windows
+    (126, 0x5C), // Right Windows
+    (127, 0x5D), // Menu
+    (163, 0x19), // Next track
+    (164, 0x22), // Play / pause
+    (165, 0x10), // Previous track
+    (166, 0x24), // Stop
+];
+
+/// The PC keyboard scan code of an evdev key code. The main block of evdev codes is the scan
+/// code itself.
+pub fn evdev_to_scan_code(code: u16) -> Option<ScanCode> {
+    if (1..=88).contains(&code) {
+        return Some(ScanCode {
+            code,
+            extended: false,
+        });
+    }
+
+    EXTENDED_SCAN_CODES
+        .iter()
+        .find(|(evdev, _)| *evdev == code)
+        .map(|(_, scan_code)| ScanCode {
+            code: *scan_code,
+            extended: true,
+        })
+}
+
+/// The evdev key code at the position of a PC keyboard scan code.
+pub fn scan_code_to_evdev(scan_code: ScanCode) -> Option<u16> {
+    if !scan_code.extended {
+        return (1..=88).contains(&scan_code.code).then_some(scan_code.code);
+    }
+
+    EXTENDED_SCAN_CODES
+        .iter()
+        .find(|(_, code)| *code == scan_code.code)
+        .map(|(evdev, _)| *evdev)
+}
+
+/// Translates an evdev mouse button code like BTN_LEFT.
+pub fn evdev_to_mouse_button(code: u16) -> Option<i32> {
+    match code {
+        0x110 => Some(VK_LBUTTON),
+        0x111 => Some(VK_RBUTTON),
+        0x112 => Some(VK_MBUTTON),
+        0x113 => Some(VK_XBUTTON1),
+        0x114 => Some(VK_XBUTTON2),
+        _ => None,
+    }
+}
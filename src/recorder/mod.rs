@@ -0,0 +1,1072 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+use self::keymap::{
+    VK_CONTROL, VK_LCONTROL, VK_LMENU, VK_LSHIFT, VK_MENU, VK_RCONTROL, VK_RMENU, VK_RSHIFT,
+    VK_SHIFT,
+};
+use self::simplify::simplify_movement;
+use crate::actions::{Action::*, MouseActionButtonState};
+use crate::actions::{KeyState, MouseActionKind::*};
+use crate::{
+    actions::{
+        Action, DragInfo, MonitorInfo, MouseActionButton, MousePointKind, Point, ScanCode,
+        WindowInfo,
+    },
+    monitors::{monitor_at, platform_monitors},
+    settings::Settings,
+    transform::{merge_adjacent_delays, mouse_points},
+    window_manager::{exact_window_info, platform_window_manager},
+};
+
+#[cfg(target_os = "linux")]
+mod evdev;
+pub mod keymap;
+pub mod simplify;
+#[cfg(windows)]
+mod win32;
+#[cfg(target_os = "linux")]
+mod x11;
+
+/// Input reported by a platform hook, keys and mouse buttons use virtual-key codes.
+#[derive(Clone, Copy, Debug)]
+pub enum InputEvent {
+    Key(i32, KeyState, Option<ScanCode>),
+    MouseMove,
+    MouseButton(i32, MouseActionButtonState),
+    /// Positive amounts scroll up, or right if horizontal.
+    Wheel {
+        amount: i32,
+        horizontal: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HookEvent {
+    pub event: InputEvent,
+    /// Cursor position on the screen.
+    pub position: Point,
+    /// Milliseconds on the hook's own clock, which can wrap around.
+    pub time: u32,
+    /// The top level window under the cursor and the screen position of its client area.
+    pub window: Option<(u64, Point)>,
+}
+
+pub trait InputHook {
+    /// Starts sending events from another thread. Returns the cursor position, None if the hook
+    /// only sees relative movement from wherever the cursor was, and the current time on the clock
+    /// events are stamped with.
+    fn start(&mut self, sender: Sender<HookEvent>) -> Result<(Option<Point>, u32), Box<dyn Error>>;
+
+    fn stop(&mut self);
+}
+
+#[cfg(windows)]
+pub fn platform_input_hook() -> Result<Box<dyn InputHook>, Box<dyn Error>> {
+    Ok(Box::new(win32::Win32InputHook::default()))
+}
+
+#[cfg(target_os = "linux")]
+pub fn platform_input_hook() -> Result<Box<dyn InputHook>, Box<dyn Error>> {
+    match x11::XRecordInputHook::connect() {
+        Ok(hook) => Ok(Box::new(hook)),
+        Err(error) => {
+            println!("Recording from input devices, X11 isn't usable: {}", error);
+            Ok(Box::new(evdev::EvdevInputHook::default()))
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn platform_input_hook() -> Result<Box<dyn InputHook>, Box<dyn Error>> {
+    Err("Recording isn't supported on this platform".into())
+}
+
+#[derive(Clone, Copy)]
+enum PauseState {
+    Recording,
+    Paused {
+        since: u32,
+    },
+    /// The pause hotkey was pressed again, recording resumes once it's released.
+    Resuming {
+        since: u32,
+    },
+}
+
+/// A recording in progress, turning hook events into actions as they arrive.
+pub struct Recorder {
+    settings: Settings,
+    hook: Box<dyn InputHook>,
+    receiver: Receiver<HookEvent>,
+    actions: Vec<Action>,
+    /// None if positions are only known relative to each other.
+    start_position: Option<Point>,
+    previous_position: Point,
+    /// Hook time that the next delay is measured from.
+    last_time: u32,
+    held_keys: Vec<i32>,
+    pause: PauseState,
+    windows: HashMap<u64, Option<WindowInfo>>,
+    monitors: Vec<MonitorInfo>,
+}
+
+impl Recorder {
+    pub fn start(settings: &Settings) -> Result<Self, Box<dyn Error>> {
+        Self::with_hook(settings, platform_input_hook()?)
+    }
+
+    fn with_hook(
+        settings: &Settings,
+        mut hook: Box<dyn InputHook>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let (sender, receiver) = channel();
+        let (start_position, start_time) = hook.start(sender)?;
+
+        let windows = if settings.record_window_relative {
+            window_infos()
+                .into_iter()
+                .map(|(id, window_info)| (id, Some(window_info)))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        let monitors = if settings.record_monitor_relative {
+            platform_monitors().unwrap_or_else(|error| {
+                println!(
+                    "Failed to list monitors, recording absolute positions: {}",
+                    error
+                );
+                vec![]
+            })
+        } else {
+            vec![]
+        };
+
+        Ok(Self {
+            settings: settings.clone(),
+            hook,
+            receiver,
+            actions: vec![],
+            start_position,
+            previous_position: start_position.unwrap_or(Point { x: 0, y: 0 }),
+            last_time: start_time,
+            held_keys: vec![],
+            pause: PauseState::Recording,
+            windows,
+            monitors,
+        })
+    }
+
+    /// Records events as they come in until the stop hotkey is pressed.
+    pub fn record_until_stopped(&mut self) {
+        while let Ok(hook_event) = self.receiver.recv() {
+            if self.handle(hook_event) {
+                break;
+            }
+        }
+    }
+
+    /// The actions recorded so far.
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+
+    /// Where the cursor was when recording started, if the hook knows.
+    pub fn start_position(&self) -> Option<Point> {
+        self.start_position
+    }
+
+    pub fn finish(mut self) -> Vec<Action> {
+        self.hook.stop();
+
+        strip_hotkey(&mut self.actions, &self.settings.record_hotkey);
+
+        while let Some(Delay(..)) = self.actions.last() {
+            self.actions.pop();
+        }
+
+        // Drags are collapsed afterwards so their paths are simplified too
+        let actions = simplify_movement(self.actions, &self.settings);
+
+        if self.settings.record_drags {
+            collapse_drags(actions)
+        } else {
+            actions
+        }
+    }
+
+    /// Returns true once the stop hotkey is pressed.
+    fn handle(&mut self, hook_event: HookEvent) -> bool {
+        let key = match hook_event.event {
+            InputEvent::Key(key_code, state, _) => Some((key_code, state == KeyState::Down)),
+            InputEvent::MouseButton(button, state) => {
+                Some((button, state == MouseActionButtonState::Pressed))
+            }
+            _ => None,
+        };
+
+        // Held keys repeat their key down, only the first one presses a hotkey
+        let mut newly_pressed = false;
+        if let Some((key_code, down)) = key {
+            if !down {
+                self.held_keys.retain(|held| *held != key_code);
+            } else if !self.held_keys.contains(&key_code) {
+                self.held_keys.push(key_code);
+                newly_pressed = true;
+            }
+        }
+
+        let recording = matches!(self.pause, PauseState::Recording);
+
+        if newly_pressed && self.hotkey_held(&self.settings.stop_hotkey) {
+            if recording {
+                strip_hotkey(&mut self.actions, &self.settings.stop_hotkey);
+            }
+            return true;
+        }
+
+        let pause_pressed = newly_pressed && self.hotkey_held(&self.settings.pause_hotkey);
+
+        match self.pause {
+            PauseState::Recording if pause_pressed => {
+                strip_hotkey(&mut self.actions, &self.settings.pause_hotkey);
+                self.pause = PauseState::Paused {
+                    since: hook_event.time,
+                };
+            }
+            PauseState::Recording => self.record(hook_event),
+            PauseState::Paused { since } if pause_pressed => {
+                self.pause = PauseState::Resuming { since };
+            }
+            PauseState::Paused { .. } => {}
+            // Resume once the hotkey is let go so releasing it isn't recorded
+            PauseState::Resuming { since } => {
+                let released = !self
+                    .settings
+                    .pause_hotkey
+                    .iter()
+                    .any(|key| self.held_keys.iter().any(|held| key_matches(*key, *held)));
+
+                if released {
+                    // Leave the paused time out of the next delay
+                    let paused_for = elapsed(since, hook_event.time);
+                    self.last_time = self.last_time.wrapping_add(paused_for);
+                    self.pause = PauseState::Recording;
+                }
+            }
+        }
+
+        false
+    }
+
+    fn record(&mut self, hook_event: HookEvent) {
+        let action = match hook_event.event {
+            InputEvent::Key(key_code, state, scan_code) => Keyboard(key_code, state, scan_code),
+            InputEvent::MouseMove if !self.settings.record_mouse_movement => return,
+            InputEvent::MouseMove => Mouse(Moved(self.point_kind(&hook_event))),
+            InputEvent::MouseButton(button, state) => Mouse(Button(MouseActionButton {
+                point: Some(self.point_kind(&hook_event)),
+                button,
+                state,
+            })),
+            InputEvent::Wheel { amount, horizontal } => {
+                let point = Some(self.point_kind(&hook_event));
+                Mouse(if horizontal {
+                    HorizontalWheel(amount, point)
+                } else {
+                    Wheel(amount, point)
+                })
+            }
+        };
+
+        let delay = elapsed(self.last_time, hook_event.time);
+        if delay != 0 {
+            match self.actions.last_mut() {
+                Some(Delay(previous, _)) => *previous += delay,
+                _ => self.actions.push(Delay(delay, Default::default())),
+            }
+        }
+        self.last_time = self.last_time.wrapping_add(delay);
+
+        self.actions.push(action);
+    }
+
+    fn point_kind(&mut self, hook_event: &HookEvent) -> MousePointKind {
+        let point = hook_event.position;
+        // Offsets are from the last position whatever kind of point it was recorded as
+        let previous = std::mem::replace(&mut self.previous_position, point);
+
+        if let Some((window, client_origin)) = hook_event.window {
+            if let Some(window_info) = self.window_info(window) {
+                return MousePointKind::Window(
+                    window_info,
+                    Point {
+                        x: point.x - client_origin.x,
+                        y: point.y - client_origin.y,
+                    },
+                );
+            }
+        }
+
+        // Without a start position only the offsets between positions mean anything
+        if self.settings.record_mouse_offsets || self.start_position.is_none() {
+            MousePointKind::By(Point {
+                x: point.x - previous.x,
+                y: point.y - previous.y,
+            })
+        } else if let Some(monitor) = monitor_at(&self.monitors, point) {
+            MousePointKind::Monitor(
+                monitor.clone(),
+                Point {
+                    x: point.x - monitor.rect.x,
+                    y: point.y - monitor.rect.y,
+                },
+            )
+        } else {
+            MousePointKind::To(point)
+        }
+    }
+
+    fn window_info(&mut self, window: u64) -> Option<WindowInfo> {
+        if !self.settings.record_window_relative {
+            return None;
+        }
+
+        // Windows opened during the recording aren't listed yet
+        if !self.windows.contains_key(&window) {
+            for (id, window_info) in window_infos() {
+                self.windows.insert(id, Some(window_info));
+            }
+            self.windows.entry(window).or_insert(None);
+        }
+
+        self.windows[&window].clone()
+    }
+
+    fn hotkey_held(&self, hotkey: &[i32]) -> bool {
+        !hotkey.is_empty()
+            && hotkey
+                .iter()
+                .all(|key| self.held_keys.iter().any(|held| key_matches(*key, *held)))
+    }
+}
+
+/// Records until the stop hotkey is pressed. Returns the recorded actions and where the cursor was
+/// when recording started, if that's known.
+pub fn record_actions(settings: &Settings) -> (Vec<Action>, Option<Point>) {
+    let mut recorder = match Recorder::start(settings) {
+        Ok(recorder) => recorder,
+        Err(error) => {
+            println!("Failed to start recording: {}", error);
+            return (vec![], None);
+        }
+    };
+
+    recorder.record_until_stopped();
+
+    let start_position = recorder.start_position();
+    (recorder.finish(), start_position)
+}
+
+/// Where playback leaves the cursor, as far as it can be told without playing.
+#[derive(Clone, Copy)]
+enum CursorState {
+    At(Point),
+    /// Moved this far from wherever it was before.
+    MovedBy(Point),
+    /// Somewhere in a window that's only found during playback.
+    Unknown,
+}
+
+impl CursorState {
+    /// Follows the cursor through `mouse_points`, playback moves it to each of them in turn, offsets
+    /// of clicks and wheels included.
+    fn after_actions(self, actions: &[Action]) -> Self {
+        use CursorState::*;
+        actions
+            .iter()
+            .flat_map(mouse_points)
+            .fold(self, |cursor, point| match (cursor, point) {
+                (_, MousePointKind::To(point)) => At(*point),
+                // Recorded and spliced in with the same monitors connected
+                (_, MousePointKind::Monitor(monitor, point)) => At(Point {
+                    x: monitor.rect.x + point.x,
+                    y: monitor.rect.y + point.y,
+                }),
+                (_, MousePointKind::Window(..)) | (Unknown, _) => Unknown,
+                (At(at), MousePointKind::By(by)) => At(Point {
+                    x: at.x + by.x,
+                    y: at.y + by.y,
+                }),
+                (MovedBy(offset), MousePointKind::By(by)) => MovedBy(Point {
+                    x: offset.x + by.x,
+                    y: offset.y + by.y,
+                }),
+            })
+    }
+}
+
+fn starts_with_offset(actions: &[Action]) -> bool {
+    matches!(
+        actions.iter().flat_map(mouse_points).next(),
+        Some(MousePointKind::By(_))
+    )
+}
+
+/// Inserts a recording into the action list. Offsets in the recording are measured from where the
+/// cursor was when recording started, and offsets after the index from where the actions before it
+/// leave the cursor, so moves are added around the recording to keep both correct. Recordings
+/// without a start position carry on from wherever the cursor is.
+pub fn splice_recording(
+    action_list: &mut Vec<Action>,
+    index: usize,
+    recording: Vec<Action>,
+    start_position: Option<Point>,
+) {
+    let before = CursorState::MovedBy(Point { x: 0, y: 0 }).after_actions(&action_list[..index]);
+    let offset_move =
+        |x, y| (x != 0 || y != 0).then_some(Mouse(Moved(MousePointKind::By(Point { x, y }))));
+
+    let mut spliced = vec![];
+
+    if starts_with_offset(&recording) {
+        match (before, start_position) {
+            (CursorState::At(at), Some(start)) => {
+                spliced.extend(offset_move(start.x - at.x, start.y - at.y))
+            }
+            (CursorState::Unknown, Some(start)) => {
+                spliced.push(Mouse(Moved(MousePointKind::To(start))))
+            }
+            // Only offsets came before, so the recording carries on from wherever they end
+            (CursorState::MovedBy(_), _) | (_, None) => {}
+        }
+    }
+
+    // Offsets after the recording expect the cursor back where the actions before it left it
+    let restore = if starts_with_offset(&action_list[index..]) {
+        let end = start_position.map_or(CursorState::Unknown, |start| {
+            CursorState::At(start).after_actions(&recording)
+        });
+        let moved = CursorState::MovedBy(Point { x: 0, y: 0 }).after_actions(&recording);
+
+        match (before, end, moved) {
+            (CursorState::At(at), CursorState::At(end), _) => {
+                offset_move(at.x - end.x, at.y - end.y)
+            }
+            (CursorState::At(at), ..) => Some(Mouse(Moved(MousePointKind::To(at)))),
+            (CursorState::MovedBy(_), _, CursorState::MovedBy(moved)) => {
+                offset_move(-moved.x, -moved.y)
+            }
+            _ => None,
+        }
+    } else {
+        None
+    };
+
+    spliced.extend(recording);
+    spliced.extend(restore);
+
+    action_list.splice(index..index, spliced);
+}
+
+/// Turns pressing a button, moving the mouse and releasing the button into a single drag.
+fn collapse_drags(actions: Vec<Action>) -> Vec<Action> {
+    let mut collapsed = vec![];
+    let mut index = 0;
+
+    while index < actions.len() {
+        match drag_at(&actions[index..]) {
+            Some((drag_info, length)) => {
+                collapsed.push(Drag(drag_info));
+                index += length;
+            }
+            None => {
+                collapsed.push(actions[index].clone());
+                index += 1;
+            }
+        }
+    }
+
+    collapsed
+}
+
+/// The drag starting at the first action and how many actions it replaces.
+fn drag_at(actions: &[Action]) -> Option<(DragInfo, usize)> {
+    let Some(Mouse(Button(MouseActionButton {
+        point: Some(from),
+        button,
+        state: MouseActionButtonState::Pressed,
+    }))) = actions.first()
+    else {
+        return None;
+    };
+
+    // Offset from the drag start after moving to the point
+    let offset = |point: &MousePointKind, current: Point| match (from, point) {
+        (_, MousePointKind::By(by)) => Some(Point {
+            x: current.x + by.x,
+            y: current.y + by.y,
+        }),
+        (MousePointKind::To(from), MousePointKind::To(point)) => Some(Point {
+            x: point.x - from.x,
+            y: point.y - from.y,
+        }),
+        (MousePointKind::Window(from_window, from), MousePointKind::Window(window, point))
+            if from_window.title == window.title && from_window.class == window.class =>
+        {
+            Some(Point {
+                x: point.x - from.x,
+                y: point.y - from.y,
+            })
+        }
+        (MousePointKind::Monitor(from_monitor, from), MousePointKind::Monitor(monitor, point))
+            if from_monitor == monitor =>
+        {
+            Some(Point {
+                x: point.x - from.x,
+                y: point.y - from.y,
+            })
+        }
+        _ => None,
+    };
+
+    let mut current = Point { x: 0, y: 0 };
+    let mut duration = 0;
+    let mut path = vec![];
+
+    for (index, action) in actions.iter().enumerate().skip(1) {
+        match action {
+            Delay(delay, _) => duration += delay,
+            Mouse(Moved(point)) => {
+                current = offset(point, current)?;
+                path.push(current);
+            }
+            Mouse(Button(MouseActionButton {
+                point: Some(to),
+                button: released_button,
+                state: MouseActionButtonState::Released,
+            })) if released_button == button => {
+                let to_offset = offset(to, current)?;
+
+                // The release happens where the last move ended
+                while path.last() == Some(&to_offset) {
+                    path.pop();
+                }
+
+                if to_offset == (Point { x: 0, y: 0 }) && path.is_empty() {
+                    return None;
+                }
+
+                let to = match from {
+                    MousePointKind::By(_) => MousePointKind::By(to_offset),
+                    _ => from.with_point(Point {
+                        x: from.x() + to_offset.x,
+                        y: from.y() + to_offset.y,
+                    }),
+                };
+
+                return Some((
+                    DragInfo {
+                        button: *button,
+                        from: from.clone(),
+                        to,
+                        duration,
+                        path,
+                    },
+                    index + 1,
+                ));
+            }
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+fn window_infos() -> HashMap<u64, WindowInfo> {
+    let windows = platform_window_manager().and_then(|window_manager| window_manager.windows());
+
+    match windows {
+        Ok(windows) => windows
+            .iter()
+            .map(|window| (window.id, exact_window_info(window)))
+            .collect(),
+        Err(error) => {
+            println!(
+                "Failed to list windows, recording absolute positions: {}",
+                error
+            );
+            HashMap::new()
+        }
+    }
+}
+
+/// Removes the presses that formed the hotkey, every key of it is still held when it triggers, and
+/// the releases at the start of keys that were held from before recording, like the record hotkey.
+fn strip_hotkey(actions: &mut Vec<Action>, hotkey: &[i32]) {
+    let key_event = |action: &Action| match action {
+        Keyboard(key_code, state, _) => Some((*key_code, *state == KeyState::Down)),
+        Mouse(Button(MouseActionButton { button, state, .. })) => {
+            Some((*button, *state == MouseActionButtonState::Pressed))
+        }
+        _ => None,
+    };
+
+    for &key in hotkey {
+        let held_since = actions
+            .iter()
+            .rposition(|action| {
+                matches!(key_event(action), Some((key_code, false)) if key_matches(key, key_code))
+            })
+            .map_or(0, |index| index + 1);
+        let pressed_at = actions
+            .iter()
+            .position(|action| {
+                matches!(key_event(action), Some((key_code, true)) if key_matches(key, key_code))
+            })
+            .unwrap_or(actions.len());
+
+        let mut index = 0;
+        actions.retain(|action| {
+            index += 1;
+            match key_event(action) {
+                Some((key_code, true)) if key_matches(key, key_code) => index <= held_since,
+                Some((key_code, false)) if key_matches(key, key_code) => index > pressed_at,
+                _ => true,
+            }
+        });
+    }
+
+    // Delays around the removed presses end up next to each other
+    merge_adjacent_delays(actions);
+}
+
+/// Milliseconds from one hook time to another. Hooks reading several devices can send events a
+/// little out of order, an earlier time counts as no time passing.
+fn elapsed(since: u32, time: u32) -> u32 {
+    let elapsed = time.wrapping_sub(since);
+
+    // The clock wrapping around is fine, differences this large only come from going backwards
+    if elapsed > u32::MAX / 2 {
+        0
+    } else {
+        elapsed
+    }
+}
+
+/// Hooks report left and right modifiers, hotkeys can use the generic ones.
+fn key_matches(hotkey: i32, key_code: i32) -> bool {
+    match hotkey {
+        VK_CONTROL => matches!(key_code, VK_CONTROL | VK_LCONTROL | VK_RCONTROL),
+        VK_SHIFT => matches!(key_code, VK_SHIFT | VK_LSHIFT | VK_RSHIFT),
+        VK_MENU => matches!(key_code, VK_MENU | VK_LMENU | VK_RMENU),
+        _ => hotkey == key_code,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recorder::keymap::{VK_F5, VK_LBUTTON, VK_RBUTTON};
+
+    struct FakeHook;
+
+    impl InputHook for FakeHook {
+        fn start(
+            &mut self,
+            _sender: Sender<HookEvent>,
+        ) -> Result<(Option<Point>, u32), Box<dyn Error>> {
+            Ok((Some(Point { x: 0, y: 0 }), 0))
+        }
+
+        fn stop(&mut self) {}
+    }
+
+    fn recorder(settings: Settings) -> Recorder {
+        Recorder::with_hook(&settings, Box::new(FakeHook)).unwrap()
+    }
+
+    fn button(button: i32, state: MouseActionButtonState, x: i32, y: i32) -> Action {
+        Mouse(Button(MouseActionButton {
+            point: Some(MousePointKind::To(Point { x, y })),
+            button,
+            state,
+        }))
+    }
+
+    fn moved_to(x: i32, y: i32) -> Action {
+        Mouse(Moved(MousePointKind::To(Point { x, y })))
+    }
+
+    fn moved_by(x: i32, y: i32) -> Action {
+        Mouse(Moved(MousePointKind::By(Point { x, y })))
+    }
+
+    fn event(event: InputEvent, x: i32, y: i32, time: u32) -> HookEvent {
+        HookEvent {
+            event,
+            position: Point { x, y },
+            time,
+            window: None,
+        }
+    }
+
+    #[test]
+    fn offsets_follow_moves_recorded_in_windows() {
+        let mut recorder = recorder(Settings {
+            record_mouse_offsets: true,
+            record_monitor_relative: false,
+            ..Default::default()
+        });
+        recorder.settings.record_window_relative = true;
+        recorder.windows.insert(
+            1,
+            Some(WindowInfo {
+                title: "Notepad".into(),
+                class: "Notepad".into(),
+            }),
+        );
+
+        recorder.handle(HookEvent {
+            window: Some((1, Point { x: 50, y: 50 })),
+            ..event(InputEvent::MouseMove, 100, 100, 0)
+        });
+        recorder.handle(event(InputEvent::MouseMove, 110, 105, 0));
+
+        assert_eq!(
+            format!("{:?}", recorder.actions()),
+            format!(
+                "{:?}",
+                [
+                    Mouse(Moved(MousePointKind::Window(
+                        WindowInfo {
+                            title: "Notepad".into(),
+                            class: "Notepad".into(),
+                        },
+                        Point { x: 50, y: 50 }
+                    ))),
+                    Mouse(Moved(MousePointKind::By(Point { x: 10, y: 5 })))
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn collapses_pressing_moving_and_releasing_into_a_drag() {
+        let actions = vec![
+            button(VK_LBUTTON, MouseActionButtonState::Pressed, 10, 10),
+            Delay(5, Default::default()),
+            moved_to(15, 12),
+            Delay(5, Default::default()),
+            moved_to(20, 20),
+            button(VK_LBUTTON, MouseActionButtonState::Released, 20, 20),
+            Keyboard(0x41, KeyState::Pressed, None),
+        ];
+
+        assert_eq!(
+            format!("{:?}", collapse_drags(actions)),
+            format!(
+                "{:?}",
+                [
+                    Drag(DragInfo {
+                        button: VK_LBUTTON,
+                        from: MousePointKind::To(Point { x: 10, y: 10 }),
+                        to: MousePointKind::To(Point { x: 20, y: 20 }),
+                        duration: 10,
+                        path: vec![Point { x: 5, y: 2 }],
+                    }),
+                    Keyboard(0x41, KeyState::Pressed, None),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn keeps_clicks_without_movement() {
+        let actions = vec![
+            button(VK_LBUTTON, MouseActionButtonState::Pressed, 10, 10),
+            Delay(80, Default::default()),
+            button(VK_LBUTTON, MouseActionButtonState::Released, 10, 10),
+        ];
+
+        assert!(drag_at(&actions).is_none());
+        assert_eq!(
+            format!("{:?}", collapse_drags(actions.clone())),
+            format!("{:?}", actions)
+        );
+    }
+
+    #[test]
+    fn keeps_presses_without_a_matching_release() {
+        let actions = vec![
+            button(VK_LBUTTON, MouseActionButtonState::Released, 0, 0),
+            button(VK_LBUTTON, MouseActionButtonState::Pressed, 10, 10),
+            moved_to(20, 20),
+            button(VK_RBUTTON, MouseActionButtonState::Released, 20, 20),
+            moved_to(30, 30),
+        ];
+
+        assert!(drag_at(&actions[1..]).is_none());
+        assert_eq!(
+            format!("{:?}", collapse_drags(actions.clone())),
+            format!("{:?}", actions)
+        );
+    }
+
+    #[test]
+    fn strips_hotkeys_from_both_ends() {
+        let mut actions = vec![
+            // Still held from starting the recording
+            Keyboard(VK_LCONTROL, KeyState::Up, None),
+            Delay(10, Default::default()),
+            Keyboard(0x51, KeyState::Down, None),
+            Keyboard(0x51, KeyState::Up, None),
+            Delay(10, Default::default()),
+            Keyboard(VK_LCONTROL, KeyState::Down, None),
+            Keyboard(0x41, KeyState::Pressed, None),
+            Keyboard(VK_LCONTROL, KeyState::Up, None),
+            Delay(20, Default::default()),
+            // Pressing Ctrl+Q
+            Keyboard(VK_LCONTROL, KeyState::Down, None),
+            Delay(5, Default::default()),
+            Keyboard(0x51, KeyState::Down, None),
+        ];
+
+        strip_hotkey(&mut actions, &[VK_CONTROL, 0x51]);
+
+        assert_eq!(
+            format!("{:?}", actions),
+            format!(
+                "{:?}",
+                [
+                    Delay(10, Default::default()),
+                    Keyboard(0x51, KeyState::Down, None),
+                    Keyboard(0x51, KeyState::Up, None),
+                    Delay(10, Default::default()),
+                    Keyboard(VK_LCONTROL, KeyState::Down, None),
+                    Keyboard(0x41, KeyState::Pressed, None),
+                    Keyboard(VK_LCONTROL, KeyState::Up, None),
+                    Delay(25, Default::default()),
+                ]
+            )
+        );
+    }
+
+    fn spliced(
+        mut action_list: Vec<Action>,
+        index: usize,
+        recording: Vec<Action>,
+        start_position: Option<Point>,
+    ) -> String {
+        splice_recording(&mut action_list, index, recording, start_position);
+        format!("{:?}", action_list)
+    }
+
+    #[test]
+    fn splices_recordings_in_at_the_start() {
+        assert_eq!(
+            spliced(
+                vec![moved_by(10, 0)],
+                0,
+                vec![moved_by(5, 5)],
+                Some(Point { x: 50, y: 50 })
+            ),
+            // Only offsets come before, so the recording's own movement is undone after it
+            format!("{:?}", [moved_by(5, 5), moved_by(-5, -5), moved_by(10, 0)])
+        );
+    }
+
+    #[test]
+    fn splices_recordings_in_the_middle() {
+        let action_list = vec![
+            moved_to(100, 100),
+            Delay(10, Default::default()),
+            moved_by(10, 0),
+        ];
+
+        assert_eq!(
+            spliced(
+                action_list.clone(),
+                1,
+                vec![moved_by(5, 5)],
+                Some(Point { x: 50, y: 50 })
+            ),
+            format!(
+                "{:?}",
+                [
+                    moved_to(100, 100),
+                    moved_by(-50, -50),
+                    moved_by(5, 5),
+                    moved_by(45, 45),
+                    Delay(10, Default::default()),
+                    moved_by(10, 0),
+                ]
+            )
+        );
+        // Without a start position the recording carries on from the cursor, and the cursor is
+        // put back afterwards
+        assert_eq!(
+            spliced(action_list, 1, vec![moved_by(5, 5)], None),
+            format!(
+                "{:?}",
+                [
+                    moved_to(100, 100),
+                    moved_by(5, 5),
+                    moved_to(100, 100),
+                    Delay(10, Default::default()),
+                    moved_by(10, 0),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn follows_offsets_of_clicks_before_the_recording() {
+        let click_by = |x, y| {
+            Mouse(Button(MouseActionButton {
+                point: Some(MousePointKind::By(Point { x, y })),
+                button: VK_LBUTTON,
+                state: MouseActionButtonState::Clicked,
+            }))
+        };
+
+        assert_eq!(
+            spliced(
+                vec![moved_to(100, 100), click_by(10, 0)],
+                2,
+                vec![moved_by(5, 5)],
+                Some(Point { x: 50, y: 50 })
+            ),
+            format!(
+                "{:?}",
+                [
+                    moved_to(100, 100),
+                    click_by(10, 0),
+                    moved_by(-60, -50),
+                    moved_by(5, 5),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn splices_recordings_in_at_the_end() {
+        assert_eq!(
+            spliced(
+                vec![moved_to(100, 100), moved_by(10, 0)],
+                2,
+                vec![moved_by(5, 5)],
+                Some(Point { x: 50, y: 50 })
+            ),
+            format!(
+                "{:?}",
+                [
+                    moved_to(100, 100),
+                    moved_by(10, 0),
+                    moved_by(-60, -50),
+                    moved_by(5, 5),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn leaves_recordings_replacing_the_list_as_they_are() {
+        let recording = vec![
+            moved_by(5, 5),
+            Delay(10, Default::default()),
+            moved_to(1, 2),
+        ];
+
+        assert_eq!(
+            spliced(vec![], 0, recording.clone(), Some(Point { x: 50, y: 50 })),
+            format!("{:?}", recording)
+        );
+    }
+
+    #[test]
+    fn leaves_out_what_happens_while_paused() {
+        let mut recorder = recorder(Settings {
+            pause_hotkey: vec![VK_CONTROL, VK_F5],
+            ..Default::default()
+        });
+        let key = |key_code, state, time| event(InputEvent::Key(key_code, state, None), 0, 0, time);
+
+        for hook_event in [
+            key(0x41, KeyState::Down, 100),
+            key(0x41, KeyState::Up, 110),
+            // Pausing
+            key(VK_LCONTROL, KeyState::Down, 200),
+            key(VK_F5, KeyState::Down, 205),
+            key(VK_F5, KeyState::Up, 210),
+            key(VK_LCONTROL, KeyState::Up, 215),
+            key(0x42, KeyState::Down, 5000),
+            key(0x42, KeyState::Up, 5010),
+            // Resuming once both keys are let go
+            key(VK_LCONTROL, KeyState::Down, 9000),
+            key(VK_F5, KeyState::Down, 9005),
+            key(VK_F5, KeyState::Up, 9010),
+            key(VK_LCONTROL, KeyState::Up, 9015),
+            key(0x43, KeyState::Down, 9100),
+        ] {
+            assert!(!recorder.handle(hook_event));
+        }
+
+        // 180 milliseconds passed between A and C while recording
+        assert_eq!(
+            format!("{:?}", recorder.actions()),
+            format!(
+                "{:?}",
+                [
+                    Delay(100, Default::default()),
+                    Keyboard(0x41, KeyState::Down, None),
+                    Delay(10, Default::default()),
+                    Keyboard(0x41, KeyState::Up, None),
+                    Delay(180, Default::default()),
+                    Keyboard(0x43, KeyState::Down, None),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn ignores_times_going_backwards() {
+        let mut recorder = recorder(Settings::default());
+        let key = |key_code, time| {
+            event(
+                InputEvent::Key(key_code, KeyState::Pressed, None),
+                0,
+                0,
+                time,
+            )
+        };
+
+        // Queued before recording started, then sent out of order by different devices
+        recorder.handle(key(0x41, u32::MAX - 5));
+        recorder.handle(key(0x42, 100));
+        recorder.handle(key(0x43, 95));
+        recorder.handle(key(0x44, 120));
+
+        assert_eq!(
+            format!("{:?}", recorder.actions()),
+            format!(
+                "{:?}",
+                [
+                    Keyboard(0x41, KeyState::Pressed, None),
+                    Delay(100, Default::default()),
+                    Keyboard(0x42, KeyState::Pressed, None),
+                    Keyboard(0x43, KeyState::Pressed, None),
+                    Delay(20, Default::default()),
+                    Keyboard(0x44, KeyState::Pressed, None),
+                ]
+            )
+        );
+        // The clock wrapping around still counts
+        assert_eq!(elapsed(u32::MAX - 5, 10), 16);
+    }
+}
@@ -0,0 +1,357 @@
+use std::{fmt::Display, ops::RangeInclusive};
+
+use serde::*;
+use strum_macros::EnumIter;
+
+use crate::{
+    actions::{Action, Action::*, DelayJitter, MouseActionKind::*, MousePointKind, Point},
+    settings::Settings,
+};
+
+/// How runs of recorded mouse movement are thinned out.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default, Debug, EnumIter)]
+pub enum MouseSimplification {
+    #[default]
+    None,
+    /// Keeps at most one movement per interval.
+    Sampling,
+    /// Drops movements closer than the tolerance to the last one kept.
+    MinimumDistance,
+    /// Ramer-Douglas-Peucker, keeps the movements needed to stay within the tolerance of the path.
+    PathTolerance,
+}
+
+impl Display for MouseSimplification {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::None => "No Simplification",
+                Self::Sampling => "Time Sampling",
+                Self::MinimumDistance => "Minimum Distance",
+                Self::PathTolerance => "Path Tolerance",
+            }
+        )
+    }
+}
+
+struct Movement {
+    /// Relative to the same origin as the rest of the run.
+    position: Point,
+    /// Milliseconds since the start of the run.
+    time: u32,
+    index: usize,
+}
+
+/// Thins out every run of mouse movement with the strategy in the settings.
+pub fn simplify_movement(actions: Vec<Action>, settings: &Settings) -> Vec<Action> {
+    simplify_movement_with(actions, settings.mouse_simplification, settings)
+}
+
+/// Thins out every run of mouse movement with the given strategy and the amounts in the settings.
+pub fn simplify_movement_with(
+    actions: Vec<Action>,
+    simplification: MouseSimplification,
+    settings: &Settings,
+) -> Vec<Action> {
+    if simplification == MouseSimplification::None {
+        return actions;
+    }
+
+    let mut simplified = vec![];
+    let mut index = 0;
+
+    while index < actions.len() {
+        let run = movement_run(&actions[index..]);
+
+        if run.len() > 2 {
+            let kept = match simplification {
+                MouseSimplification::None => (0..run.len()).collect(),
+                MouseSimplification::Sampling => sample(&run, settings.simplify_interval),
+                MouseSimplification::MinimumDistance => {
+                    minimum_distance(&run, settings.simplify_tolerance)
+                }
+                MouseSimplification::PathTolerance => {
+                    douglas_peucker(&run, settings.simplify_tolerance)
+                }
+            };
+
+            rebuild_run(&actions[index..], &run, &kept, &mut simplified);
+            index += run.last().unwrap().index + 1;
+        } else {
+            simplified.push(actions[index].clone());
+            index += 1;
+        }
+    }
+
+    simplified
+}
+
+/// The rows of movement and delays around a row, for simplifying without a selection.
+pub fn movement_rows(actions: &[Action], row: usize) -> RangeInclusive<usize> {
+    let is_movement = |action: &Action| matches!(action, Mouse(Moved(_)) | Delay(..));
+
+    if !actions.get(row).is_some_and(is_movement) {
+        return row..=row;
+    }
+
+    let start = actions[..row]
+        .iter()
+        .rposition(|action| !is_movement(action))
+        .map_or(0, |index| index + 1);
+    let end = actions[row..]
+        .iter()
+        .position(|action| !is_movement(action))
+        .map_or(actions.len() - 1, |index| row + index - 1);
+
+    start..=end
+}
+
+/// The movements in the run starting at the first action, they're all the same kind of point so
+/// their positions can be compared.
+fn movement_run(actions: &[Action]) -> Vec<Movement> {
+    let Some(Mouse(Moved(first))) = actions.first() else {
+        return vec![];
+    };
+
+    let mut run = vec![];
+    let mut time = 0;
+    let mut offset = Point { x: 0, y: 0 };
+
+    for (index, action) in actions.iter().enumerate() {
+        let position = match (first, action) {
+            (_, Delay(delay, _)) => {
+                time += delay;
+                continue;
+            }
+            (MousePointKind::To(_), Mouse(Moved(MousePointKind::To(point)))) => *point,
+            (MousePointKind::By(_), Mouse(Moved(MousePointKind::By(by)))) => {
+                offset = Point {
+                    x: offset.x + by.x,
+                    y: offset.y + by.y,
+                };
+                offset
+            }
+            (
+                MousePointKind::Window(first_window, _),
+                Mouse(Moved(MousePointKind::Window(window, point))),
+            ) if first_window.title == window.title && first_window.class == window.class => *point,
+            (
+                MousePointKind::Monitor(first_monitor, _),
+                Mouse(Moved(MousePointKind::Monitor(monitor, point))),
+            ) if first_monitor == monitor => *point,
+            _ => break,
+        };
+
+        run.push(Movement {
+            position,
+            time,
+            index,
+        });
+    }
+
+    run
+}
+
+/// Writes the kept movements of a run with the delays between them added up.
+fn rebuild_run(actions: &[Action], run: &[Movement], kept: &[usize], simplified: &mut Vec<Action>) {
+    simplified.push(actions[run[kept[0]].index].clone());
+
+    for pair in kept.windows(2) {
+        let (previous, movement) = (&run[pair[0]], &run[pair[1]]);
+
+        let delay = movement.time - previous.time;
+        if delay > 0 {
+            let jitter = actions[previous.index..movement.index]
+                .iter()
+                .find_map(|action| match action {
+                    Delay(_, jitter) if *jitter != DelayJitter::None => Some(*jitter),
+                    _ => None,
+                })
+                .unwrap_or_default();
+
+            simplified.push(Delay(delay, jitter));
+        }
+
+        simplified.push(match &actions[movement.index] {
+            Mouse(Moved(MousePointKind::By(_))) => Mouse(Moved(MousePointKind::By(Point {
+                x: movement.position.x - previous.position.x,
+                y: movement.position.y - previous.position.y,
+            }))),
+            action => action.clone(),
+        });
+    }
+}
+
+fn distance(a: Point, b: Point) -> f32 {
+    (((a.x - b.x) as f32).powi(2) + ((a.y - b.y) as f32).powi(2)).sqrt()
+}
+
+/// Keeps the first movement of every interval, and the last movement of the run.
+fn sample(run: &[Movement], interval: u32) -> Vec<usize> {
+    let mut kept = vec![0];
+
+    for (index, movement) in run.iter().enumerate().skip(1) {
+        let last_kept = &run[*kept.last().unwrap()];
+        if movement.time - last_kept.time >= interval || index == run.len() - 1 {
+            kept.push(index);
+        }
+    }
+
+    kept
+}
+
+fn minimum_distance(run: &[Movement], tolerance: f32) -> Vec<usize> {
+    let mut kept = vec![0];
+
+    for (index, movement) in run.iter().enumerate().skip(1) {
+        let last_kept = &run[*kept.last().unwrap()];
+        if distance(movement.position, last_kept.position) >= tolerance || index == run.len() - 1 {
+            kept.push(index);
+        }
+    }
+
+    kept
+}
+
+fn douglas_peucker(run: &[Movement], tolerance: f32) -> Vec<usize> {
+    let mut keep = vec![false; run.len()];
+    keep[0] = true;
+    keep[run.len() - 1] = true;
+
+    let mut sections = vec![(0, run.len() - 1)];
+
+    while let Some((start, end)) = sections.pop() {
+        let (a, b) = (run[start].position, run[end].position);
+
+        let farthest = (start + 1..end)
+            .map(|index| (index, distance_to_segment(run[index].position, a, b)))
+            .max_by(|(_, first), (_, second)| first.total_cmp(second));
+
+        if let Some((index, farthest_distance)) = farthest {
+            if farthest_distance > tolerance {
+                keep[index] = true;
+                sections.push((start, index));
+                sections.push((index, end));
+            }
+        }
+    }
+
+    (0..run.len()).filter(|index| keep[*index]).collect()
+}
+
+fn distance_to_segment(point: Point, a: Point, b: Point) -> f32 {
+    let (dx, dy) = ((b.x - a.x) as f32, (b.y - a.y) as f32);
+    let length_squared = dx * dx + dy * dy;
+
+    if length_squared == 0.0 {
+        return distance(point, a);
+    }
+
+    let t = (((point.x - a.x) as f32 * dx + (point.y - a.y) as f32 * dy) / length_squared)
+        .clamp(0.0, 1.0);
+    let (closest_x, closest_y) = (a.x as f32 + t * dx, a.y as f32 + t * dy);
+
+    ((point.x as f32 - closest_x).powi(2) + (point.y as f32 - closest_y).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actions::{MouseActionButton, MouseActionButtonState},
+        recorder::keymap::VK_LBUTTON,
+    };
+
+    fn click(x: i32, y: i32) -> Action {
+        Mouse(Button(MouseActionButton {
+            point: Some(MousePointKind::To(Point { x, y })),
+            button: VK_LBUTTON,
+            state: MouseActionButtonState::Clicked,
+        }))
+    }
+
+    /// Two clicks with an L shaped path between them, moving 10 pixels every 10 milliseconds.
+    fn recording() -> Vec<Action> {
+        let path = (1..=5)
+            .map(|x| Point { x: x * 10, y: 0 })
+            .chain((1..=5).map(|y| Point { x: 50, y: y * 10 }));
+
+        let mut actions = vec![click(0, 0)];
+        for point in path {
+            actions.push(Delay(10, DelayJitter::None));
+            actions.push(Mouse(Moved(MousePointKind::To(point))));
+        }
+        actions.push(click(50, 50));
+
+        actions
+    }
+
+    /// Simplifies the recording and checks the clicks and time are kept, returns the movements.
+    fn simplified(simplification: MouseSimplification, settings: Settings) -> Vec<(i32, i32)> {
+        let actions = simplify_movement_with(recording(), simplification, &settings);
+
+        assert_eq!(
+            format!("{:?}", (actions.first(), actions.last())),
+            format!("{:?}", (Some(click(0, 0)), Some(click(50, 50))))
+        );
+        assert_eq!(
+            actions
+                .iter()
+                .map(|action| match action {
+                    Delay(delay, _) => *delay,
+                    _ => 0,
+                })
+                .sum::<u32>(),
+            100
+        );
+
+        actions
+            .iter()
+            .filter_map(|action| match action {
+                Mouse(Moved(MousePointKind::To(point))) => Some((point.x, point.y)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn samples_one_movement_per_interval() {
+        let settings = Settings {
+            simplify_interval: 50,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            simplified(MouseSimplification::Sampling, settings),
+            [(10, 0), (50, 10), (50, 50)]
+        );
+    }
+
+    #[test]
+    fn drops_movements_closer_than_the_minimum_distance() {
+        let settings = Settings {
+            simplify_tolerance: 25.0,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            simplified(MouseSimplification::MinimumDistance, settings),
+            [(10, 0), (40, 0), (50, 30), (50, 50)]
+        );
+    }
+
+    #[test]
+    fn keeps_the_corners_of_the_path() {
+        let settings = Settings {
+            simplify_tolerance: 2.0,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            simplified(MouseSimplification::PathTolerance, settings),
+            [(10, 0), (50, 0), (50, 50)]
+        );
+    }
+}
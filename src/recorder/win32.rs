@@ -0,0 +1,198 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    mem::zeroed,
+    ptr::null_mut,
+    sync::mpsc::{sync_channel, Sender},
+    thread::{self, JoinHandle},
+};
+
+use winapi::{
+    ctypes::c_int,
+    shared::{
+        minwindef::{HIWORD, LPARAM, LRESULT, WPARAM},
+        windef::POINT,
+    },
+    um::{processthreadsapi::GetCurrentThreadId, sysinfoapi::GetTickCount, winuser::*},
+};
+
+use super::{HookEvent, InputEvent, InputHook};
+use crate::actions::{KeyState, MouseActionButtonState, Point, ScanCode};
+
+thread_local! {
+    // Low level hooks run on the thread that installed them and don't get any user data
+    static SENDER: RefCell<Option<Sender<HookEvent>>> = const { RefCell::new(None) };
+}
+
+/// Low level keyboard and mouse hooks, running on their own thread with a message loop.
+#[derive(Default)]
+pub struct Win32InputHook {
+    thread: Option<(u32, JoinHandle<()>)>,
+}
+
+impl InputHook for Win32InputHook {
+    fn start(&mut self, sender: Sender<HookEvent>) -> Result<(Option<Point>, u32), Box<dyn Error>> {
+        self.stop();
+
+        let (started_sender, started_receiver) = sync_channel(0);
+
+        let handle = thread::spawn(move || unsafe {
+            SENDER.with(|cell| *cell.borrow_mut() = Some(sender));
+
+            let keyboard_hook = SetWindowsHookExA(WH_KEYBOARD_LL, Some(keyboard), null_mut(), 0);
+            let mouse_hook = SetWindowsHookExA(WH_MOUSE_LL, Some(mouse), null_mut(), 0);
+            let hooked = !keyboard_hook.is_null() && !mouse_hook.is_null();
+
+            let mut msg = zeroed();
+
+            // Makes sure the thread has a message queue before anyone posts WM_QUIT to it
+            PeekMessageA(&mut msg, null_mut(), WM_USER, WM_USER, PM_NOREMOVE);
+
+            started_sender
+                .send(hooked.then(|| GetCurrentThreadId()))
+                .unwrap();
+
+            if hooked {
+                while GetMessageA(&mut msg, null_mut(), 0, 0) > 0 {
+                    TranslateMessage(&msg);
+                    DispatchMessageA(&msg);
+                }
+            }
+
+            if !keyboard_hook.is_null() {
+                UnhookWindowsHookEx(keyboard_hook);
+            }
+            if !mouse_hook.is_null() {
+                UnhookWindowsHookEx(mouse_hook);
+            }
+        });
+
+        let Some(thread_id) = started_receiver.recv()? else {
+            handle.join().ok();
+            return Err("Failed to install the keyboard and mouse hooks".into());
+        };
+        self.thread = Some((thread_id, handle));
+
+        let mut position = unsafe { zeroed() };
+        unsafe { GetCursorPos(&mut position) };
+
+        Ok((Some(to_point(position)), unsafe { GetTickCount() }))
+    }
+
+    fn stop(&mut self) {
+        if let Some((thread_id, handle)) = self.thread.take() {
+            unsafe { PostThreadMessageA(thread_id, WM_QUIT, 0, 0) };
+            handle.join().ok();
+        }
+    }
+}
+
+impl Drop for Win32InputHook {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn to_point(point: POINT) -> Point {
+    Point {
+        x: point.x,
+        y: point.y,
+    }
+}
+
+fn send(hook_event: HookEvent) {
+    SENDER.with(|cell| {
+        if let Some(sender) = &*cell.borrow() {
+            sender.send(hook_event).ok();
+        }
+    });
+}
+
+unsafe extern "system" fn keyboard(n_code: c_int, w_param: WPARAM, l_param: LPARAM) -> LRESULT {
+    let info = *(l_param as PKBDLLHOOKSTRUCT);
+
+    let state = match w_param as u32 {
+        WM_KEYDOWN | WM_SYSKEYDOWN => Some(KeyState::Down),
+        WM_KEYUP | WM_SYSKEYUP => Some(KeyState::Up),
+        _ => None,
+    };
+
+    if let Some(state) = state {
+        let mut position = zeroed();
+        GetCursorPos(&mut position);
+
+        send(HookEvent {
+            event: InputEvent::Key(
+                info.vkCode as i32,
+                state,
+                Some(ScanCode {
+                    code: info.scanCode as u16,
+                    extended: info.flags & LLKHF_EXTENDED != 0,
+                }),
+            ),
+            position: to_point(position),
+            time: info.time,
+            window: None,
+        });
+    }
+
+    CallNextHookEx(null_mut(), n_code, w_param, l_param)
+}
+
+unsafe extern "system" fn mouse(n_code: c_int, w_param: WPARAM, l_param: LPARAM) -> LRESULT {
+    let info = *(l_param as PMSLLHOOKSTRUCT);
+
+    let message = w_param as u32;
+    let wheel_amount = (info.mouseData as i32) >> 16;
+
+    let event = match message {
+        WM_MOUSEMOVE => Some(InputEvent::MouseMove),
+        WM_MOUSEWHEEL => Some(InputEvent::Wheel {
+            amount: wheel_amount,
+            horizontal: false,
+        }),
+        WM_MOUSEHWHEEL => Some(InputEvent::Wheel {
+            amount: wheel_amount,
+            horizontal: true,
+        }),
+        _ => {
+            let button = match message {
+                WM_LBUTTONDOWN | WM_LBUTTONUP => Some(VK_LBUTTON),
+                WM_RBUTTONDOWN | WM_RBUTTONUP => Some(VK_RBUTTON),
+                WM_MBUTTONDOWN | WM_MBUTTONUP => Some(VK_MBUTTON),
+                WM_XBUTTONDOWN | WM_XBUTTONUP => match HIWORD(info.mouseData) {
+                    XBUTTON1 => Some(VK_XBUTTON1),
+                    _ => Some(VK_XBUTTON2),
+                },
+                _ => None,
+            };
+
+            let state = if matches!(
+                message,
+                WM_LBUTTONDOWN | WM_RBUTTONDOWN | WM_MBUTTONDOWN | WM_XBUTTONDOWN
+            ) {
+                MouseActionButtonState::Pressed
+            } else {
+                MouseActionButtonState::Released
+            };
+
+            button.map(|button| InputEvent::MouseButton(button, state))
+        }
+    };
+
+    // Messages that aren't buttons, movement or scrolling are left out
+    if let Some(event) = event {
+        let window = GetAncestor(WindowFromPoint(info.pt), GA_ROOT);
+        let mut client_origin = POINT { x: 0, y: 0 };
+        ClientToScreen(window, &mut client_origin);
+
+        send(HookEvent {
+            event,
+            position: to_point(info.pt),
+            time: info.time,
+            window: (!window.is_null()).then(|| (window as usize as u64, to_point(client_origin))),
+        });
+    }
+
+    CallNextHookEx(null_mut(), n_code, w_param, l_param)
+}
@@ -0,0 +1,438 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::mpsc::Sender,
+    thread::{self, JoinHandle},
+};
+
+use x11rb::{
+    connection::{Connection, RequestConnection},
+    protocol::{
+        record::{self, ConnectionExt as _},
+        xproto::{
+            self, Atom, AtomEnum, ConnectionExt as _, CreateWindowAux, EventMask, Window,
+            WindowClass,
+        },
+        Event,
+    },
+    rust_connection::RustConnection,
+    wrapper::ConnectionExt as _,
+    x11_utils::TryParse,
+    COPY_FROM_PARENT, NONE,
+};
+
+use super::{keymap::*, HookEvent, InputEvent, InputHook};
+use crate::actions::{KeyState, MouseActionButtonState, Point};
+
+// Reply categories of the record extension
+const FROM_SERVER: u8 = 0;
+
+// X11 key codes are evdev codes shifted by the minimum key code
+const KEYCODE_OFFSET: u8 = 8;
+
+/// Records the X server's input through the record extension. Needs one connection to control
+/// the recording context and another that blocks while reading the recorded events.
+pub struct XRecordInputHook {
+    control: RustConnection,
+    data: Option<RustConnection>,
+    root: Window,
+    recording: Option<(record::Context, JoinHandle<RustConnection>)>,
+}
+
+impl XRecordInputHook {
+    pub fn connect() -> Result<Self, Box<dyn Error>> {
+        let (control, screen_num) = x11rb::connect(None)?;
+        let (data, _) = x11rb::connect(None)?;
+        let root = control.setup().roots[screen_num].root;
+
+        if control
+            .extension_information(record::X11_EXTENSION_NAME)?
+            .is_none()
+        {
+            return Err("The X server doesn't support the record extension".into());
+        }
+
+        Ok(Self {
+            control,
+            data: Some(data),
+            root,
+            recording: None,
+        })
+    }
+
+    /// The server only tells its time in events, so this changes a property to get one.
+    fn server_time(&self) -> Result<u32, Box<dyn Error>> {
+        let window = self.control.generate_id()?;
+        self.control.create_window(
+            COPY_FROM_PARENT as u8,
+            window,
+            self.root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_ONLY,
+            COPY_FROM_PARENT,
+            &CreateWindowAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+        )?;
+        self.control.change_property8(
+            xproto::PropMode::APPEND,
+            window,
+            xproto::AtomEnum::WM_NAME,
+            xproto::AtomEnum::STRING,
+            &[],
+        )?;
+        self.control.flush()?;
+
+        let time = loop {
+            if let Event::PropertyNotify(event) = self.control.wait_for_event()? {
+                if event.window == window {
+                    break event.time;
+                }
+            }
+        };
+
+        self.control.destroy_window(window)?;
+        self.control.flush()?;
+
+        Ok(time)
+    }
+}
+
+impl InputHook for XRecordInputHook {
+    fn start(&mut self, sender: Sender<HookEvent>) -> Result<(Option<Point>, u32), Box<dyn Error>> {
+        self.stop();
+
+        let pointer = self.control.query_pointer(self.root)?.reply()?;
+        let time = self.server_time()?;
+
+        let empty = record::Range8 { first: 0, last: 0 };
+        let empty_ext = record::ExtRange {
+            major: empty,
+            minor: record::Range16 { first: 0, last: 0 },
+        };
+        let range = record::Range {
+            core_requests: empty,
+            core_replies: empty,
+            ext_requests: empty_ext,
+            ext_replies: empty_ext,
+            delivered_events: empty,
+            device_events: record::Range8 {
+                first: xproto::KEY_PRESS_EVENT,
+                last: xproto::MOTION_NOTIFY_EVENT,
+            },
+            errors: empty,
+            client_started: false,
+            client_died: false,
+        };
+
+        let context = self.control.generate_id()?;
+        self.control
+            .record_create_context(context, 0, &[record::CS::ALL_CLIENTS.into()], &[range])?
+            .check()?;
+
+        let data = self
+            .data
+            .take()
+            .ok_or("The recording connection was lost")?;
+        // Looking up windows can't wait on the connection that's blocked reading events
+        let mut window_finder = WindowFinder::connect()
+            .map_err(|error| println!("Recording without windows: {}", error))
+            .ok();
+        let handle = thread::spawn(move || {
+            forward_events(&data, context, &sender, window_finder.as_mut()).ok();
+            data
+        });
+        self.recording = Some((context, handle));
+
+        Ok((
+            Some(Point {
+                x: pointer.root_x as i32,
+                y: pointer.root_y as i32,
+            }),
+            time,
+        ))
+    }
+
+    fn stop(&mut self) {
+        if let Some((context, handle)) = self.recording.take() {
+            // Disabling the context ends the replies the recording thread is waiting on
+            self.control.record_disable_context(context).ok();
+            self.control.sync().ok();
+
+            self.data = handle.join().ok();
+
+            self.control.record_free_context(context).ok();
+            self.control.flush().ok();
+        }
+    }
+}
+
+impl Drop for XRecordInputHook {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Finds the client window under a position. Window managers put clients in frames, so the
+/// window under the cursor is usually a frame around the client window that's listed.
+struct WindowFinder {
+    connection: RustConnection,
+    root: Window,
+    client_list: Atom,
+    /// Clients by the child of the root window they're in, None for windows without a client
+    /// such as panels.
+    clients: HashMap<Window, Option<Window>>,
+}
+
+impl WindowFinder {
+    fn connect() -> Result<Self, Box<dyn Error>> {
+        let (connection, screen_num) = x11rb::connect(None)?;
+        let root = connection.setup().roots[screen_num].root;
+        let client_list = connection
+            .intern_atom(false, b"_NET_CLIENT_LIST")?
+            .reply()?
+            .atom;
+
+        Ok(Self {
+            connection,
+            root,
+            client_list,
+            clients: HashMap::new(),
+        })
+    }
+
+    /// The client window at the position and the screen position of its top left corner.
+    fn window_at(&mut self, position: Point) -> Option<(u64, Point)> {
+        let top_level = self
+            .connection
+            .translate_coordinates(self.root, self.root, position.x as i16, position.y as i16)
+            .ok()?
+            .reply()
+            .ok()?
+            .child;
+        if top_level == NONE {
+            return None;
+        }
+
+        // Windows opened during the recording aren't known yet
+        if !self.clients.contains_key(&top_level) {
+            self.update_clients();
+            self.clients.entry(top_level).or_insert(None);
+        }
+        let client = self.clients[&top_level]?;
+
+        let origin = self
+            .connection
+            .translate_coordinates(client, self.root, 0, 0)
+            .ok()?
+            .reply()
+            .ok()?;
+
+        Some((
+            client as u64,
+            Point {
+                x: origin.dst_x as i32,
+                y: origin.dst_y as i32,
+            },
+        ))
+    }
+
+    fn update_clients(&mut self) {
+        let Ok(Ok(reply)) = self
+            .connection
+            .get_property(
+                false,
+                self.root,
+                self.client_list,
+                AtomEnum::WINDOW,
+                0,
+                u32::MAX,
+            )
+            .map(|cookie| cookie.reply())
+        else {
+            return;
+        };
+
+        for client in reply.value32().into_iter().flatten() {
+            // Clients closed since the list was read are left out
+            if let Ok(top_level) = self.top_level(client) {
+                self.clients.insert(top_level, Some(client));
+            }
+        }
+    }
+
+    fn top_level(&self, mut window: Window) -> Result<Window, Box<dyn Error>> {
+        loop {
+            let parent = self.connection.query_tree(window)?.reply()?.parent;
+            if parent == self.root {
+                return Ok(window);
+            }
+            window = parent;
+        }
+    }
+}
+
+fn forward_events(
+    data: &RustConnection,
+    context: record::Context,
+    sender: &Sender<HookEvent>,
+    mut window_finder: Option<&mut WindowFinder>,
+) -> Result<(), Box<dyn Error>> {
+    for reply in data.record_enable_context(context)? {
+        let reply = reply?;
+        if reply.category != FROM_SERVER || reply.client_swapped {
+            continue;
+        }
+
+        // Device events are always 32 bytes long
+        for chunk in reply.data.chunks_exact(32) {
+            if let Some(mut hook_event) = parse_event(chunk) {
+                hook_event.window = window_finder
+                    .as_mut()
+                    .and_then(|finder| finder.window_at(hook_event.position));
+                sender.send(hook_event)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_event(data: &[u8]) -> Option<HookEvent> {
+    // The highest bit marks events sent by other clients
+    let (event, root_x, root_y, time) = match data[0] & 0x7F {
+        xproto::KEY_PRESS_EVENT | xproto::KEY_RELEASE_EVENT => {
+            let (event, _) = xproto::KeyPressEvent::try_parse(data).ok()?;
+            let evdev_code = event.detail.checked_sub(KEYCODE_OFFSET)? as u16;
+            let key_code = evdev_to_virtual_key(evdev_code)?;
+            let state = if data[0] & 0x7F == xproto::KEY_PRESS_EVENT {
+                KeyState::Down
+            } else {
+                KeyState::Up
+            };
+
+            (
+                InputEvent::Key(key_code, state, evdev_to_scan_code(evdev_code)),
+                event.root_x,
+                event.root_y,
+                event.time,
+            )
+        }
+        xproto::BUTTON_PRESS_EVENT | xproto::BUTTON_RELEASE_EVENT => {
+            let (event, _) = xproto::ButtonPressEvent::try_parse(data).ok()?;
+            let pressed = data[0] & 0x7F == xproto::BUTTON_PRESS_EVENT;
+
+            let input_event = match (event.detail, pressed) {
+                // Every wheel step is a press and a release
+                (4..=7, false) => return None,
+                (4, true) => InputEvent::Wheel {
+                    amount: 120,
+                    horizontal: false,
+                },
+                (5, true) => InputEvent::Wheel {
+                    amount: -120,
+                    horizontal: false,
+                },
+                (6, true) => InputEvent::Wheel {
+                    amount: -120,
+                    horizontal: true,
+                },
+                (7, true) => InputEvent::Wheel {
+                    amount: 120,
+                    horizontal: true,
+                },
+                (button, _) => {
+                    let button = match button {
+                        1 => VK_LBUTTON,
+                        2 => VK_MBUTTON,
+                        3 => VK_RBUTTON,
+                        8 => VK_XBUTTON1,
+                        9 => VK_XBUTTON2,
+                        _ => return None,
+                    };
+                    let state = if pressed {
+                        MouseActionButtonState::Pressed
+                    } else {
+                        MouseActionButtonState::Released
+                    };
+
+                    InputEvent::MouseButton(button, state)
+                }
+            };
+
+            (input_event, event.root_x, event.root_y, event.time)
+        }
+        xproto::MOTION_NOTIFY_EVENT => {
+            let (event, _) = xproto::MotionNotifyEvent::try_parse(data).ok()?;
+
+            (
+                InputEvent::MouseMove,
+                event.root_x,
+                event.root_y,
+                event.time,
+            )
+        }
+        _ => return None,
+    };
+
+    Some(HookEvent {
+        event,
+        position: Point {
+            x: root_x as i32,
+            y: root_y as i32,
+        },
+        time,
+        window: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::mpsc::channel,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+    use crate::input::{send_key, set_cursor_position};
+
+    /// Run with `cargo test -- --ignored` against the X server in `DISPLAY`, such as `Xvfb :99`.
+    #[test]
+    #[ignore = "needs an X server with the record and XTEST extensions"]
+    fn records_xtest_input() {
+        let mut hook = XRecordInputHook::connect().unwrap();
+
+        let (sender, receiver) = channel();
+        hook.start(sender).unwrap();
+        // The recording thread enables the context on its own connection
+        thread::sleep(Duration::from_millis(200));
+
+        set_cursor_position(Point { x: 13, y: 17 }).unwrap();
+        send_key(0x41, KeyState::Pressed, None, false).unwrap();
+
+        let mut events = vec![];
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while events.len() < 3 && Instant::now() < deadline {
+            if let Ok(event) = receiver.recv_timeout(Duration::from_millis(100)) {
+                events.push(event);
+            }
+        }
+        hook.stop();
+
+        let events = events
+            .iter()
+            .map(|event| format!("{:?} {:?}", event.event, event.position))
+            .collect::<Vec<_>>();
+        let scan_code = "Some(ScanCode { code: 30, extended: false })";
+        assert_eq!(
+            events,
+            [
+                "MouseMove Point { x: 13, y: 17 }".to_string(),
+                format!("Key(65, Down, {}) Point {{ x: 13, y: 17 }}", scan_code),
+                format!("Key(65, Up, {}) Point {{ x: 13, y: 17 }}", scan_code),
+            ]
+        );
+    }
+}
@@ -1,6 +1,10 @@
 use eframe::egui::*;
 
-use crate::{actions::*, gui::Recorder};
+use crate::{
+    actions::*,
+    gui::Recorder,
+    modals::{cleanup_window::CleanupWindow, convert_points_window::ConvertPointsWindow},
+};
 
 pub struct ActionRightClickDialog {
     pub position: Pos2,
@@ -45,6 +49,20 @@ impl ActionRightClickDialog {
             let button = Button::new("Move").fill(Color32::from_rgba_premultiplied(0, 0, 0, 0));
             let move_response = button.ui(ui);
 
+            ui.allocate_space(vec2(0.0, 5.0));
+            let button =
+                Button::new("Simplify Movement").fill(Color32::from_rgba_premultiplied(0, 0, 0, 0));
+            let simplify_response = button.ui(ui);
+
+            ui.allocate_space(vec2(0.0, 5.0));
+            let button = Button::new("Clean Up").fill(Color32::from_rgba_premultiplied(0, 0, 0, 0));
+            let cleanup_response = button.ui(ui);
+
+            ui.allocate_space(vec2(0.0, 5.0));
+            let button =
+                Button::new("Convert Positions").fill(Color32::from_rgba_premultiplied(0, 0, 0, 0));
+            let convert_response = button.ui(ui);
+
             if edit_response.clicked() {
                 recorder.modal = recorder.action_list()[selected_row].get_modify_command_window(
                     false,
@@ -58,10 +76,27 @@ impl ActionRightClickDialog {
                 recorder.action_list().remove(selected_row);
                 recorder.right_click_dialog = None;
                 recorder.selected_row = None;
+                recorder.selection_anchor = None;
             }
 
             if move_response.is_pointer_button_down_on() {
                 recorder.moving_row = true;
+                recorder.selection_anchor = None;
+                recorder.right_click_dialog = None;
+            }
+
+            if simplify_response.clicked() {
+                recorder.simplify_selection();
+                recorder.right_click_dialog = None;
+            }
+
+            if cleanup_response.clicked() {
+                recorder.modal = Some(CleanupWindow::new());
+                recorder.right_click_dialog = None;
+            }
+
+            if convert_response.clicked() {
+                recorder.modal = Some(ConvertPointsWindow::new(recorder.metadata.start_position));
                 recorder.right_click_dialog = None;
             }
         });
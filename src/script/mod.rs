@@ -0,0 +1,298 @@
+//! Macros as plain text, one action per line, so they can be written by hand and reviewed in a
+//! diff. Every list of actions prints as a script that parses back into the same list:
+//!
+//! ```text
+//! delay 250 jitter uniform 30
+//! move to 100, 200
+//! click left at 100, 200 in window "Notepad" class "Notepad"
+//! key press "Left Shift" scan 42
+//! if image "image1.png" captured at 40, 60 similarity 0.95 {
+//!     repeat 5 {
+//!         wheel -120
+//!     }
+//! } else {
+//!     launch "notepad.exe" with "notes.txt" wait timeout 5000
+//! }
+//! ```
+//!
+//! Offsets are written `by X, Y` in place of a position. Wheel amounts are in the units Windows
+//! uses, 120 to a notch. Keys recorded with a scan code have it after the key, followed by
+//! `extended` for keys sent with an 0xE0 prefix. Blocks that aren't closed in the macro are
+//! written without braces as `if ...`, `else`, `end if`, `repeat ...` and `end repeat`. Lines
+//! starting with `//` are comments.
+
+use std::{error::Error, ffi::OsStr, fmt, path::Path};
+
+use crate::images::RawScreenshot;
+
+mod parse;
+mod print;
+
+pub use self::parse::parse_script;
+pub use self::print::{braced_blocks, print_script};
+
+/// Extension of scripts, macros saved with it are written as text instead of a container.
+pub const SCRIPT_EXTENSION: &str = "floofscript";
+
+pub fn is_script(path: &Path) -> bool {
+    path.extension() == Some(OsStr::new(SCRIPT_EXTENSION))
+}
+
+/// A script printed from a macro, along with the screenshots its image actions refer to.
+pub struct Script<'a> {
+    pub text: String,
+    /// Names used in the script and the screenshots to save as PNG files under them.
+    pub images: Vec<(String, &'a RawScreenshot)>,
+}
+
+/// A mistake in a script, lines and columns count from 1.
+#[derive(PartialEq, Debug)]
+pub struct ScriptError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ScriptError {}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{
+        actions::*,
+        images::RawScreenshotPair,
+        recorder::keymap::{VK_LSHIFT, VK_RBUTTON, VK_RETURN},
+    };
+
+    fn round_trip(actions: &[Action]) -> Vec<Action> {
+        let script = print_script(actions, "image");
+        let pngs: Vec<(String, Vec<u8>)> = script
+            .images
+            .iter()
+            .map(|(name, screenshot)| (name.clone(), screenshot.to_png().unwrap()))
+            .collect();
+
+        parse_script(&script.text, |name| {
+            pngs.iter()
+                .find(|(png_name, _)| png_name == name)
+                .map(|(_, png)| png.clone())
+                .ok_or_else(|| "Missing image".into())
+        })
+        .unwrap_or_else(|error| panic!("{}\n{}", error, script.text))
+    }
+
+    fn window() -> WindowInfo {
+        WindowInfo {
+            title: "Untitled - \"Notepad\"".into(),
+            class: "Notepad\\d".into(),
+        }
+    }
+
+    #[test]
+    fn reads_back_what_it_prints() {
+        let monitor = MonitorInfo {
+            name: r"\\.\DISPLAY2".into(),
+            rect: WindowRect {
+                x: -1920,
+                y: 0,
+                width: 1920,
+                height: 1080,
+            },
+            scale: 1.25,
+            primary: false,
+        };
+        let color = RawScreenshot {
+            pixels: vec![10, 20, 30, 255, 40, 50, 60, 255],
+            width: 2,
+            height: 1,
+            x: 5,
+            y: -7,
+        };
+        let actions = vec![
+            Action::Delay(250, DelayJitter::None),
+            Action::Delay(100, DelayJitter::Normal(15)),
+            Action::Mouse(MouseActionKind::Moved(MousePointKind::To(Point {
+                x: 100,
+                y: -200,
+            }))),
+            Action::Mouse(MouseActionKind::Moved(MousePointKind::Monitor(
+                monitor,
+                Point { x: 3, y: 4 },
+            ))),
+            Action::Mouse(MouseActionKind::Button(MouseActionButton {
+                point: Some(MousePointKind::Window(window(), Point { x: 1, y: 2 })),
+                button: VK_RBUTTON,
+                state: MouseActionButtonState::Clicked,
+            })),
+            Action::Mouse(MouseActionKind::Button(MouseActionButton {
+                point: None,
+                button: 0x07,
+                state: MouseActionButtonState::Released,
+            })),
+            Action::Mouse(MouseActionKind::Wheel(-120, None)),
+            Action::Mouse(MouseActionKind::HorizontalWheel(
+                60,
+                Some(MousePointKind::By(Point { x: 0, y: 5 })),
+            )),
+            Action::Keyboard(VK_LSHIFT, KeyState::Down, None),
+            Action::Keyboard(
+                0x31,
+                KeyState::Pressed,
+                Some(ScanCode {
+                    code: 2,
+                    extended: false,
+                }),
+            ),
+            Action::Keyboard(
+                0x41,
+                KeyState::Up,
+                Some(ScanCode {
+                    code: 0x1D,
+                    extended: true,
+                }),
+            ),
+            Action::Keyboard(0x3A, KeyState::Pressed, None),
+            Action::IfImage(ImageInfo {
+                screenshot_raw: Some(RawScreenshotPair::from_color(color).unwrap()),
+                search_location_left_top: Some((10, 20)),
+                search_location_width_height: Some((300, 400)),
+                image_similarity: 0.95,
+                check_if_not_found: true,
+                search_anchor: SearchAnchor::LastFoundImage,
+                ..Default::default()
+            }),
+            Action::Repeat(5),
+            Action::WaitForPixel(PixelInfo {
+                color: (255, 0, 128),
+                search_location_left_top: (1, 2),
+                search_location_width_height: (3, 4),
+                move_mouse_if_found: true,
+                search_anchor: SearchAnchor::Window(window()),
+                ..Default::default()
+            }),
+            Action::Break,
+            Action::EndRepeat,
+            Action::Else,
+            Action::WaitForImage(ImageInfo::default()),
+            Action::EndIf,
+            Action::Play(PathBuf::from(r"C:\macros\login.floof")),
+            Action::Launch(LaunchInfo {
+                program: "notepad.exe".into(),
+                args: vec!["a b".into(), "".into()],
+                working_dir: Some("/tmp".into()),
+                wait_for_exit: false,
+                timeout: Some(5000),
+            }),
+            Action::WaitForWindow(window()),
+            Action::ActivateWindow(WindowInfo::default()),
+            Action::MoveWindow(
+                window(),
+                WindowRect {
+                    x: 0,
+                    y: 0,
+                    width: 800,
+                    height: 600,
+                },
+            ),
+            Action::CopyClipboard("order".into()),
+            Action::SetClipboard("Order {order}\n\tdone".into()),
+            Action::WaitForClipboardChange,
+            Action::IfClipboard(r"^\d+$".into()),
+            Action::Drag(DragInfo {
+                from: MousePointKind::By(Point { x: 0, y: 0 }),
+                to: MousePointKind::To(Point { x: 30, y: 40 }),
+                path: vec![Point { x: 5, y: 5 }, Point { x: -5, y: 10 }],
+                ..Default::default()
+            }),
+            Action::EndIf,
+            Action::Repeat(0),
+            Action::EndRepeat,
+        ];
+
+        assert_eq!(
+            format!("{:?}", round_trip(&actions)),
+            format!("{:?}", actions)
+        );
+    }
+
+    #[test]
+    fn keeps_blocks_that_arent_closed() {
+        let actions = vec![
+            Action::EndIf,
+            Action::IfClipboard("a".into()),
+            Action::Repeat(2),
+            Action::Else,
+            Action::EndIf,
+            Action::Repeat(3),
+            Action::Else,
+            Action::EndRepeat,
+            Action::EndRepeat,
+        ];
+        let text = print_script(&actions, "image").text;
+
+        assert_eq!(
+            text,
+            "end if\nif clipboard matches \"a\"\nrepeat 2 {\n    else\n    end if\n    repeat 3 {\n        else\n    }\n}\n"
+        );
+        assert_eq!(
+            format!("{:?}", round_trip(&actions)),
+            format!("{:?}", actions)
+        );
+    }
+
+    #[test]
+    fn parses_blocks_into_actions() {
+        let text = "// Log in\nif clipboard matches \"ok\" {\n  key press Enter\n} else {\n  repeat forever {\n    break\n  }\n}\n";
+
+        assert_eq!(
+            format!(
+                "{:?}",
+                parse_script(text, |_| Err("No images".into())).unwrap()
+            ),
+            format!(
+                "{:?}",
+                [
+                    Action::IfClipboard("ok".into()),
+                    Action::Keyboard(VK_RETURN, KeyState::Pressed, None),
+                    Action::Else,
+                    Action::Repeat(0),
+                    Action::Break,
+                    Action::EndRepeat,
+                    Action::EndIf,
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn points_at_mistakes() {
+        let parse = |text| parse_script(text, |_| Err("No images".into())).unwrap_err();
+
+        assert_eq!(
+            parse("delay 5\nclick left at 5,"),
+            ScriptError {
+                line: 2,
+                column: 17,
+                message: "Expected a Y position".into()
+            }
+        );
+        assert_eq!(parse("  jump 3").column, 3);
+        assert_eq!(parse("repeat 2 {\ndelay 1").line, 1);
+        assert_eq!(parse("delay 1\n}").line, 2);
+        assert_eq!(parse("key press \"Nope\"").column, 11);
+        assert_eq!(parse("set clipboard \"open").column, 15);
+        assert_eq!(parse("if image \"a.png\" {\n}").column, 10);
+    }
+}
@@ -0,0 +1,770 @@
+use std::{error::Error, path::PathBuf, str::FromStr};
+
+use super::ScriptError;
+use crate::{
+    actions::*,
+    images::{RawScreenshot, RawScreenshotPair},
+    keycodes_to_string::string_to_key_code,
+    recorder::keymap::{VK_LBUTTON, VK_MBUTTON, VK_RBUTTON, VK_XBUTTON1, VK_XBUTTON2},
+};
+
+#[derive(PartialEq, Debug)]
+enum Token {
+    Word(String),
+    Number(String),
+    Text(String),
+    Comma,
+    Semicolon,
+    OpenBrace,
+    CloseBrace,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Block {
+    If,
+    Repeat,
+}
+
+/// Reads a script back into actions. Image actions name PNG files, `load_image` gives the
+/// contents of one by its name.
+pub fn parse_script(
+    text: &str,
+    mut load_image: impl FnMut(&str) -> Result<Vec<u8>, Box<dyn Error>>,
+) -> Result<Vec<Action>, ScriptError> {
+    let mut actions = vec![];
+    // The kind of each open block and where its brace is
+    let mut blocks: Vec<(Block, usize, usize)> = vec![];
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let mut tokens = tokenize(line, line_number)?;
+
+        let Some(&(_, first_column)) = tokens.first() else {
+            continue;
+        };
+        let opening = match tokens.last() {
+            Some((Token::OpenBrace, column)) => Some(*column),
+            _ => None,
+        };
+        if opening.is_some() {
+            tokens.pop();
+        }
+
+        let mut statement = Statement {
+            tokens,
+            next: 0,
+            line: line_number,
+            end: line.chars().count() + 1,
+        };
+
+        if statement.peek() == Some(&Token::CloseBrace) {
+            statement.next += 1;
+
+            let Some((block, ..)) = blocks.pop() else {
+                return Err(statement.error_at(first_column, "There's no block to close"));
+            };
+
+            if statement.eat("else") {
+                let Some(column) = opening else {
+                    return Err(statement.error("Expected \"{\""));
+                };
+                if block != Block::If {
+                    return Err(statement.error_at(first_column, "Only ifs can have an else"));
+                }
+
+                actions.push(Action::Else);
+                blocks.push((Block::If, line_number, column));
+            } else {
+                if opening.is_some() {
+                    return Err(statement.error("Expected \"else\""));
+                }
+
+                actions.push(match block {
+                    Block::If => Action::EndIf,
+                    Block::Repeat => Action::EndRepeat,
+                });
+            }
+
+            statement.finish()?;
+            continue;
+        }
+
+        let action = statement.action(&mut load_image)?;
+        statement.finish()?;
+
+        if let Some(column) = opening {
+            let block = match action {
+                Action::IfImage(_) | Action::IfPixel(_) | Action::IfClipboard(_) => Block::If,
+                Action::Repeat(_) => Block::Repeat,
+                _ => {
+                    return Err(statement.error_at(column, "Only ifs and repeats can open a block"))
+                }
+            };
+            blocks.push((block, line_number, column));
+        }
+
+        actions.push(action);
+    }
+
+    if let Some((_, line, column)) = blocks.pop() {
+        return Err(ScriptError {
+            line,
+            column,
+            message: "This block is never closed".into(),
+        });
+    }
+
+    Ok(actions)
+}
+
+/// The tokens of a line and the column each starts at.
+fn tokenize(line: &str, line_number: usize) -> Result<Vec<(Token, usize)>, ScriptError> {
+    let chars: Vec<char> = line.chars().collect();
+    let error = |column: usize, message: &str| ScriptError {
+        line: line_number,
+        column,
+        message: message.into(),
+    };
+    let mut tokens = vec![];
+    let mut at = 0;
+
+    while at < chars.len() {
+        let column = at + 1;
+        let c = chars[at];
+        at += 1;
+
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '/' if chars.get(at) == Some(&'/') => break,
+            ',' => Token::Comma,
+            ';' => Token::Semicolon,
+            '{' => Token::OpenBrace,
+            '}' => Token::CloseBrace,
+            '"' => {
+                let mut text = String::new();
+
+                loop {
+                    let Some(&c) = chars.get(at) else {
+                        return Err(error(column, "This text is missing its closing quote"));
+                    };
+                    at += 1;
+
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            text.push(match chars.get(at) {
+                                Some('"') => '"',
+                                Some('\\') => '\\',
+                                Some('n') => '\n',
+                                Some('r') => '\r',
+                                Some('t') => '\t',
+                                _ => return Err(error(at, "Unknown escape, use \\\\ for a \\")),
+                            });
+                            at += 1;
+                        }
+                        c => text.push(c),
+                    }
+                }
+
+                Token::Text(text)
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let start = at - 1;
+
+                while matches!(chars.get(at), Some(c) if c.is_ascii_digit() || *c == '.') {
+                    at += 1;
+                }
+                if c == '-' && at == start + 1 {
+                    return Err(error(column, "Expected a number after \"-\""));
+                }
+
+                Token::Number(chars[start..at].iter().collect())
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = at - 1;
+
+                while matches!(chars.get(at), Some(c) if c.is_alphanumeric() || *c == '_') {
+                    at += 1;
+                }
+
+                Token::Word(chars[start..at].iter().collect())
+            }
+            c => return Err(error(column, &format!("Unexpected \"{}\"", c))),
+        };
+
+        tokens.push((token, column));
+    }
+
+    Ok(tokens)
+}
+
+/// The options shared by image and pixel conditions.
+#[derive(Default)]
+struct SearchOptions {
+    captured_at: (i32, i32),
+    similarity: Option<f32>,
+    left_top: Option<(i32, i32)>,
+    width_height: Option<(i32, i32)>,
+    anchor: SearchAnchor,
+    not_found: bool,
+    move_mouse: bool,
+}
+
+/// One line of a script being read.
+struct Statement {
+    tokens: Vec<(Token, usize)>,
+    next: usize,
+    line: usize,
+    /// The column after the last character, where errors about missing things point.
+    end: usize,
+}
+
+impl Statement {
+    fn error_at(&self, column: usize, message: &str) -> ScriptError {
+        ScriptError {
+            line: self.line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// An error about the next token.
+    fn error(&self, message: &str) -> ScriptError {
+        self.error_at(self.column(), message)
+    }
+
+    /// Where the next token starts.
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map_or(self.end, |&(_, column)| column)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(token, _)| token)
+    }
+
+    fn peek_word(&self) -> Option<&str> {
+        match self.peek() {
+            Some(Token::Word(word)) => Some(word),
+            _ => None,
+        }
+    }
+
+    /// Skips the word if it's next.
+    fn eat(&mut self, word: &str) -> bool {
+        let found = self.peek_word() == Some(word);
+
+        if found {
+            self.next += 1;
+        }
+
+        found
+    }
+
+    fn expect(&mut self, word: &str) -> Result<(), ScriptError> {
+        if self.eat(word) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected \"{}\"", word)))
+        }
+    }
+
+    fn word(&mut self, what: &str) -> Result<String, ScriptError> {
+        match self.peek_word() {
+            Some(word) => {
+                let word = word.to_string();
+                self.next += 1;
+                Ok(word)
+            }
+            None => Err(self.error(&format!("Expected {}", what))),
+        }
+    }
+
+    fn text(&mut self, what: &str) -> Result<String, ScriptError> {
+        match self.peek() {
+            Some(Token::Text(text)) => {
+                let text = text.clone();
+                self.next += 1;
+                Ok(text)
+            }
+            _ => Err(self.error(&format!("Expected {} in quotes", what))),
+        }
+    }
+
+    fn number<T: FromStr>(&mut self, what: &str) -> Result<T, ScriptError> {
+        match self.peek() {
+            Some(Token::Number(number)) => match number.parse() {
+                Ok(number) => {
+                    self.next += 1;
+                    Ok(number)
+                }
+                Err(_) => Err(self.error(&format!("{} isn't a valid {}", number, what))),
+            },
+            _ => Err(self.error(&format!("Expected {}", what))),
+        }
+    }
+
+    fn comma(&mut self) -> Result<(), ScriptError> {
+        if self.peek() == Some(&Token::Comma) {
+            self.next += 1;
+            Ok(())
+        } else {
+            Err(self.error("Expected \",\""))
+        }
+    }
+
+    fn point(&mut self) -> Result<Point, ScriptError> {
+        let x = self.number("an X position")?;
+        self.comma()?;
+        let y = self.number("a Y position")?;
+
+        Ok(Point { x, y })
+    }
+
+    fn pair(&mut self) -> Result<(i32, i32), ScriptError> {
+        let point = self.point()?;
+
+        Ok((point.x, point.y))
+    }
+
+    /// `X, Y size WIDTH, HEIGHT`
+    fn area(&mut self) -> Result<WindowRect, ScriptError> {
+        let Point { x, y } = self.point()?;
+        self.expect("size")?;
+        let width = self.number("a width")?;
+        self.comma()?;
+        let height = self.number("a height")?;
+
+        Ok(WindowRect {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    /// `window "TITLE" class "CLASS"`
+    fn window(&mut self) -> Result<WindowInfo, ScriptError> {
+        self.expect("window")?;
+        let title = self.text("a window title")?;
+        self.expect("class")?;
+        let class = self.text("a window class")?;
+
+        Ok(WindowInfo { title, class })
+    }
+
+    /// An offset `by X, Y`, or the keyword and a position on the screen, in a window or on a
+    /// monitor.
+    fn position(&mut self, keyword: &str) -> Result<MousePointKind, ScriptError> {
+        if self.eat("by") {
+            return Ok(MousePointKind::By(self.point()?));
+        }
+        if !self.eat(keyword) {
+            return Err(self.error(&format!("Expected \"{}\" or \"by\"", keyword)));
+        }
+        if self.eat("by") {
+            return Ok(MousePointKind::By(self.point()?));
+        }
+
+        let point = self.point()?;
+
+        Ok(if self.eat("in") {
+            MousePointKind::Window(self.window()?, point)
+        } else if self.eat("on") {
+            self.expect("monitor")?;
+            let name = self.text("a monitor name")?;
+            let rect = self.area()?;
+            self.expect("scale")?;
+            let scale = self.number("a scale")?;
+            let primary = self.eat("primary");
+
+            MousePointKind::Monitor(
+                MonitorInfo {
+                    name,
+                    rect,
+                    scale,
+                    primary,
+                },
+                point,
+            )
+        } else {
+            MousePointKind::To(point)
+        })
+    }
+
+    fn optional_position(&mut self) -> Result<Option<MousePointKind>, ScriptError> {
+        match self.peek_word() {
+            Some("at" | "by") => Ok(Some(self.position("at")?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn button(&mut self) -> Result<i32, ScriptError> {
+        let column = self.column();
+
+        Ok(match self.word("a mouse button")?.as_str() {
+            "left" => VK_LBUTTON,
+            "right" => VK_RBUTTON,
+            "middle" => VK_MBUTTON,
+            "x1" => VK_XBUTTON1,
+            "x2" => VK_XBUTTON2,
+            "button" => self.number("a button code")?,
+            _ => return Err(self.error_at(column, "Unknown mouse button")),
+        })
+    }
+
+    fn key(&mut self) -> Result<i32, ScriptError> {
+        let column = self.column();
+        let name = match self.peek() {
+            Some(Token::Number(_)) => return self.number("a key code"),
+            Some(Token::Word(name) | Token::Text(name)) => name.clone(),
+            _ => return Err(self.error("Expected a key")),
+        };
+        self.next += 1;
+
+        string_to_key_code(&name)
+            .ok_or_else(|| self.error_at(column, &format!("Unknown key \"{}\"", name)))
+    }
+
+    fn search_options(&mut self, image: bool) -> Result<SearchOptions, ScriptError> {
+        let mut options = SearchOptions::default();
+
+        while let Some(option) = self.peek_word() {
+            let column = self.column();
+
+            match option {
+                "captured" if image => {
+                    self.next += 1;
+                    self.expect("at")?;
+                    options.captured_at = self.pair()?;
+                }
+                "similarity" if image => {
+                    self.next += 1;
+                    options.similarity = Some(self.number("a similarity")?);
+                }
+                "in" => {
+                    self.next += 1;
+                    options.left_top = Some(self.pair()?);
+                }
+                "size" => {
+                    self.next += 1;
+                    options.width_height = Some(self.pair()?);
+                }
+                "relative" => {
+                    self.next += 1;
+                    self.expect("to")?;
+                    options.anchor = if self.eat("last") {
+                        self.expect("image")?;
+                        SearchAnchor::LastFoundImage
+                    } else {
+                        SearchAnchor::Window(self.window()?)
+                    };
+                }
+                "not" => {
+                    self.next += 1;
+                    self.expect("found")?;
+                    options.not_found = true;
+                }
+                "move" => {
+                    self.next += 1;
+                    self.expect("mouse")?;
+                    options.move_mouse = true;
+                }
+                option => {
+                    let message = format!("Unknown option \"{}\"", option);
+                    return Err(self.error_at(column, &message));
+                }
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// `"NAME.png" OPTIONS` or `none OPTIONS`
+    fn image(
+        &mut self,
+        load_image: &mut impl FnMut(&str) -> Result<Vec<u8>, Box<dyn Error>>,
+    ) -> Result<ImageInfo, ScriptError> {
+        let column = self.column();
+        let name = if self.eat("none") {
+            None
+        } else {
+            Some(self.text("an image file name or none")?)
+        };
+        let options = self.search_options(true)?;
+
+        let screenshot_raw = match name {
+            Some(name) => {
+                let (x, y) = options.captured_at;
+                let screenshot = load_image(&name)
+                    .and_then(|png| RawScreenshot::from_png(&png, x, y))
+                    .and_then(RawScreenshotPair::from_color)
+                    .map_err(|error| {
+                        let message = format!("Couldn't load {}: {}", name, error);
+                        self.error_at(column, &message)
+                    })?;
+
+                Some(screenshot)
+            }
+            None => None,
+        };
+
+        Ok(ImageInfo {
+            screenshot_raw,
+            move_mouse_if_found: options.move_mouse,
+            check_if_not_found: options.not_found,
+            search_location_left_top: options.left_top,
+            search_location_width_height: options.width_height,
+            image_similarity: options
+                .similarity
+                .unwrap_or(ImageInfo::default().image_similarity),
+            search_anchor: options.anchor,
+        })
+    }
+
+    /// `color RED, GREEN, BLUE OPTIONS`
+    fn pixel(&mut self) -> Result<PixelInfo, ScriptError> {
+        self.expect("color")?;
+        let red = self.number("a red amount")?;
+        self.comma()?;
+        let green = self.number("a green amount")?;
+        self.comma()?;
+        let blue = self.number("a blue amount")?;
+        let options = self.search_options(false)?;
+
+        Ok(PixelInfo {
+            color: (red, green, blue),
+            search_location_left_top: options.left_top.unwrap_or_default(),
+            search_location_width_height: options.width_height.unwrap_or_default(),
+            check_if_not_found: options.not_found,
+            move_mouse_if_found: options.move_mouse,
+            search_anchor: options.anchor,
+        })
+    }
+
+    fn action(
+        &mut self,
+        load_image: &mut impl FnMut(&str) -> Result<Vec<u8>, Box<dyn Error>>,
+    ) -> Result<Action, ScriptError> {
+        let column = self.column();
+
+        Ok(match self.word("an action")?.as_str() {
+            "delay" => {
+                let delay = self.number("a delay in milliseconds")?;
+                let jitter = if self.eat("jitter") {
+                    let column = self.column();
+
+                    match self.word("uniform or normal")?.as_str() {
+                        "uniform" => DelayJitter::Uniform(self.number("a range")?),
+                        "normal" => DelayJitter::Normal(self.number("a deviation")?),
+                        _ => return Err(self.error_at(column, "Expected uniform or normal")),
+                    }
+                } else {
+                    DelayJitter::None
+                };
+
+                Action::Delay(delay, jitter)
+            }
+            "move" => {
+                if self.peek_word() == Some("window") {
+                    let window = self.window()?;
+                    self.expect("to")?;
+
+                    Action::MoveWindow(window, self.area()?)
+                } else {
+                    Action::Mouse(MouseActionKind::Moved(self.position("to")?))
+                }
+            }
+            word @ ("click" | "press" | "release") => {
+                let state = match word {
+                    "click" => MouseActionButtonState::Clicked,
+                    "press" => MouseActionButtonState::Pressed,
+                    _ => MouseActionButtonState::Released,
+                };
+                let button = self.button()?;
+
+                Action::Mouse(MouseActionKind::Button(MouseActionButton {
+                    point: self.optional_position()?,
+                    button,
+                    state,
+                }))
+            }
+            "wheel" => {
+                let amount = self.number("a wheel amount")?;
+                Action::Mouse(MouseActionKind::Wheel(amount, self.optional_position()?))
+            }
+            "hwheel" => {
+                let amount = self.number("a wheel amount")?;
+                Action::Mouse(MouseActionKind::HorizontalWheel(
+                    amount,
+                    self.optional_position()?,
+                ))
+            }
+            "key" => {
+                let column = self.column();
+                let state = match self.word("down, up or press")?.as_str() {
+                    "down" => KeyState::Down,
+                    "up" => KeyState::Up,
+                    "press" => KeyState::Pressed,
+                    _ => return Err(self.error_at(column, "Expected down, up or press")),
+                };
+
+                let key_code = self.key()?;
+                let scan_code = if self.eat("scan") {
+                    Some(ScanCode {
+                        code: self.number("a scan code")?,
+                        extended: self.eat("extended"),
+                    })
+                } else {
+                    None
+                };
+
+                Action::Keyboard(key_code, state, scan_code)
+            }
+            "wait" => {
+                self.expect("for")?;
+
+                match self.peek_word() {
+                    Some("window") => Action::WaitForWindow(self.window()?),
+                    Some("image") => {
+                        self.next += 1;
+                        Action::WaitForImage(self.image(load_image)?)
+                    }
+                    Some("pixel") => {
+                        self.next += 1;
+                        Action::WaitForPixel(self.pixel()?)
+                    }
+                    Some("clipboard") => {
+                        self.next += 1;
+                        self.expect("change")?;
+                        Action::WaitForClipboardChange
+                    }
+                    _ => return Err(self.error("Expected image, pixel, window or clipboard")),
+                }
+            }
+            "if" => match self.peek_word() {
+                Some("image") => {
+                    self.next += 1;
+                    Action::IfImage(self.image(load_image)?)
+                }
+                Some("pixel") => {
+                    self.next += 1;
+                    Action::IfPixel(self.pixel()?)
+                }
+                Some("clipboard") => {
+                    self.next += 1;
+                    self.expect("matches")?;
+                    Action::IfClipboard(self.text("a regular expression")?)
+                }
+                _ => return Err(self.error("Expected image, pixel or clipboard")),
+            },
+            "else" => Action::Else,
+            "end" => match self.peek_word() {
+                Some("if") => {
+                    self.next += 1;
+                    Action::EndIf
+                }
+                Some("repeat") => {
+                    self.next += 1;
+                    Action::EndRepeat
+                }
+                _ => return Err(self.error("Expected if or repeat")),
+            },
+            "repeat" => {
+                if self.eat("forever") {
+                    Action::Repeat(0)
+                } else {
+                    Action::Repeat(self.number("a number of times or forever")?)
+                }
+            }
+            "break" => Action::Break,
+            "play" => Action::Play(PathBuf::from(self.text("a macro file")?)),
+            "launch" => {
+                let mut info = LaunchInfo {
+                    program: self.text("a program")?.into(),
+                    ..Default::default()
+                };
+
+                while let Some(option) = self.peek_word() {
+                    let column = self.column();
+
+                    match option {
+                        "with" => {
+                            self.next += 1;
+                            while let Some(Token::Text(arg)) = self.peek() {
+                                info.args.push(arg.clone());
+                                self.next += 1;
+                            }
+                        }
+                        "in" => {
+                            self.next += 1;
+                            info.working_dir = Some(self.text("a folder")?.into());
+                        }
+                        "wait" => {
+                            self.next += 1;
+                            info.wait_for_exit = true;
+                        }
+                        "timeout" => {
+                            self.next += 1;
+                            info.timeout = Some(self.number("a timeout in milliseconds")?);
+                        }
+                        option => {
+                            let message = format!("Unknown option \"{}\"", option);
+                            return Err(self.error_at(column, &message));
+                        }
+                    }
+                }
+
+                Action::Launch(info)
+            }
+            "activate" => Action::ActivateWindow(self.window()?),
+            "set" => {
+                self.expect("clipboard")?;
+                Action::SetClipboard(self.text("the clipboard text")?)
+            }
+            "copy" => {
+                self.expect("clipboard")?;
+                self.expect("to")?;
+                Action::CopyClipboard(self.text("a name")?)
+            }
+            "drag" => {
+                let button = self.button()?;
+                let from = self.position("from")?;
+                let to = self.position("to")?;
+                self.expect("over")?;
+                let duration = self.number("a duration in milliseconds")?;
+                self.expect("ms")?;
+
+                let mut path = vec![];
+                if self.eat("via") {
+                    path.push(self.point()?);
+                    while self.peek() == Some(&Token::Semicolon) {
+                        self.next += 1;
+                        path.push(self.point()?);
+                    }
+                }
+
+                Action::Drag(DragInfo {
+                    button,
+                    from,
+                    to,
+                    duration,
+                    path,
+                })
+            }
+            word => {
+                let message = format!("Unknown action \"{}\"", word);
+                return Err(self.error_at(column, &message));
+            }
+        })
+    }
+
+    /// Makes sure nothing is left on the line.
+    fn finish(&self) -> Result<(), ScriptError> {
+        match self.peek() {
+            Some(_) => Err(self.error("Expected the end of the line")),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,387 @@
+use super::Script;
+use crate::{
+    actions::*,
+    images::RawScreenshot,
+    keycodes_to_string::{key_code_to_string, string_to_key_code},
+    recorder::keymap::{VK_LBUTTON, VK_MBUTTON, VK_RBUTTON, VK_XBUTTON1, VK_XBUTTON2},
+};
+
+const INDENT: &str = "    ";
+
+/// Writes the actions as a script. Screenshots are named after the prefix and their number, such
+/// as `image1.png`.
+pub fn print_script<'a>(actions: &'a [Action], image_prefix: &str) -> Script<'a> {
+    let braced = braced_blocks(actions);
+    let mut printer = Printer {
+        text: String::new(),
+        depth: 0,
+        images: vec![],
+        image_prefix,
+    };
+
+    for (action, braced) in actions.iter().zip(braced) {
+        match (action, braced) {
+            (
+                Action::IfImage(_)
+                | Action::IfPixel(_)
+                | Action::IfClipboard(_)
+                | Action::Repeat(_),
+                true,
+            ) => {
+                let header = printer.action(action);
+                printer.line(&format!("{} {{", header));
+                printer.depth += 1;
+            }
+            (Action::Else, true) => {
+                printer.depth -= 1;
+                printer.line("} else {");
+                printer.depth += 1;
+            }
+            (Action::EndIf | Action::EndRepeat, true) => {
+                printer.depth -= 1;
+                printer.line("}");
+            }
+            _ => {
+                let line = printer.action(action);
+                printer.line(&line);
+            }
+        }
+    }
+
+    Script {
+        text: printer.text,
+        images: printer.images,
+    }
+}
+
+/// Which ifs, elses, repeats and ends are written as braces. Only blocks closed in the right
+/// order can be, everything else is written out as keywords so it reads back the same.
+pub fn braced_blocks(actions: &[Action]) -> Vec<bool> {
+    let is_if = |action: &Action| {
+        matches!(
+            action,
+            Action::IfImage(_) | Action::IfPixel(_) | Action::IfClipboard(_)
+        )
+    };
+    let mut braced = vec![false; actions.len()];
+    // Blocks that are still open, with the elses of ifs
+    let mut open: Vec<(usize, Vec<usize>)> = vec![];
+
+    for (index, action) in actions.iter().enumerate() {
+        match action {
+            Action::IfImage(_)
+            | Action::IfPixel(_)
+            | Action::IfClipboard(_)
+            | Action::Repeat(_) => open.push((index, vec![])),
+            Action::Else => {
+                if let Some((start, elses)) = open.last_mut() {
+                    if is_if(&actions[*start]) {
+                        elses.push(index);
+                    }
+                }
+            }
+            Action::EndIf | Action::EndRepeat => {
+                let closes = |start: usize| match action {
+                    Action::EndIf => is_if(&actions[start]),
+                    _ => matches!(actions[start], Action::Repeat(_)),
+                };
+
+                if matches!(open.last(), Some(&(start, _)) if closes(start)) {
+                    let (start, elses) = open.pop().unwrap();
+
+                    braced[start] = true;
+                    braced[index] = true;
+                    for index in elses {
+                        braced[index] = true;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    braced
+}
+
+struct Printer<'a, 'b> {
+    text: String,
+    depth: usize,
+    images: Vec<(String, &'a RawScreenshot)>,
+    image_prefix: &'b str,
+}
+
+impl<'a, 'b> Printer<'a, 'b> {
+    fn line(&mut self, line: &str) {
+        for _ in 0..self.depth {
+            self.text.push_str(INDENT);
+        }
+        self.text.push_str(line);
+        self.text.push('\n');
+    }
+
+    fn action(&mut self, action: &'a Action) -> String {
+        match action {
+            Action::Delay(delay, jitter) => match jitter {
+                DelayJitter::None => format!("delay {}", delay),
+                DelayJitter::Uniform(range) => format!("delay {} jitter uniform {}", delay, range),
+                DelayJitter::Normal(deviation) => {
+                    format!("delay {} jitter normal {}", delay, deviation)
+                }
+            },
+            Action::Mouse(kind) => match kind {
+                MouseActionKind::Moved(point) => format!("move {}", position(point, "to")),
+                MouseActionKind::Button(action_button) => format!(
+                    "{} {}{}",
+                    match action_button.state {
+                        MouseActionButtonState::Pressed => "press",
+                        MouseActionButtonState::Released => "release",
+                        MouseActionButtonState::Clicked => "click",
+                    },
+                    button(action_button.button),
+                    optional_position(&action_button.point)
+                ),
+                MouseActionKind::Wheel(amount, point) => {
+                    format!("wheel {}{}", amount, optional_position(point))
+                }
+                MouseActionKind::HorizontalWheel(amount, point) => {
+                    format!("hwheel {}{}", amount, optional_position(point))
+                }
+            },
+            Action::Keyboard(key_code, state, scan_code) => format!(
+                "key {} {}{}",
+                match state {
+                    KeyState::Down => "down",
+                    KeyState::Up => "up",
+                    KeyState::Pressed => "press",
+                },
+                key(*key_code),
+                match scan_code {
+                    Some(ScanCode {
+                        code,
+                        extended: false,
+                    }) => format!(" scan {}", code),
+                    Some(ScanCode {
+                        code,
+                        extended: true,
+                    }) => format!(" scan {} extended", code),
+                    None => String::new(),
+                }
+            ),
+            Action::WaitForImage(info) => format!("wait for image {}", self.image(info)),
+            Action::IfImage(info) => format!("if image {}", self.image(info)),
+            Action::WaitForPixel(info) => format!("wait for pixel {}", pixel(info)),
+            Action::IfPixel(info) => format!("if pixel {}", pixel(info)),
+            Action::Else => "else".into(),
+            Action::EndIf => "end if".into(),
+            Action::Repeat(0) => "repeat forever".into(),
+            Action::Repeat(amount) => format!("repeat {}", amount),
+            Action::EndRepeat => "end repeat".into(),
+            Action::Break => "break".into(),
+            Action::Play(path) => format!("play {}", quote(&path.to_string_lossy())),
+            Action::Launch(info) => {
+                let mut line = format!("launch {}", quote(&info.program.to_string_lossy()));
+
+                if !info.args.is_empty() {
+                    line += " with";
+                    for arg in &info.args {
+                        line += &format!(" {}", quote(arg));
+                    }
+                }
+                if let Some(working_dir) = &info.working_dir {
+                    line += &format!(" in {}", quote(&working_dir.to_string_lossy()));
+                }
+                if info.wait_for_exit {
+                    line += " wait";
+                }
+                if let Some(timeout) = info.timeout {
+                    line += &format!(" timeout {}", timeout);
+                }
+
+                line
+            }
+            Action::WaitForWindow(info) => format!("wait for {}", window(info)),
+            Action::ActivateWindow(info) => format!("activate {}", window(info)),
+            Action::MoveWindow(info, rect) => format!("move {} to {}", window(info), area(rect)),
+            Action::SetClipboard(text) => format!("set clipboard {}", quote(text)),
+            Action::CopyClipboard(name) => format!("copy clipboard to {}", quote(name)),
+            Action::WaitForClipboardChange => "wait for clipboard change".into(),
+            Action::IfClipboard(regex) => format!("if clipboard matches {}", quote(regex)),
+            Action::Drag(info) => {
+                let mut line = format!(
+                    "drag {} {} {} over {} ms",
+                    button(info.button),
+                    position(&info.from, "from"),
+                    position(&info.to, "to"),
+                    info.duration
+                );
+
+                if !info.path.is_empty() {
+                    let path: Vec<String> = info
+                        .path
+                        .iter()
+                        .map(|point| format!("{}, {}", point.x, point.y))
+                        .collect();
+                    line += &format!(" via {}", path.join("; "));
+                }
+
+                line
+            }
+        }
+    }
+
+    fn image(&mut self, info: &'a ImageInfo) -> String {
+        let mut options = match &info.screenshot_raw {
+            Some(screenshot) => {
+                let name = format!("{}{}.png", self.image_prefix, self.images.len() + 1);
+                let options = format!(
+                    "{} captured at {}, {}",
+                    quote(&name),
+                    screenshot.color.x,
+                    screenshot.color.y
+                );
+
+                self.images.push((name, &screenshot.color));
+                options
+            }
+            None => "none".into(),
+        };
+
+        options += &format!(" similarity {}", info.image_similarity);
+        if let Some((x, y)) = info.search_location_left_top {
+            options += &format!(" in {}, {}", x, y);
+        }
+        if let Some((width, height)) = info.search_location_width_height {
+            options += &format!(" size {}, {}", width, height);
+        }
+        options += &search_flags(
+            &info.search_anchor,
+            info.check_if_not_found,
+            info.move_mouse_if_found,
+        );
+
+        options
+    }
+}
+
+fn pixel(info: &PixelInfo) -> String {
+    let (red, green, blue) = info.color;
+    let (x, y) = info.search_location_left_top;
+    let (width, height) = info.search_location_width_height;
+
+    format!(
+        "color {}, {}, {} in {}, {} size {}, {}{}",
+        red,
+        green,
+        blue,
+        x,
+        y,
+        width,
+        height,
+        search_flags(
+            &info.search_anchor,
+            info.check_if_not_found,
+            info.move_mouse_if_found
+        )
+    )
+}
+
+fn search_flags(anchor: &SearchAnchor, check_if_not_found: bool, move_mouse: bool) -> String {
+    let mut flags = match anchor {
+        SearchAnchor::Screen => String::new(),
+        SearchAnchor::Window(info) => format!(" relative to {}", window(info)),
+        SearchAnchor::LastFoundImage => " relative to last image".into(),
+    };
+
+    if check_if_not_found {
+        flags += " not found";
+    }
+    if move_mouse {
+        flags += " move mouse";
+    }
+
+    flags
+}
+
+/// Offsets don't need the keyword, `by` already says what they are.
+fn position(point: &MousePointKind, keyword: &str) -> String {
+    match point {
+        MousePointKind::To(point) => format!("{} {}, {}", keyword, point.x, point.y),
+        MousePointKind::By(point) => format!("by {}, {}", point.x, point.y),
+        MousePointKind::Window(info, point) => {
+            format!("{} {}, {} in {}", keyword, point.x, point.y, window(info))
+        }
+        MousePointKind::Monitor(monitor, point) => format!(
+            "{} {}, {} on monitor {} {} scale {}{}",
+            keyword,
+            point.x,
+            point.y,
+            quote(&monitor.name),
+            area(&monitor.rect),
+            monitor.scale,
+            if monitor.primary { " primary" } else { "" }
+        ),
+    }
+}
+
+fn optional_position(point: &Option<MousePointKind>) -> String {
+    match point {
+        Some(point) => format!(" {}", position(point, "at")),
+        None => String::new(),
+    }
+}
+
+fn window(info: &WindowInfo) -> String {
+    format!("window {} class {}", quote(&info.title), quote(&info.class))
+}
+
+fn area(rect: &WindowRect) -> String {
+    format!(
+        "{}, {} size {}, {}",
+        rect.x, rect.y, rect.width, rect.height
+    )
+}
+
+fn button(button: i32) -> String {
+    match button {
+        VK_LBUTTON => "left".into(),
+        VK_RBUTTON => "right".into(),
+        VK_MBUTTON => "middle".into(),
+        VK_XBUTTON1 => "x1".into(),
+        VK_XBUTTON2 => "x2".into(),
+        _ => format!("button {}", button),
+    }
+}
+
+/// The key's name, which only needs quotes if it isn't a single word. Keys without a name of
+/// their own are written as their key code.
+fn key(key_code: i32) -> String {
+    let name = key_code_to_string(key_code);
+
+    if string_to_key_code(&name) != Some(key_code) {
+        key_code.to_string()
+    } else if name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        name.into_owned()
+    } else {
+        quote(&name)
+    }
+}
+
+fn quote(text: &str) -> String {
+    let mut quoted = String::from('"');
+
+    for c in text.chars() {
+        match c {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            '\n' => quoted += "\\n",
+            '\r' => quoted += "\\r",
+            '\t' => quoted += "\\t",
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
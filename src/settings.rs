@@ -4,7 +4,9 @@ use serde::*;
 
 use crate::{
     hotkeys::HotkeyMacro,
+    keys::Key,
     modals::{warning_window::DefaultErrorWindow, ModalWindow},
+    recorder::simplify::MouseSimplification,
 };
 
 pub const SETTINGS_FILE_NAME: &'static str = "fluffy-macro-recorder-settings.txt";
@@ -13,10 +15,79 @@ pub const SETTINGS_FILE_NAME: &'static str = "fluffy-macro-recorder-settings.txt
 pub struct Settings {
     pub record_mouse_movement: bool,
     pub record_mouse_offsets: bool,
+    /// How recorded mouse movement is thinned out when recording stops.
+    #[serde(default)]
+    pub mouse_simplification: MouseSimplification,
+    /// In milliseconds, for time sampling.
+    #[serde(default = "default_simplify_interval")]
+    pub simplify_interval: u32,
+    /// In pixels, for minimum distance and path tolerance.
+    #[serde(default = "default_simplify_tolerance")]
+    pub simplify_tolerance: f32,
+    #[serde(default)]
+    pub record_window_relative: bool,
+    /// Records positions relative to the monitor under the mouse along with its resolution and
+    /// scaling, so playback can place them again if the monitors change.
+    #[serde(default)]
+    pub record_monitor_relative: bool,
+    /// Records a button press, movement and release as one drag.
+    #[serde(default)]
+    pub record_drags: bool,
     pub playback_speed: f32,
     pub ignore_delays: bool,
+    /// Plays keys back by their scan code instead of their virtual-key code.
+    #[serde(default)]
+    pub keys_by_scan_code: bool,
     pub repeat_times: usize,
     pub hotkeys: Vec<HotkeyMacro>,
+    #[serde(default)]
+    pub humanize_mouse: bool,
+    /// In pixels per second.
+    #[serde(default = "default_humanize_mouse_speed")]
+    pub humanize_mouse_speed: f32,
+    /// Makes delay jitter and humanized mouse paths repeat between runs.
+    #[serde(default)]
+    pub random_seed: Option<u64>,
+    #[serde(default = "default_stop_hotkey")]
+    pub stop_hotkey: Vec<i32>,
+    #[serde(default = "default_play_hotkey")]
+    pub play_hotkey: Vec<i32>,
+    #[serde(default = "default_record_hotkey")]
+    pub record_hotkey: Vec<i32>,
+    /// Pauses and resumes recording.
+    #[serde(default = "default_pause_hotkey")]
+    pub pause_hotkey: Vec<i32>,
+    /// Written into macros saved without an author.
+    #[serde(default)]
+    pub author: String,
+}
+
+fn default_simplify_interval() -> u32 {
+    50
+}
+
+fn default_simplify_tolerance() -> f32 {
+    2.0
+}
+
+fn default_humanize_mouse_speed() -> f32 {
+    2000.0
+}
+
+fn default_stop_hotkey() -> Vec<i32> {
+    vec![Key::Control.to_vk(), Key::Letter('Q').to_vk()]
+}
+
+fn default_play_hotkey() -> Vec<i32> {
+    vec![Key::Control.to_vk(), Key::Letter('P').to_vk()]
+}
+
+fn default_record_hotkey() -> Vec<i32> {
+    vec![Key::Control.to_vk(), Key::Letter('R').to_vk()]
+}
+
+fn default_pause_hotkey() -> Vec<i32> {
+    vec![Key::Control.to_vk(), Key::Function(8).to_vk()]
 }
 
 impl Default for Settings {
@@ -24,10 +95,25 @@ impl Default for Settings {
         Self {
             record_mouse_movement: true,
             record_mouse_offsets: false,
+            mouse_simplification: MouseSimplification::None,
+            simplify_interval: default_simplify_interval(),
+            simplify_tolerance: default_simplify_tolerance(),
+            record_window_relative: false,
+            record_monitor_relative: false,
+            record_drags: false,
             playback_speed: 1.0,
             ignore_delays: false,
+            keys_by_scan_code: false,
             repeat_times: 1,
             hotkeys: vec![],
+            humanize_mouse: false,
+            humanize_mouse_speed: default_humanize_mouse_speed(),
+            random_seed: None,
+            stop_hotkey: default_stop_hotkey(),
+            play_hotkey: default_play_hotkey(),
+            record_hotkey: default_record_hotkey(),
+            pause_hotkey: default_pause_hotkey(),
+            author: String::new(),
         }
     }
 }
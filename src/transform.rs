@@ -0,0 +1,479 @@
+use std::fmt::Display;
+
+use strum_macros::EnumIter;
+
+use crate::{
+    actions::{
+        Action, Action::*, DelayJitter, MonitorInfo, MouseActionButton, MouseActionKind::*,
+        MousePointKind, Point,
+    },
+    monitors::{monitor_at, remap_point},
+    window_manager::{exact_window_info, WindowManager},
+};
+
+/// The kinds of mouse positions a macro can be rewritten to.
+#[derive(PartialEq, Eq, EnumIter, Clone, Copy, Debug)]
+pub enum PointMode {
+    Absolute,
+    Relative,
+    Window,
+    Monitor,
+}
+
+impl Display for PointMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Absolute => "Absolute",
+                Self::Relative => "Relative",
+                Self::Window => "Window Relative",
+                Self::Monitor => "Monitor Relative",
+            }
+        )
+    }
+}
+
+/// Adds up delays that follow each other, keeping the first jitter that's set.
+pub fn merge_adjacent_delays(actions: &mut Vec<Action>) {
+    let mut merged: Vec<Action> = vec![];
+
+    for action in actions.drain(..) {
+        match (merged.last_mut(), &action) {
+            (Some(Delay(previous, previous_jitter)), Delay(delay, jitter)) => {
+                *previous += delay;
+                if *previous_jitter == DelayJitter::None {
+                    *previous_jitter = *jitter;
+                }
+            }
+            _ => merged.push(action),
+        }
+    }
+
+    *actions = merged;
+}
+
+/// Keeps every delay within `min..=max` milliseconds.
+pub fn clamp_delays(actions: &mut [Action], min: u32, max: u32) {
+    for action in actions {
+        if let Delay(delay, _) = action {
+            *delay = (*delay).clamp(min, max.max(min));
+        }
+    }
+}
+
+/// Multiplies every delay, 0.5 plays the delays twice as fast.
+pub fn scale_delays(actions: &mut [Action], factor: f32) {
+    for action in actions {
+        if let Delay(delay, _) = action {
+            *delay = (*delay as f32 * factor.max(0.0)).round() as u32;
+        }
+    }
+}
+
+/// Removes delays shorter than `min` milliseconds.
+pub fn drop_short_delays(actions: &mut Vec<Action>, min: u32) {
+    actions.retain(|action| !matches!(action, Delay(delay, _) if *delay < min));
+}
+
+/// Removes mouse movement that doesn't go anywhere, and moves that are immediately followed by
+/// another move. Offsets right after each other are added up into one.
+pub fn remove_redundant_moves(actions: &mut Vec<Action>) {
+    let mut kept: Vec<Action> = vec![];
+
+    for action in actions.drain(..) {
+        let Mouse(Moved(point)) = &action else {
+            kept.push(action);
+            continue;
+        };
+
+        if let MousePointKind::By(Point { x: 0, y: 0 }) = point {
+            continue;
+        }
+
+        match (kept.last_mut(), point) {
+            (Some(Mouse(Moved(MousePointKind::By(previous)))), MousePointKind::By(by)) => {
+                *previous = Point {
+                    x: previous.x + by.x,
+                    y: previous.y + by.y,
+                };
+                if *previous == (Point { x: 0, y: 0 }) {
+                    kept.pop();
+                }
+            }
+            // Moving somewhere absolute makes the move right before it pointless
+            (
+                Some(Mouse(Moved(
+                    MousePointKind::To(_)
+                    | MousePointKind::Window(..)
+                    | MousePointKind::Monitor(..),
+                ))),
+                MousePointKind::To(_) | MousePointKind::Window(..) | MousePointKind::Monitor(..),
+            ) => *kept.last_mut().unwrap() = action,
+            _ => {
+                if !is_same_move(&kept, point) {
+                    kept.push(action);
+                }
+            }
+        }
+    }
+
+    *actions = kept;
+}
+
+/// Whether the last move before `point`, with only delays in between, goes to the same place.
+fn is_same_move(actions: &[Action], point: &MousePointKind) -> bool {
+    let previous = actions
+        .iter()
+        .rev()
+        .find(|action| !matches!(action, Delay(..)));
+
+    match (previous, point) {
+        (Some(Mouse(Moved(MousePointKind::To(previous)))), MousePointKind::To(point)) => {
+            previous == point
+        }
+        (
+            Some(Mouse(Moved(MousePointKind::Window(previous_window, previous)))),
+            MousePointKind::Window(window, point),
+        ) => {
+            previous == point
+                && previous_window.title == window.title
+                && previous_window.class == window.class
+        }
+        (
+            Some(Mouse(Moved(MousePointKind::Monitor(previous_monitor, previous)))),
+            MousePointKind::Monitor(monitor, point),
+        ) => previous == point && previous_monitor == monitor,
+        _ => false,
+    }
+}
+
+/// Rewrites every mouse position to the mode, following the cursor from `start`. Window and
+/// monitor positions are worked out from the windows open and monitors connected now, points that
+/// can't be placed on the screen are left as they are.
+pub fn convert_points(
+    actions: &mut [Action],
+    mode: PointMode,
+    start: Point,
+    window_manager: Option<&dyn WindowManager>,
+    monitors: &[MonitorInfo],
+) {
+    let mut cursor = Some(start);
+
+    for point in actions.iter_mut().flat_map(mouse_points_mut) {
+        let position = screen_position(point, cursor, window_manager, monitors);
+
+        let converted = match (mode, position) {
+            (PointMode::Absolute, Some(position)) => Some(MousePointKind::To(position)),
+            (PointMode::Relative, Some(position)) => cursor.map(|cursor| {
+                MousePointKind::By(Point {
+                    x: position.x - cursor.x,
+                    y: position.y - cursor.y,
+                })
+            }),
+            (PointMode::Window, Some(position)) => window_manager.and_then(|window_manager| {
+                let window = window_manager.window_at(position.x, position.y)?;
+                let client_rect = window_manager.client_rect(window.id).ok()?;
+
+                Some(MousePointKind::Window(
+                    exact_window_info(&window),
+                    Point {
+                        x: position.x - client_rect.x,
+                        y: position.y - client_rect.y,
+                    },
+                ))
+            }),
+            (PointMode::Monitor, Some(position)) => monitor_at(monitors, position).map(|monitor| {
+                MousePointKind::Monitor(
+                    monitor.clone(),
+                    Point {
+                        x: position.x - monitor.rect.x,
+                        y: position.y - monitor.rect.y,
+                    },
+                )
+            }),
+            (_, None) => None,
+        };
+
+        if let Some(converted) = converted {
+            *point = converted;
+        }
+        cursor = position;
+    }
+}
+
+/// Where the cursor is after the actions, if it can be told without playing them.
+pub fn cursor_after(
+    actions: &[Action],
+    start: Point,
+    window_manager: Option<&dyn WindowManager>,
+    monitors: &[MonitorInfo],
+) -> Option<Point> {
+    let mut cursor = Some(start);
+
+    // A known position after an unknown one places the cursor again
+    for point in actions.iter().flat_map(mouse_points) {
+        cursor = screen_position(point, cursor, window_manager, monitors);
+    }
+
+    cursor
+}
+
+fn screen_position(
+    point: &MousePointKind,
+    cursor: Option<Point>,
+    window_manager: Option<&dyn WindowManager>,
+    monitors: &[MonitorInfo],
+) -> Option<Point> {
+    match point {
+        MousePointKind::To(point) => Some(*point),
+        MousePointKind::By(by) => cursor.map(|cursor| Point {
+            x: cursor.x + by.x,
+            y: cursor.y + by.y,
+        }),
+        MousePointKind::Window(window_info, point) => {
+            let window_manager = window_manager?;
+            let window = window_manager.find_window(window_info).ok().flatten()?;
+            let client_rect = window_manager.client_rect(window.id).ok()?;
+
+            Some(Point {
+                x: client_rect.x + point.x,
+                y: client_rect.y + point.y,
+            })
+        }
+        MousePointKind::Monitor(monitor, point) => Some(remap_point(monitor, *point, monitors).0),
+    }
+}
+
+/// The positions an action moves the cursor to, in order. Playback moves the cursor to every one
+/// of them, offsets included, so following them tells where it ends up.
+pub fn mouse_points(action: &Action) -> Vec<&MousePointKind> {
+    match action {
+        Mouse(Moved(point) | Wheel(_, Some(point)) | HorizontalWheel(_, Some(point))) => {
+            vec![point]
+        }
+        Mouse(Button(MouseActionButton {
+            point: Some(point), ..
+        })) => vec![point],
+        Drag(drag_info) => vec![&drag_info.from, &drag_info.to],
+        _ => vec![],
+    }
+}
+
+// A drag's path is relative to where it starts, so it doesn't need converting
+fn mouse_points_mut(action: &mut Action) -> Vec<&mut MousePointKind> {
+    match action {
+        Mouse(Moved(point) | Wheel(_, Some(point)) | HorizontalWheel(_, Some(point))) => {
+            vec![point]
+        }
+        Mouse(Button(MouseActionButton {
+            point: Some(point), ..
+        })) => vec![point],
+        Drag(drag_info) => vec![&mut drag_info.from, &mut drag_info.to],
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delay(milliseconds: u32) -> Action {
+        Delay(milliseconds, DelayJitter::None)
+    }
+
+    fn move_to(x: i32, y: i32) -> Action {
+        Mouse(Moved(MousePointKind::To(Point { x, y })))
+    }
+
+    fn move_by(x: i32, y: i32) -> Action {
+        Mouse(Moved(MousePointKind::By(Point { x, y })))
+    }
+
+    fn delays(actions: &[Action]) -> Vec<u32> {
+        actions
+            .iter()
+            .filter_map(|action| match action {
+                Delay(delay, _) => Some(*delay),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn describe(actions: &[Action]) -> Vec<String> {
+        actions
+            .iter()
+            .map(|action| format!("{:?}", action))
+            .collect()
+    }
+
+    #[test]
+    fn merges_adjacent_delays() {
+        let mut actions = vec![
+            delay(5),
+            Delay(10, DelayJitter::Uniform(3)),
+            delay(1),
+            move_to(1, 1),
+            delay(2),
+        ];
+
+        merge_adjacent_delays(&mut actions);
+
+        assert_eq!(
+            describe(&actions),
+            describe(&[Delay(16, DelayJitter::Uniform(3)), move_to(1, 1), delay(2)])
+        );
+    }
+
+    #[test]
+    fn clamps_delays() {
+        let mut actions = vec![delay(1), delay(50), delay(5000), move_to(0, 0)];
+
+        clamp_delays(&mut actions, 10, 1000);
+
+        assert_eq!(delays(&actions), vec![10, 50, 1000]);
+    }
+
+    #[test]
+    fn scales_delays() {
+        let mut actions = vec![delay(10), move_to(0, 0), delay(25)];
+
+        scale_delays(&mut actions, 0.5);
+
+        assert_eq!(delays(&actions), vec![5, 13]);
+    }
+
+    #[test]
+    fn scales_delays_in_a_selection() {
+        let mut actions = vec![delay(10), delay(20), delay(30)];
+
+        scale_delays(&mut actions[1..], 2.0);
+
+        assert_eq!(delays(&actions), vec![10, 40, 60]);
+    }
+
+    #[test]
+    fn drops_short_delays() {
+        let mut actions = vec![delay(1), move_to(0, 0), delay(15), delay(14)];
+
+        drop_short_delays(&mut actions, 15);
+
+        assert_eq!(describe(&actions), describe(&[move_to(0, 0), delay(15)]));
+    }
+
+    #[test]
+    fn removes_moves_followed_by_other_moves() {
+        let mut actions = vec![move_to(0, 0), move_to(5, 5), delay(10), move_to(9, 9)];
+
+        remove_redundant_moves(&mut actions);
+
+        assert_eq!(
+            describe(&actions),
+            describe(&[move_to(5, 5), delay(10), move_to(9, 9)])
+        );
+    }
+
+    #[test]
+    fn removes_moves_to_the_same_place() {
+        let mut actions = vec![move_to(3, 3), delay(10), move_to(3, 3), move_by(0, 0)];
+
+        remove_redundant_moves(&mut actions);
+
+        assert_eq!(describe(&actions), describe(&[move_to(3, 3), delay(10)]));
+    }
+
+    #[test]
+    fn adds_up_offsets() {
+        let mut actions = vec![
+            move_by(1, 2),
+            move_by(3, 4),
+            delay(10),
+            move_by(1, 0),
+            move_by(-1, 0),
+        ];
+
+        remove_redundant_moves(&mut actions);
+
+        assert_eq!(describe(&actions), describe(&[move_by(4, 6), delay(10)]));
+    }
+
+    #[test]
+    fn converts_positions_to_offsets_and_back() {
+        let start = Point { x: 10, y: 10 };
+        let absolute = vec![move_to(15, 10), delay(10), move_to(15, 30), move_to(0, 0)];
+        let mut actions = absolute.clone();
+
+        convert_points(&mut actions, PointMode::Relative, start, None, &[]);
+
+        assert_eq!(
+            describe(&actions),
+            describe(&[move_by(5, 0), delay(10), move_by(0, 20), move_by(-15, -30)])
+        );
+
+        convert_points(&mut actions, PointMode::Absolute, start, None, &[]);
+
+        assert_eq!(describe(&actions), describe(&absolute));
+        assert_eq!(
+            cursor_after(&actions[..2], start, None, &[]),
+            Some(Point { x: 15, y: 10 })
+        );
+    }
+
+    #[test]
+    fn leaves_positions_it_cant_place() {
+        let window_move = Mouse(Moved(MousePointKind::Window(
+            Default::default(),
+            Point { x: 1, y: 1 },
+        )));
+        let mut actions = vec![window_move.clone(), move_by(5, 5), move_to(2, 2)];
+
+        convert_points(
+            &mut actions,
+            PointMode::Absolute,
+            Point { x: 0, y: 0 },
+            None,
+            &[],
+        );
+
+        assert_eq!(
+            describe(&actions),
+            describe(&[window_move, move_by(5, 5), move_to(2, 2)])
+        );
+    }
+
+    #[test]
+    fn converts_positions_onto_monitors() {
+        let monitor = MonitorInfo {
+            name: "DISPLAY2".into(),
+            rect: crate::actions::WindowRect {
+                x: -1280,
+                y: 0,
+                width: 1280,
+                height: 1024,
+            },
+            scale: 1.0,
+            primary: false,
+        };
+        let mut actions = vec![move_to(-1000, 20), move_to(5000, 5000)];
+
+        convert_points(
+            &mut actions,
+            PointMode::Monitor,
+            Point { x: 0, y: 0 },
+            None,
+            std::slice::from_ref(&monitor),
+        );
+
+        assert_eq!(
+            describe(&actions),
+            describe(&[
+                Mouse(Moved(MousePointKind::Monitor(
+                    monitor,
+                    Point { x: 280, y: 20 }
+                ))),
+                move_to(5000, 5000)
+            ])
+        );
+    }
+}
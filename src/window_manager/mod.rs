@@ -0,0 +1,129 @@
+use std::error::Error;
+
+use regex::Regex;
+
+use crate::actions::{WindowInfo, WindowRect};
+
+#[cfg(windows)]
+mod win32;
+#[cfg(target_os = "linux")]
+mod x11;
+
+#[derive(Clone, Debug)]
+pub struct WindowDescription {
+    pub id: u64,
+    pub title: String,
+    pub class: String,
+}
+
+pub trait WindowManager {
+    /// Visible top level windows, topmost first.
+    fn windows(&self) -> Result<Vec<WindowDescription>, Box<dyn Error>>;
+
+    fn rect(&self, id: u64) -> Result<WindowRect, Box<dyn Error>>;
+
+    /// The area inside the window's frame and title bar, in screen coordinates.
+    fn client_rect(&self, id: u64) -> Result<WindowRect, Box<dyn Error>>;
+
+    fn activate(&self, id: u64) -> Result<(), Box<dyn Error>>;
+
+    fn set_rect(&self, id: u64, rect: WindowRect) -> Result<(), Box<dyn Error>>;
+
+    /// The topmost window matching the info, None if there isn't one. Fails if the title or class
+    /// isn't a valid regular expression.
+    fn find_window(&self, info: &WindowInfo) -> Result<Option<WindowDescription>, Box<dyn Error>> {
+        let title = Regex::new(&info.title)
+            .map_err(|error| format!("Invalid window title regex: {}", error))?;
+        let class = Regex::new(&info.class)
+            .map_err(|error| format!("Invalid window class regex: {}", error))?;
+
+        Ok(self
+            .windows()?
+            .into_iter()
+            .find(|window| title.is_match(&window.title) && class.is_match(&window.class)))
+    }
+
+    fn window_at(&self, x: i32, y: i32) -> Option<WindowDescription> {
+        self.windows()
+            .ok()?
+            .into_iter()
+            .find(|window| matches!(self.rect(window.id), Ok(rect) if rect.contains(x, y)))
+    }
+}
+
+/// Builds a `WindowInfo` that only matches windows with exactly this title and class.
+pub fn exact_window_info(window: &WindowDescription) -> WindowInfo {
+    WindowInfo {
+        title: format!("^{}$", regex::escape(&window.title)),
+        class: format!("^{}$", regex::escape(&window.class)),
+    }
+}
+
+#[cfg(windows)]
+pub fn platform_window_manager() -> Result<Box<dyn WindowManager>, Box<dyn Error>> {
+    Ok(Box::new(win32::Win32WindowManager))
+}
+
+#[cfg(target_os = "linux")]
+pub fn platform_window_manager() -> Result<Box<dyn WindowManager>, Box<dyn Error>> {
+    Ok(Box::new(x11::X11WindowManager::connect()?))
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn platform_window_manager() -> Result<Box<dyn WindowManager>, Box<dyn Error>> {
+    Err("Window actions aren't supported on this platform".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeWindowManager(Vec<WindowDescription>);
+
+    impl WindowManager for FakeWindowManager {
+        fn windows(&self) -> Result<Vec<WindowDescription>, Box<dyn Error>> {
+            Ok(self.0.clone())
+        }
+
+        fn rect(&self, _id: u64) -> Result<WindowRect, Box<dyn Error>> {
+            Ok(Default::default())
+        }
+
+        fn client_rect(&self, _id: u64) -> Result<WindowRect, Box<dyn Error>> {
+            Ok(Default::default())
+        }
+
+        fn activate(&self, _id: u64) -> Result<(), Box<dyn Error>> {
+            Ok(())
+        }
+
+        fn set_rect(&self, _id: u64, _rect: WindowRect) -> Result<(), Box<dyn Error>> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn finds_windows_by_regex() {
+        let window_manager = FakeWindowManager(vec![WindowDescription {
+            id: 1,
+            title: "notes.txt - Notepad".into(),
+            class: "Notepad".into(),
+        }]);
+        let window_info = |title: &str, class: &str| WindowInfo {
+            title: title.into(),
+            class: class.into(),
+        };
+
+        let found = window_manager
+            .find_window(&window_info("Notepad$", ""))
+            .unwrap();
+        assert_eq!(found.map(|window| window.id), Some(1));
+        assert!(window_manager
+            .find_window(&window_info("Paint", ""))
+            .unwrap()
+            .is_none());
+        assert!(window_manager
+            .find_window(&window_info("", "Note(pad"))
+            .is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use std::{error::Error, mem::zeroed, ptr::null_mut};
+
+use winapi::{
+    shared::{
+        minwindef::{BOOL, LPARAM, TRUE},
+        windef::{HWND, POINT, RECT},
+    },
+    um::winuser::*,
+};
+
+use super::{WindowDescription, WindowManager};
+use crate::actions::WindowRect;
+
+pub struct Win32WindowManager;
+
+unsafe extern "system" fn collect_window(hwnd: HWND, l_param: LPARAM) -> BOOL {
+    let windows = &mut *(l_param as *mut Vec<HWND>);
+
+    if IsWindowVisible(hwnd) != 0 {
+        windows.push(hwnd);
+    }
+
+    TRUE
+}
+
+fn window_title(hwnd: HWND) -> String {
+    let mut buffer = [0u16; 512];
+    let length = unsafe { GetWindowTextW(hwnd, buffer.as_mut_ptr(), buffer.len() as i32) };
+    String::from_utf16_lossy(&buffer[..length.max(0) as usize])
+}
+
+fn window_class(hwnd: HWND) -> String {
+    let mut buffer = [0u16; 256];
+    let length = unsafe { GetClassNameW(hwnd, buffer.as_mut_ptr(), buffer.len() as i32) };
+    String::from_utf16_lossy(&buffer[..length.max(0) as usize])
+}
+
+fn to_hwnd(id: u64) -> HWND {
+    id as usize as HWND
+}
+
+impl WindowManager for Win32WindowManager {
+    fn windows(&self) -> Result<Vec<WindowDescription>, Box<dyn Error>> {
+        let mut handles: Vec<HWND> = vec![];
+
+        // EnumWindows goes through top level windows in z order, topmost first
+        unsafe {
+            EnumWindows(
+                Some(collect_window),
+                &mut handles as *mut Vec<HWND> as LPARAM,
+            )
+        };
+
+        Ok(handles
+            .into_iter()
+            .map(|hwnd| WindowDescription {
+                id: hwnd as usize as u64,
+                title: window_title(hwnd),
+                class: window_class(hwnd),
+            })
+            .collect())
+    }
+
+    fn rect(&self, id: u64) -> Result<WindowRect, Box<dyn Error>> {
+        let mut rect: RECT = unsafe { zeroed() };
+
+        if unsafe { GetWindowRect(to_hwnd(id), &mut rect) } == 0 {
+            return Err("Window no longer exists".into());
+        }
+
+        Ok(WindowRect {
+            x: rect.left,
+            y: rect.top,
+            width: rect.right - rect.left,
+            height: rect.bottom - rect.top,
+        })
+    }
+
+    fn client_rect(&self, id: u64) -> Result<WindowRect, Box<dyn Error>> {
+        let hwnd = to_hwnd(id);
+        let mut rect: RECT = unsafe { zeroed() };
+        let mut origin = POINT { x: 0, y: 0 };
+
+        if unsafe { GetClientRect(hwnd, &mut rect) == 0 || ClientToScreen(hwnd, &mut origin) == 0 }
+        {
+            return Err("Window no longer exists".into());
+        }
+
+        Ok(WindowRect {
+            x: origin.x,
+            y: origin.y,
+            width: rect.right - rect.left,
+            height: rect.bottom - rect.top,
+        })
+    }
+
+    fn activate(&self, id: u64) -> Result<(), Box<dyn Error>> {
+        let hwnd = to_hwnd(id);
+
+        unsafe {
+            if IsIconic(hwnd) != 0 {
+                ShowWindow(hwnd, SW_RESTORE);
+            }
+
+            if SetForegroundWindow(hwnd) == 0 {
+                return Err("Windows refused to bring the window to the foreground".into());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn set_rect(&self, id: u64, rect: WindowRect) -> Result<(), Box<dyn Error>> {
+        let hwnd = to_hwnd(id);
+
+        unsafe {
+            if IsIconic(hwnd) != 0 || IsZoomed(hwnd) != 0 {
+                ShowWindow(hwnd, SW_RESTORE);
+            }
+
+            if SetWindowPos(
+                hwnd,
+                null_mut(),
+                rect.x,
+                rect.y,
+                rect.width,
+                rect.height,
+                SWP_NOZORDER | SWP_NOACTIVATE,
+            ) == 0
+            {
+                return Err("Failed to move the window".into());
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,217 @@
+use std::error::Error;
+
+use x11rb::{
+    connection::Connection,
+    protocol::xproto::{AtomEnum, ClientMessageEvent, ConnectionExt, EventMask, Window},
+    rust_connection::RustConnection,
+    CURRENT_TIME,
+};
+
+use super::{WindowDescription, WindowManager};
+use crate::actions::WindowRect;
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        _NET_CLIENT_LIST_STACKING,
+        _NET_ACTIVE_WINDOW,
+        _NET_MOVERESIZE_WINDOW,
+        _NET_WM_NAME,
+        UTF8_STRING,
+    }
+}
+
+// EWMH source indication for pagers, which window managers trust more than applications
+const SOURCE_PAGER: u32 = 2;
+
+/// Talks to an EWMH compliant window manager.
+pub struct X11WindowManager {
+    connection: RustConnection,
+    root: Window,
+    atoms: Atoms,
+}
+
+impl X11WindowManager {
+    pub fn connect() -> Result<Self, Box<dyn Error>> {
+        let (connection, screen_num) = x11rb::connect(None)?;
+        let root = connection.setup().roots[screen_num].root;
+        let atoms = Atoms::new(&connection)?.reply()?;
+
+        Ok(Self {
+            connection,
+            root,
+            atoms,
+        })
+    }
+
+    fn title(&self, window: Window) -> Result<String, Box<dyn Error>> {
+        let reply = self
+            .connection
+            .get_property(
+                false,
+                window,
+                self.atoms._NET_WM_NAME,
+                self.atoms.UTF8_STRING,
+                0,
+                u32::MAX,
+            )?
+            .reply()?;
+
+        if !reply.value.is_empty() {
+            return Ok(String::from_utf8_lossy(&reply.value).into_owned());
+        }
+
+        let reply = self
+            .connection
+            .get_property(
+                false,
+                window,
+                AtomEnum::WM_NAME,
+                AtomEnum::STRING,
+                0,
+                u32::MAX,
+            )?
+            .reply()?;
+
+        Ok(String::from_utf8_lossy(&reply.value).into_owned())
+    }
+
+    fn class(&self, window: Window) -> Result<String, Box<dyn Error>> {
+        let reply = self
+            .connection
+            .get_property(
+                false,
+                window,
+                AtomEnum::WM_CLASS,
+                AtomEnum::STRING,
+                0,
+                u32::MAX,
+            )?
+            .reply()?;
+
+        Ok(class_name(&reply.value))
+    }
+
+    fn send_root_message(
+        &self,
+        window: Window,
+        message_type: u32,
+        data: [u32; 5],
+    ) -> Result<(), Box<dyn Error>> {
+        let event = ClientMessageEvent::new(32, window, message_type, data);
+
+        self.connection.send_event(
+            false,
+            self.root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        )?;
+        self.connection.flush()?;
+
+        Ok(())
+    }
+}
+
+impl WindowManager for X11WindowManager {
+    fn windows(&self) -> Result<Vec<WindowDescription>, Box<dyn Error>> {
+        let reply = self
+            .connection
+            .get_property(
+                false,
+                self.root,
+                self.atoms._NET_CLIENT_LIST_STACKING,
+                AtomEnum::WINDOW,
+                0,
+                u32::MAX,
+            )?
+            .reply()?;
+
+        let mut windows = reply
+            .value32()
+            .ok_or("The window manager doesn't provide _NET_CLIENT_LIST_STACKING")?
+            .collect::<Vec<_>>();
+
+        // The stacking list goes from the bottom to the top
+        windows.reverse();
+
+        // Windows can close while the list is read, those are left out instead of failing the list
+        Ok(windows
+            .into_iter()
+            .filter_map(|window| {
+                Some(WindowDescription {
+                    id: window as u64,
+                    title: self.title(window).ok()?,
+                    class: self.class(window).ok()?,
+                })
+            })
+            .collect())
+    }
+
+    fn rect(&self, id: u64) -> Result<WindowRect, Box<dyn Error>> {
+        let window = id as Window;
+        let geometry = self.connection.get_geometry(window)?.reply()?;
+        let position = self
+            .connection
+            .translate_coordinates(window, self.root, 0, 0)?
+            .reply()?;
+
+        Ok(WindowRect {
+            x: position.dst_x as i32,
+            y: position.dst_y as i32,
+            width: geometry.width as i32,
+            height: geometry.height as i32,
+        })
+    }
+
+    fn client_rect(&self, id: u64) -> Result<WindowRect, Box<dyn Error>> {
+        // Client windows are reparented into frames, so their own geometry excludes decorations
+        self.rect(id)
+    }
+
+    fn activate(&self, id: u64) -> Result<(), Box<dyn Error>> {
+        self.send_root_message(
+            id as Window,
+            self.atoms._NET_ACTIVE_WINDOW,
+            [SOURCE_PAGER, CURRENT_TIME, 0, 0, 0],
+        )
+    }
+
+    fn set_rect(&self, id: u64, rect: WindowRect) -> Result<(), Box<dyn Error>> {
+        // North west gravity, with the x, y, width and height flags set
+        let flags = 1 | 0b1111 << 8 | SOURCE_PAGER << 12;
+
+        self.send_root_message(
+            id as Window,
+            self.atoms._NET_MOVERESIZE_WINDOW,
+            [
+                flags,
+                rect.x as u32,
+                rect.y as u32,
+                rect.width as u32,
+                rect.height as u32,
+            ],
+        )
+    }
+}
+
+/// The class name of a WM_CLASS property, which is the instance name followed by the class name,
+/// both null terminated.
+fn class_name(wm_class: &[u8]) -> String {
+    String::from_utf8_lossy(wm_class)
+        .split('\0')
+        .nth(1)
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_class_name_of_wm_class() {
+        assert_eq!(class_name(b"xterm\0XTerm\0"), "XTerm");
+        assert_eq!(class_name(b"navigator\0firefox\0"), "firefox");
+        assert_eq!(class_name(b"instance-only\0"), "");
+        assert_eq!(class_name(b""), "");
+    }
+}